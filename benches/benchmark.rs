@@ -1,50 +1,110 @@
-use std::fs;
-
-use advent_of_code_2024::days::DAYS;
+use advent_of_code_2024::days::{input::default_data_dir, Day, InputSource, Params, DAYS};
 use criterion::{criterion_group, criterion_main, Criterion};
 
+/// Every day that has an input available, paired with that input
+fn days_with_input() -> Vec<(&'static Day, String)> {
+    let source = InputSource::DataDir(default_data_dir());
+    DAYS.iter()
+        .filter_map(|day| day.load_input(&source).ok().map(|input| (day, input)))
+        .collect()
+}
+
 fn day_benches(c: &mut Criterion) {
     let mut group = c.benchmark_group("days");
-    DAYS.iter().for_each(|day| {
+    days_with_input().iter().for_each(|(day, input)| {
+        let implementation = day.default_implementation();
+        group.bench_function(format!("{}_parse", day.name).as_str(), |b| {
+            b.iter(|| day.parse(implementation, input).is_ok());
+        });
+        let Ok(parsed) = day.parse(implementation, input) else {
+            return;
+        };
+        let params = Params::defaults(implementation.params);
         group.bench_function(format!("{}_part1", day.name).as_str(), |b| {
-            b.iter(|| day.part1());
+            b.iter(|| day.part1(&*parsed, &params));
         });
         group.bench_function(format!("{}_part2", day.name).as_str(), |b| {
-            b.iter(|| day.part2());
+            b.iter(|| day.part2(&*parsed, &params));
         });
     });
     group.finish();
 }
 
-fn d05_benches(c: &mut Criterion) {
-    let mut d05 = c.benchmark_group("d05");
-
-    use advent_of_code_2024::days::d05;
-    let input = fs::read_to_string("./data/d05.txt").unwrap();
-    d05.bench_function("part1", |b| b.iter(|| d05::part1(input.as_str())));
-    d05.bench_function("part2", |b| b.iter(|| d05::part2(input.as_str())));
-    d05.bench_function("old_part1", |b| b.iter(|| d05::old::part1(input.as_str())));
-    d05.bench_function("old_part2", |b| b.iter(|| d05::old::part2(input.as_str())));
+/// Each example from parsing to the parts it has answers for, which needs no real inputs
+fn example_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("examples");
+    for day in DAYS.iter() {
+        let implementation = day.default_implementation();
+        for (i, example) in day.examples.iter().enumerate() {
+            let Ok(params) = implementation.example_params(example) else {
+                continue;
+            };
+            let input = example.input.trim();
+            group.bench_function(format!("{}_example{}", day.name, i + 1).as_str(), |b| {
+                b.iter(|| {
+                    let parsed = day.parse(implementation, input).unwrap();
+                    if example.part1.is_some() {
+                        let _ = day.part1(&*parsed, &params);
+                    }
+                    if example.part2.is_some() {
+                        let _ = day.part2(&*parsed, &params);
+                    }
+                })
+            });
+        }
+    }
+    group.finish();
+}
 
-    d05.finish()
+/// Every implementation of each day that has more than one, against each other. Parsed forms differ
+/// between implementations, so each part is timed from the input.
+fn implementation_benches(c: &mut Criterion) {
+    for (day, input) in days_with_input() {
+        if day.implementations.len() < 2 {
+            continue;
+        }
+        let mut group = c.benchmark_group(format!("{}_implementations", day.name));
+        for implementation in day.implementations {
+            let params = Params::defaults(implementation.params);
+            group.bench_function(format!("{}_part1", implementation.name).as_str(), |b| {
+                b.iter(|| {
+                    let parsed = day.parse(implementation, &input).unwrap();
+                    day.part1(&*parsed, &params)
+                })
+            });
+            group.bench_function(format!("{}_part2", implementation.name).as_str(), |b| {
+                b.iter(|| {
+                    let parsed = day.parse(implementation, &input).unwrap();
+                    day.part2(&*parsed, &params)
+                })
+            });
+        }
+        group.finish();
+    }
 }
 
 fn all_benches(c: &mut Criterion) {
+    let days = days_with_input();
     c.bench_function("all", |b| {
         b.iter(|| {
-            DAYS.iter().for_each(|day| {
-                day.part1();
-                day.part2();
+            days.iter().for_each(|(day, input)| {
+                let implementation = day.default_implementation();
+                if let Ok(parsed) = day.parse(implementation, input) {
+                    let params = Params::defaults(implementation.params);
+                    let _ = day.part1(&*parsed, &params);
+                    let _ = day.part2(&*parsed, &params);
+                }
             });
         });
     });
 }
 
 criterion_group!(days, day_benches);
-criterion_group!(d05, d05_benches);
+criterion_group!(examples, example_benches);
+criterion_group!(implementations, implementation_benches);
 criterion_group! {
     name = all;
     config = Criterion::default().measurement_time(std::time::Duration::from_secs(15));
     targets = all_benches
 }
-criterion_main!(days, d05, all);
+criterion_main!(days, examples, implementations, all);
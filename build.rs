@@ -0,0 +1,106 @@
+//! Lists the files in `data/` for the `embed-inputs` feature, the inputs in `data/YYYY/` and
+//! `answers.toml`, as `("YYYY/dNN.txt", include_str!(path))` pairs in
+//! `$OUT_DIR/embedded_inputs.rs`. Without the feature the list is empty, so nothing is embedded and
+//! the data directory isn't watched.
+//!
+//! Files still encrypted by transcrypt are decrypted with the key from `AOC_DATA_KEY` or
+//! `AOC_DATA_KEY_FILE`, so that the binary needs no key to run. Without one they're embedded as
+//! they are, with a warning, and need the key at runtime instead.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+#[allow(dead_code)]
+#[path = "src/days/crypt.rs"]
+mod crypt;
+
+/// The entries of `dir` whose names match `pattern`, where `#` is any digit, sorted
+fn matching(dir: &Path, pattern: &str) -> Vec<PathBuf> {
+    let matches = |name: &str| {
+        name.len() == pattern.len()
+            && name.chars().zip(pattern.chars()).all(|(c, p)| match p {
+                '#' => c.is_ascii_digit(),
+                p => c == p,
+            })
+    };
+    let mut matching: Vec<_> = fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("{} can't be read: {}", dir.display(), e))
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| matches(&path.file_name().unwrap().to_string_lossy()))
+        .collect();
+    matching.sort();
+    matching
+}
+
+/// The file to embed for `path`, which is a decrypted copy under `out_dir` if it's encrypted and
+/// there is a key. Fails the build if the key doesn't decrypt it.
+fn decrypted(path: &Path, name: &str, out_dir: &Path, key: Option<&str>) -> PathBuf {
+    let content = fs::read_to_string(path)
+        .unwrap_or_else(|e| panic!("{} can't be read: {}", path.display(), e));
+    if !crypt::is_encrypted(&content) {
+        return path.to_path_buf();
+    }
+    let Some(key) = key else {
+        println!(
+            "cargo:warning={} is encrypted and no key is set, so it's embedded encrypted and \
+             needs AOC_DATA_KEY or AOC_DATA_KEY_FILE when run",
+            name
+        );
+        return path.to_path_buf();
+    };
+
+    let plaintext = crypt::decrypt(&content, key)
+        .unwrap_or_else(|e| panic!("{} can't be decrypted: {}", path.display(), e));
+    let copy = out_dir.join("inputs").join(name);
+    fs::create_dir_all(copy.parent().unwrap()).unwrap();
+    fs::write(&copy, plaintext).unwrap();
+    copy
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let out_dir = PathBuf::from(env::var_os("OUT_DIR").unwrap());
+    let mut entries = String::new();
+    if env::var_os("CARGO_FEATURE_EMBED_INPUTS").is_some() {
+        println!("cargo:rerun-if-env-changed=AOC_DATA_KEY");
+        println!("cargo:rerun-if-env-changed=AOC_DATA_KEY_FILE");
+        if let Some(key_file) = env::var_os("AOC_DATA_KEY_FILE") {
+            println!("cargo:rerun-if-changed={}", Path::new(&key_file).display());
+        }
+        let key = match crypt::key_from_env() {
+            Ok(key) => Some(key),
+            Err(crypt::CryptError::NoKey) => None,
+            Err(e) => panic!("{}", e),
+        };
+
+        let data_dir = PathBuf::from(env::var_os("CARGO_MANIFEST_DIR").unwrap()).join("data");
+        println!("cargo:rerun-if-changed={}", data_dir.display());
+
+        let mut files = Vec::new();
+        for year in matching(&data_dir, "####") {
+            println!("cargo:rerun-if-changed={}", year.display());
+            files.extend(matching(&year, "d##.txt"));
+        }
+        // Changes to the directory cover it being added later
+        let answers = data_dir.join("answers.toml");
+        if answers.exists() {
+            println!("cargo:rerun-if-changed={}", answers.display());
+            files.push(answers);
+        }
+
+        for path in files {
+            let name = path.strip_prefix(&data_dir).unwrap().to_string_lossy();
+            let embedded = decrypted(&path, &name, &out_dir, key.as_deref());
+            entries += &format!("    ({:?}, include_str!({:?})),\n", name, embedded);
+        }
+    }
+
+    fs::write(
+        out_dir.join("embedded_inputs.rs"),
+        format!("pub static FILES: &[(&str, &str)] = &[\n{}];\n", entries),
+    )
+    .unwrap();
+}
@@ -1,8 +1,17 @@
-{ rustPlatform }:
+{ lib, rustPlatform, dataKeyFile ? null }:
 rustPlatform.buildRustPackage {
   pname = "advent-of-code-2024";
   version = "0.1.0";
   src = ./.;
+  # The binary is run from anywhere, away from the data directory
+  buildFeatures = [ "embed-inputs" ];
+  # The inputs are committed encrypted, and are only built in decrypted given the transcrypt key,
+  # e.g. `callPackage ./package.nix { dataKeyFile = /path/to/key; }`. That copies the key into the
+  # Nix store, where anyone on the machine can read it. Without it the binary needs AOC_DATA_KEY
+  # or AOC_DATA_KEY_FILE when run.
+  env = lib.optionalAttrs (dataKeyFile != null) {
+    AOC_DATA_KEY_FILE = "${dataKeyFile}";
+  };
   cargoLock = {
     lockFile = ./Cargo.lock;
   };
@@ -0,0 +1,135 @@
+//! Counting allocations, with the `count-allocations` feature. The feature installs [`Counting`] as
+//! the global allocator, which keeps counts for each thread so that days solved at once don't mix
+//! their numbers. Without the feature nothing is counted, and [`measure`] reports nothing.
+
+#[cfg(feature = "count-allocations")]
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+use serde::{Deserialize, Serialize};
+
+/// What something allocated while it ran
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Allocations {
+    /// How many allocations were made, counting each reallocation as one
+    pub count: u64,
+    /// The total size of every allocation
+    pub bytes: u64,
+    /// The most memory that was allocated at once, over what was already allocated beforehand
+    pub peak_bytes: u64,
+}
+
+thread_local! {
+    // These have no destructors and need no allocation to initialise, so the allocator can use them
+    static COUNT: Cell<u64> = const { Cell::new(0) };
+    static BYTES: Cell<u64> = const { Cell::new(0) };
+    static LIVE: Cell<u64> = const { Cell::new(0) };
+    static PEAK: Cell<u64> = const { Cell::new(0) };
+}
+
+#[cfg(feature = "count-allocations")]
+fn allocated(size: usize) {
+    let size = size as u64;
+    COUNT.set(COUNT.get() + 1);
+    BYTES.set(BYTES.get() + size);
+    let live = LIVE.get() + size;
+    LIVE.set(live);
+    PEAK.set(PEAK.get().max(live));
+}
+
+#[cfg(feature = "count-allocations")]
+fn freed(size: usize) {
+    // Memory allocated on another thread can be freed on this one
+    LIVE.set(LIVE.get().saturating_sub(size as u64));
+}
+
+/// The system allocator, counting what each thread allocates
+#[cfg(feature = "count-allocations")]
+pub struct Counting;
+
+#[cfg(feature = "count-allocations")]
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        allocated(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        allocated(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        freed(layout.size());
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        freed(layout.size());
+        allocated(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Call `f`, counting what it allocates on this thread if the feature is enabled
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, Option<Allocations>) {
+    if !cfg!(feature = "count-allocations") {
+        return (f(), None);
+    }
+
+    let (count, bytes, live) = (COUNT.get(), BYTES.get(), LIVE.get());
+    PEAK.set(live);
+    let result = f();
+    let allocations = Allocations {
+        count: COUNT.get() - count,
+        bytes: BYTES.get() - bytes,
+        peak_bytes: PEAK.get().saturating_sub(live),
+    };
+    (result, Some(allocations))
+}
+
+/// A number of bytes in binary units, e.g. `1.5 MiB`
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64;
+    let mut unit = "B";
+    for next in UNITS {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next;
+    }
+    format!("{:.1} {}", size, unit)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KiB");
+        assert_eq!(format_bytes(3 << 20), "3.0 MiB");
+    }
+
+    #[cfg(feature = "count-allocations")]
+    #[test]
+    fn counts_this_threads_allocations() {
+        let (_, allocations) = measure(|| {
+            let mut v = Vec::<u64>::with_capacity(100);
+            v.push(1);
+            let s = String::from("counted");
+            drop(v);
+            s
+        });
+        let allocations = allocations.unwrap();
+        assert_eq!(allocations.count, 2);
+        assert_eq!(allocations.bytes, 807);
+        assert_eq!(allocations.peak_bytes, 807);
+    }
+}
@@ -0,0 +1,304 @@
+//! Saved timings to compare later runs against. A baseline is the JSON report of a run, saved as
+//! `<dir>/<name>.json`, so a report printed with `--format json` works as one too.
+
+use std::{
+    fmt::{self, Write},
+    fs, io,
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::report::{format_duration, PartReport, Report, Status};
+
+pub const DEFAULT_DIR: &str = "bench_baselines";
+
+#[derive(Debug)]
+pub enum BaselineError {
+    /// Names are used as file names, so only letters, digits, `-`, `_` and `.` are allowed
+    InvalidName(String),
+    Io(PathBuf, io::Error),
+    Json(PathBuf, serde_json::Error),
+}
+
+impl fmt::Display for BaselineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BaselineError::InvalidName(name) => write!(
+                f,
+                "invalid baseline name {:?}, use letters, digits, '-', '_' and '.'",
+                name
+            ),
+            BaselineError::Io(path, e) => write!(f, "{}: {}", path.display(), e),
+            BaselineError::Json(path, e) => write!(f, "{}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for BaselineError {}
+
+/// Where the baseline `name` is saved, if it is a valid name
+pub fn path(dir: &Path, name: &str) -> Result<PathBuf, BaselineError> {
+    let valid = !name.is_empty()
+        && !name.starts_with('.')
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c));
+    match valid {
+        true => Ok(dir.join(format!("{}.json", name))),
+        false => Err(BaselineError::InvalidName(name.to_string())),
+    }
+}
+
+/// Save `report` as the baseline `name`, returning where it was saved
+pub fn save(dir: &Path, name: &str, report: &Report) -> Result<PathBuf, BaselineError> {
+    let path = path(dir, name)?;
+    let json =
+        serde_json::to_string_pretty(report).map_err(|e| BaselineError::Json(path.clone(), e))?;
+    fs::create_dir_all(dir)
+        .and_then(|()| fs::write(&path, json + "\n"))
+        .map_err(|e| BaselineError::Io(path.clone(), e))?;
+    Ok(path)
+}
+
+pub fn load(dir: &Path, name: &str) -> Result<Report, BaselineError> {
+    let path = path(dir, name)?;
+    let json = fs::read_to_string(&path).map_err(|e| BaselineError::Io(path.clone(), e))?;
+    serde_json::from_str(&json).map_err(|e| BaselineError::Json(path, e))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Change {
+    Faster,
+    Slower,
+    /// Within the threshold or the noise
+    Unchanged,
+}
+
+/// How one part's median time changed since the baseline
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartChange {
+    pub day: String,
+    pub implementation: String,
+    pub example: Option<usize>,
+    /// The part, or 0 for parsing the input
+    pub part: u8,
+    pub baseline_ns: u64,
+    pub current_ns: u64,
+    /// Positive when slower
+    pub change_percent: f64,
+    pub change: Change,
+}
+
+/// A run compared against a baseline, part by part. Parts that failed in either run, or that only
+/// one of them ran, aren't compared.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Comparison {
+    pub baseline: String,
+    pub threshold_percent: f64,
+    pub parts: Vec<PartChange>,
+}
+
+fn ran(p: &PartReport) -> bool {
+    p.timing.samples > 0
+        && matches!(
+            p.status,
+            Status::Ok | Status::Pass | Status::Fail | Status::Unknown
+        )
+}
+
+/// Whether a change from `before` to `after` is a real one. It has to be more than `threshold`
+/// percent, and more than twice the combined standard deviation of the two runs, so that parts
+/// timed over several runs need a change that stands out from their noise.
+fn classify(before: &PartReport, after: &PartReport, threshold_percent: f64) -> (f64, Change) {
+    let (b, a) = (
+        before.timing.median_ns as f64,
+        after.timing.median_ns as f64,
+    );
+    let percent = if b > 0.0 { (a - b) / b * 100.0 } else { 0.0 };
+    let noise = 2.0 * (before.timing.stddev_ns as f64).hypot(after.timing.stddev_ns as f64);
+    let change = if percent.abs() <= threshold_percent || (a - b).abs() <= noise {
+        Change::Unchanged
+    } else if a > b {
+        Change::Slower
+    } else {
+        Change::Faster
+    };
+    (percent, change)
+}
+
+impl Comparison {
+    pub fn new(name: &str, baseline: &Report, current: &Report, threshold_percent: f64) -> Self {
+        let parts = current
+            .parts
+            .iter()
+            .filter(|p| ran(p))
+            .filter_map(|after| {
+                let before = baseline.parts.iter().find(|b| {
+                    (&b.day, &b.implementation, b.example, b.part)
+                        == (&after.day, &after.implementation, after.example, after.part)
+                })?;
+                if !ran(before) {
+                    return None;
+                }
+                let (change_percent, change) = classify(before, after, threshold_percent);
+                Some(PartChange {
+                    day: after.day.clone(),
+                    implementation: after.implementation.clone(),
+                    example: after.example,
+                    part: after.part,
+                    baseline_ns: before.timing.median_ns,
+                    current_ns: after.timing.median_ns,
+                    change_percent,
+                    change,
+                })
+            })
+            .collect();
+
+        Comparison {
+            baseline: name.to_string(),
+            threshold_percent,
+            parts,
+        }
+    }
+
+    pub fn regressions(&self) -> usize {
+        self.parts
+            .iter()
+            .filter(|p| p.change == Change::Slower)
+            .count()
+    }
+
+    /// A line for each part, for people
+    pub fn describe(&self) -> String {
+        let mut out = String::new();
+        writeln!(
+            out,
+            "Compared with baseline {} (threshold {}%):",
+            self.baseline, self.threshold_percent
+        )
+        .unwrap();
+        for p in &self.parts {
+            let mut name = p.day.clone();
+            if p.implementation != "default" {
+                write!(name, "/{}", p.implementation).unwrap();
+            }
+            if let Some(i) = p.example {
+                write!(name, " example {}", i).unwrap();
+            }
+            let part = match p.part {
+                0 => "parse".to_string(),
+                part => format!("part {}", part),
+            };
+            writeln!(
+                out,
+                "  {} {}: {} -> {} ({:+.1}%, {})",
+                name,
+                part,
+                format_duration(Duration::from_nanos(p.baseline_ns)),
+                format_duration(Duration::from_nanos(p.current_ns)),
+                p.change_percent,
+                match p.change {
+                    Change::Faster => "faster",
+                    Change::Slower => "SLOWER",
+                    Change::Unchanged => "unchanged",
+                }
+            )
+            .unwrap();
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Timing;
+
+    fn part(day: &str, part: u8, median_ns: u64, stddev_ns: u64) -> PartReport {
+        PartReport {
+            day: day.to_string(),
+            example: None,
+            part,
+            implementation: "default".to_string(),
+            status: Status::Ok,
+            answer: None,
+            expected: None,
+            duration_ns: median_ns,
+            error: None,
+            timing: Timing {
+                samples: 5,
+                min_ns: median_ns,
+                median_ns,
+                mean_ns: median_ns,
+                stddev_ns,
+            },
+            allocations: None,
+        }
+    }
+
+    fn report(parts: Vec<PartReport>) -> Report {
+        Report {
+            parts,
+            jobs: 1,
+            total_duration_ns: 0,
+            cpu_duration_ns: 0,
+            total: Timing::default(),
+            baseline: None,
+        }
+    }
+
+    #[test]
+    fn verdicts() {
+        let baseline = report(vec![
+            part("d01", 1, 1000, 0),
+            part("d01", 2, 1000, 0),
+            part("d02", 1, 1000, 0),
+            part("d03", 1, 1000, 200),
+            part("d04", 1, 1000, 0),
+        ]);
+        let current = report(vec![
+            part("d01", 1, 1200, 0),
+            part("d01", 2, 800, 0),
+            part("d02", 1, 1050, 0),
+            // Slower by more than the threshold, but not by more than the noise
+            part("d03", 1, 1300, 200),
+            part("d05", 1, 1000, 0),
+        ]);
+        let comparison = Comparison::new("main", &baseline, &current, 10.0);
+        let changes: Vec<_> = comparison
+            .parts
+            .iter()
+            .map(|p| (p.day.as_str(), p.part, p.change))
+            .collect();
+        assert_eq!(
+            changes,
+            [
+                ("d01", 1, Change::Slower),
+                ("d01", 2, Change::Faster),
+                ("d02", 1, Change::Unchanged),
+                ("d03", 1, Change::Unchanged),
+            ]
+        );
+        assert_eq!(comparison.parts[0].change_percent, 20.0);
+        assert_eq!(comparison.regressions(), 1);
+    }
+
+    #[test]
+    fn saves_and_loads() {
+        let dir = std::env::temp_dir().join(format!("baselines-{}", std::process::id()));
+        let saved = report(vec![part("d01", 1, 1000, 0)]);
+        let path = save(&dir, "main", &saved).unwrap();
+        assert_eq!(path, dir.join("main.json"));
+        let loaded = load(&dir, "main").unwrap();
+        assert_eq!(loaded.parts[0].timing, saved.parts[0].timing);
+        fs::remove_dir_all(dir).unwrap();
+
+        assert!(matches!(
+            save(Path::new("."), "../main", &saved),
+            Err(BaselineError::InvalidName(_))
+        ));
+    }
+}
@@ -1,62 +1,178 @@
-use std::{fs, sync::LazyLock};
+use std::sync::LazyLock;
 
+pub mod answer;
+pub mod cancel;
+pub mod crypt;
+pub mod error;
+pub mod input;
+pub mod known_answers;
+pub mod params;
+pub mod parse;
+pub mod registry;
+pub mod solution;
 pub mod utils;
+pub mod visualize;
 
-type PartFn = fn(&str) -> i64;
+pub use answer::Answer;
+pub use error::{ParseError, SolveError};
+pub use input::{InputError, InputSource};
+pub use params::{Param, Params};
+pub use solution::{ParsedInput, Solution};
+
+use itertools::Itertools;
+use registry::{register, Example, Implementation, Registration};
 
 pub struct Day {
+    /// Unique across years, see [`day_name`]
     pub name: String,
-    input: String,
-    part1_impl: PartFn,
-    part2_impl: PartFn,
+    pub year: u16,
+    pub number: u8,
+    pub title: &'static str,
+    pub tags: &'static [&'static str],
+    pub implementations: &'static [Implementation],
+    pub examples: &'static [Example],
+}
+
+/// The name of a day, e.g. `d05`, or `2023/d05` for a year other than [`registry::DEFAULT_YEAR`]
+pub fn day_name(year: u16, number: u8) -> String {
+    match year {
+        registry::DEFAULT_YEAR => format!("d{:02}", number),
+        year => format!("{}/d{:02}", year, number),
+    }
+}
+
+/// The registered day `number` of `year`, if there is one
+pub fn find_day(year: u16, number: u8) -> Option<&'static Day> {
+    DAYS.iter()
+        .find(|day| day.year == year && day.number == number)
 }
 
 impl Day {
-    fn new(name: &str, filename: &str, part1: PartFn, part2: PartFn) -> Self {
+    fn new(registration: &'static Registration) -> Self {
         Day {
-            name: name.to_string(),
-            input: fs::read_to_string(filename).unwrap(),
-            part1_impl: part1,
-            part2_impl: part2,
+            name: day_name(registration.year, registration.day),
+            year: registration.year,
+            number: registration.day,
+            title: registration.title,
+            tags: registration.tags,
+            implementations: registration.implementations,
+            examples: registration.examples,
         }
     }
 
-    pub fn part1(&self) -> i64 {
-        (self.part1_impl)(&self.input)
+    /// The implementation that runs unless another is asked for
+    pub fn default_implementation(&self) -> &'static Implementation {
+        &self.implementations[0]
     }
 
-    pub fn part2(&self) -> i64 {
-        (self.part2_impl)(&self.input)
+    pub fn implementation(&self, name: &str) -> Option<&'static Implementation> {
+        self.implementations.iter().find(|i| i.name == name)
     }
-}
 
-macro_rules! declare_modules {
-    ($($day:ident),*) => {
-        $(
-            pub mod $day;
-        )*
-    };
-}
+    /// The parameters any of the day's implementations accept, with their defaults
+    pub fn params(&self) -> impl Iterator<Item = &'static Param> {
+        self.implementations
+            .iter()
+            .flat_map(|i| i.params)
+            .unique_by(|p| p.name)
+    }
 
-macro_rules! make_days {
-    ($($day:ident),*) => {
-        {
-            vec![
-                $(
-                    Day::new(stringify!($day), concat!("data/", stringify!($day), ".txt"), $day::part1, $day::part2),
-                )*
-            ]
-        }
-    };
+    pub fn load_input(&self, source: &InputSource) -> Result<String, InputError> {
+        source.load(self.year, self.number)
+    }
+
+    /// Parse the input with one of the day's implementations, ready for solving either part with
+    /// the same one. Errors are reported as part 0.
+    pub fn parse<'a>(
+        &self,
+        implementation: &Implementation,
+        input: &'a str,
+    ) -> Result<Box<dyn ParsedInput + 'a>, SolveError> {
+        (implementation.parse)(input).map_err(|e| SolveError::new(&self.name, 0, e))
+    }
+
+    pub fn part1(&self, parsed: &dyn ParsedInput, params: &Params) -> Result<Answer, SolveError> {
+        parsed
+            .part1(params)
+            .map_err(|e| SolveError::new(&self.name, 1, e))
+    }
+
+    pub fn part2(&self, parsed: &dyn ParsedInput, params: &Params) -> Result<Answer, SolveError> {
+        parsed
+            .part2(params)
+            .map_err(|e| SolveError::new(&self.name, 2, e))
+    }
 }
 
-declare_modules!(
-    d01, d02, d03, d04, d05, d06, d07, d08, d09, d10, d11, d13, d14, d15, d16, d17, d18, d19, d20,
-    d21, d22, d23, d24, d25
-);
+pub mod d01;
+pub mod d02;
+pub mod d03;
+pub mod d04;
+pub mod d05;
+pub mod d06;
+pub mod d07;
+pub mod d08;
+pub mod d09;
+pub mod d10;
+pub mod d11;
+pub mod d13;
+pub mod d14;
+pub mod d15;
+pub mod d16;
+pub mod d17;
+pub mod d18;
+pub mod d19;
+pub mod d20;
+pub mod d21;
+pub mod d22;
+pub mod d23;
+pub mod d24;
+pub mod d25;
+
+/// Every registered day, in order
 pub static DAYS: LazyLock<Vec<Day>> = LazyLock::new(|| {
-    make_days!(
-        d01, d02, d03, d04, d05, d06, d07, d08, d09, d10, d11, d13, d14, d15, d16, d17, d18, d19,
-        d20, d21, d22, d23, d24, d25
-    )
+    registry::registrations()
+        .into_iter()
+        .map(Day::new)
+        .collect()
 });
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use known_answers::Verdict;
+
+    #[test]
+    fn every_example_passes() {
+        for day in DAYS.iter() {
+            for ((i, example), implementation) in day
+                .examples
+                .iter()
+                .enumerate()
+                .cartesian_product(day.implementations)
+            {
+                let params = implementation.example_params(example).unwrap();
+                let parsed = day.parse(implementation, example.input.trim()).unwrap();
+                for part in 1..=2 {
+                    if example.answer(part).is_none() {
+                        continue;
+                    }
+                    let answer = match part {
+                        1 => day.part1(&*parsed, &params),
+                        _ => day.part2(&*parsed, &params),
+                    }
+                    .unwrap();
+                    assert_eq!(
+                        example.check(part, &answer),
+                        Verdict::Pass,
+                        "{}/{} example {} part {}",
+                        day.name,
+                        implementation.name,
+                        i + 1,
+                        part
+                    );
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::fmt;
+
+/// The answer to one part of a puzzle, in the form that is actually submitted
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Answer {
+    Int(i64),
+    BigInt(i128),
+    Str(String),
+    NotImplemented,
+}
+
+impl fmt::Display for Answer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Answer::Int(x) => write!(f, "{}", x),
+            Answer::BigInt(x) => write!(f, "{}", x),
+            Answer::Str(s) => write!(f, "{}", s),
+            Answer::NotImplemented => write!(f, "-"),
+        }
+    }
+}
+
+macro_rules! impl_from_small_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(x: $t) -> Self {
+                    Answer::Int(x.into())
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_from_large_int {
+    ($($t:ty),*) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(x: $t) -> Self {
+                    match i64::try_from(x) {
+                        Ok(x) => Answer::Int(x),
+                        Err(_) => Answer::BigInt(x as i128),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_small_int!(i8, i16, i32, i64, u8, u16, u32);
+impl_from_large_int!(u64, usize);
+
+impl From<i128> for Answer {
+    fn from(x: i128) -> Self {
+        match i64::try_from(x) {
+            Ok(x) => Answer::Int(x),
+            Err(_) => Answer::BigInt(x),
+        }
+    }
+}
+
+impl From<String> for Answer {
+    fn from(s: String) -> Self {
+        Answer::Str(s)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(s: &str) -> Self {
+        Answer::Str(s.to_string())
+    }
+}
+
+// Lets the example tests compare directly against integer literals
+impl PartialEq<i64> for Answer {
+    fn eq(&self, other: &i64) -> bool {
+        match self {
+            Answer::Int(x) => x == other,
+            Answer::BigInt(x) => *x == *other as i128,
+            _ => false,
+        }
+    }
+}
+
+impl PartialEq<&str> for Answer {
+    fn eq(&self, other: &&str) -> bool {
+        matches!(self, Answer::Str(s) if s == other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn large_values_become_big_ints() {
+        assert_eq!(Answer::from(u64::MAX), Answer::BigInt(u64::MAX as i128));
+        assert_eq!(Answer::from(42_u64), Answer::Int(42));
+    }
+
+    #[test]
+    fn display() {
+        assert_eq!(Answer::from(-3).to_string(), "-3");
+        assert_eq!(Answer::from("co,de,ka,ta").to_string(), "co,de,ka,ta");
+        assert_eq!(Answer::NotImplemented.to_string(), "-");
+    }
+}
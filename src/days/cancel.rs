@@ -0,0 +1,81 @@
+//! Cooperative cancellation. A part that could run forever on an unexpected input calls [`check`]
+//! in its loops, and gives up with an error once the runner has cancelled its thread's [`Token`].
+//! Threads without a token are never cancelled.
+
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+use super::ParseError;
+
+/// A flag shared between a runner and the thread it's waiting on
+#[derive(Debug, Clone, Default)]
+pub struct Token(Arc<AtomicBool>);
+
+impl Token {
+    pub fn new() -> Self {
+        Token::default()
+    }
+
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+thread_local! {
+    static TOKEN: RefCell<Option<Token>> = const { RefCell::new(None) };
+}
+
+/// Make `token` cancel the work done on the current thread from now on
+pub fn install(token: Token) {
+    TOKEN.with(|t| *t.borrow_mut() = Some(token));
+}
+
+/// Whether the current thread's work has been cancelled
+pub fn is_cancelled() -> bool {
+    TOKEN.with(|t| t.borrow().as_ref().is_some_and(Token::is_cancelled))
+}
+
+/// An error if the current thread's work has been cancelled. The error has no location, since the
+/// input wasn't at fault.
+pub fn check() -> Result<(), ParseError> {
+    match is_cancelled() {
+        true => Err(ParseError {
+            line: 0,
+            column: 0,
+            message: "cancelled".to_string(),
+        }),
+        false => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_the_installed_thread_is_cancelled() {
+        let token = Token::new();
+        let worker = {
+            let token = token.clone();
+            std::thread::spawn(move || {
+                install(token);
+                while check().is_ok() {
+                    std::thread::yield_now();
+                }
+            })
+        };
+        assert!(check().is_ok());
+        token.cancel();
+        worker.join().unwrap();
+        assert!(!is_cancelled());
+    }
+}
@@ -0,0 +1,164 @@
+//! Decryption of inputs committed with transcrypt. Without the git filter configured the data files
+//! are checked out as base64 OpenSSL `enc` output (aes-256-cbc, key derived with MD5), which this
+//! module can decrypt directly given the transcrypt password.
+
+use std::{env, fmt, fs, path::PathBuf};
+
+use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, BlockEncryptMut, KeyIvInit};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use md5::{Digest, Md5};
+
+type Aes256CbcDec = cbc::Decryptor<aes::Aes256>;
+type Aes256CbcEnc = cbc::Encryptor<aes::Aes256>;
+
+/// Base64 of `Salted__`, the start of every file written by `openssl enc -a` with a salt
+const SALTED_BASE64: &str = "U2FsdGVkX1";
+const SALTED: &[u8] = b"Salted__";
+const KEY_LEN: usize = 32;
+const IV_LEN: usize = 16;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CryptError {
+    /// The input is encrypted but neither `AOC_DATA_KEY` nor `AOC_DATA_KEY_FILE` is set
+    NoKey,
+    KeyFile(PathBuf, String),
+    Base64(String),
+    /// The ciphertext didn't decrypt to valid padding, almost always because the key is wrong
+    BadKey,
+    NotUtf8,
+}
+
+impl fmt::Display for CryptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CryptError::NoKey => write!(
+                f,
+                "input is encrypted, set AOC_DATA_KEY or AOC_DATA_KEY_FILE to decrypt it"
+            ),
+            CryptError::KeyFile(path, e) => {
+                write!(f, "failed to read key file {}: {}", path.display(), e)
+            }
+            CryptError::Base64(e) => write!(f, "invalid base64 in encrypted input: {}", e),
+            CryptError::BadKey => write!(f, "failed to decrypt input, is the key correct?"),
+            CryptError::NotUtf8 => write!(f, "decrypted input is not valid UTF-8"),
+        }
+    }
+}
+
+impl std::error::Error for CryptError {}
+
+/// Whether `content` looks like base64 OpenSSL output rather than a plain puzzle input
+pub fn is_encrypted(content: &str) -> bool {
+    content.trim_start().starts_with(SALTED_BASE64)
+}
+
+/// The transcrypt password, from `AOC_DATA_KEY` or else the contents of the file named by
+/// `AOC_DATA_KEY_FILE`. Surrounding whitespace in the file is ignored.
+pub fn key_from_env() -> Result<String, CryptError> {
+    if let Ok(key) = env::var("AOC_DATA_KEY") {
+        return Ok(key);
+    }
+    let path = env::var_os("AOC_DATA_KEY_FILE")
+        .map(PathBuf::from)
+        .ok_or(CryptError::NoKey)?;
+    fs::read_to_string(&path)
+        .map(|key| key.trim().to_string())
+        .map_err(|e| CryptError::KeyFile(path, e.to_string()))
+}
+
+/// Decrypt the base64 output of `openssl enc -aes-256-cbc -md MD5 -a` with `password`
+pub fn decrypt(content: &str, password: &str) -> Result<String, CryptError> {
+    let encoded: String = content.split_whitespace().collect();
+    let data = STANDARD
+        .decode(encoded)
+        .map_err(|e| CryptError::Base64(e.to_string()))?;
+
+    let salt = data
+        .strip_prefix(SALTED)
+        .filter(|rest| rest.len() >= 8)
+        .ok_or(CryptError::Base64("missing salt header".to_string()))?;
+    let (salt, ciphertext) = salt.split_at(8);
+
+    let (key, iv) = bytes_to_key(password.as_bytes(), salt);
+    let plaintext = Aes256CbcDec::new(&key.into(), &iv.into())
+        .decrypt_padded_vec_mut::<Pkcs7>(ciphertext)
+        .map_err(|_| CryptError::BadKey)?;
+
+    String::from_utf8(plaintext).map_err(|_| CryptError::NotUtf8)
+}
+
+/// Encrypt `content` like `openssl enc -aes-256-cbc -md MD5 -a -S <salt>` with `password`, the
+/// inverse of [`decrypt`]. Transcrypt picks the salt from the file's content so that encrypting the
+/// same file twice gives the same result, and so should callers.
+pub fn encrypt(content: &str, password: &str, salt: [u8; 8]) -> String {
+    let (key, iv) = bytes_to_key(password.as_bytes(), &salt);
+    let ciphertext = Aes256CbcEnc::new(&key.into(), &iv.into())
+        .encrypt_padded_vec_mut::<Pkcs7>(content.as_bytes());
+
+    let data = [SALTED, &salt, &ciphertext].concat();
+    // `openssl enc -a` wraps its output at 64 columns
+    let encoded = STANDARD.encode(data);
+    let mut out = String::new();
+    for line in encoded.as_bytes().chunks(64) {
+        out += std::str::from_utf8(line).unwrap();
+        out.push('\n');
+    }
+    out
+}
+
+/// OpenSSL's `EVP_BytesToKey` with MD5 and a single iteration, the derivation used by `enc -md MD5`
+fn bytes_to_key(password: &[u8], salt: &[u8]) -> ([u8; KEY_LEN], [u8; IV_LEN]) {
+    let mut derived = Vec::with_capacity(KEY_LEN + IV_LEN);
+    let mut prev: Vec<u8> = Vec::new();
+    while derived.len() < KEY_LEN + IV_LEN {
+        let mut hasher = Md5::new();
+        hasher.update(&prev);
+        hasher.update(password);
+        hasher.update(salt);
+        prev = hasher.finalize().to_vec();
+        derived.extend_from_slice(&prev);
+    }
+
+    let mut key = [0; KEY_LEN];
+    let mut iv = [0; IV_LEN];
+    key.copy_from_slice(&derived[..KEY_LEN]);
+    iv.copy_from_slice(&derived[KEY_LEN..KEY_LEN + IV_LEN]);
+    (key, iv)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // `printf '3   4\n4   3\n2   5\n' | openssl enc -aes-256-cbc -md MD5 -pass pass:correct-horse -a`
+    const TEST_KEY: &str = "correct-horse";
+    const ENCRYPTED: &str = "U2FsdGVkX1/ZSX//6fHWUoQ8bKduRGxiAmE4NzoJLbjO5DErlVAH2CMVaxTBZ0v4\n";
+
+    #[test]
+    fn detects_encrypted_input() {
+        assert!(is_encrypted(ENCRYPTED));
+        assert!(!is_encrypted("3   4\n4   3\n"));
+    }
+
+    #[test]
+    fn decrypts_with_test_key() {
+        assert_eq!(
+            decrypt(ENCRYPTED, TEST_KEY).unwrap(),
+            "3   4\n4   3\n2   5\n"
+        );
+    }
+
+    #[test]
+    fn encrypts_like_openssl() {
+        let salt = STANDARD.decode(ENCRYPTED.trim()).unwrap()[8..16]
+            .try_into()
+            .unwrap();
+        let encrypted = encrypt("3   4\n4   3\n2   5\n", TEST_KEY, salt);
+        assert_eq!(encrypted, ENCRYPTED);
+    }
+
+    #[test]
+    fn wrong_key() {
+        assert!(decrypt(ENCRYPTED, "wrong").is_err());
+    }
+}
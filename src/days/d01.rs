@@ -3,6 +3,10 @@ use std::{
     iter::zip,
 };
 
+use itertools::Itertools;
+
+use super::{parse, registry::Example, Answer, Params, ParseError, Solution};
+
 struct PopIterator<T> {
     heap: BinaryHeap<T>,
 }
@@ -23,51 +27,50 @@ where
     }
 }
 
-pub fn part1(input: &str) -> i64 {
-    let mut list1 = BinaryHeap::new();
-    let mut list2 = BinaryHeap::new();
-
-    input.lines().for_each(|l| {
-        let mut iter = l.split_whitespace().map(|s| s.parse::<i32>().unwrap());
-        list1.push(iter.next().unwrap());
-        list2.push(iter.next().unwrap());
-    });
-
-    return zip(PopIterator::new(list1), PopIterator::new(list2))
-        .map(|(a, b)| (a - b).abs())
-        .sum::<i32>()
-        .try_into()
-        .unwrap();
+fn parse_line(input: &str, l: &str) -> Result<(i32, i32), ParseError> {
+    let mut iter = l.split_whitespace();
+    let a = parse::number(input, parse::next(input, l, &mut iter, "left number")?)?;
+    let b = parse::number(input, parse::next(input, l, &mut iter, "right number")?)?;
+    Ok((a, b))
 }
 
-pub fn part2(input: &str) -> i64 {
-    let mut map: HashMap<i32, (i32, i32)> = HashMap::new();
+pub struct Solver;
 
-    input.lines().for_each(|l| {
-        let mut iter = l.split_whitespace().map(|s| s.parse().unwrap());
+impl Solution for Solver {
+    /// The left and right lists
+    type Parsed<'a> = (Vec<i32>, Vec<i32>);
 
-        map.entry(iter.next().unwrap())
-            .and_modify(|(l, _)| *l += 1)
-            .or_insert((1, 0));
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        input
+            .lines()
+            .map(|l| parse_line(input, l))
+            .process_results(|pairs| pairs.unzip())
+    }
 
-        map.entry(iter.next().unwrap())
-            .and_modify(|(_, r)| *r += 1)
-            .or_insert((0, 1));
-    });
+    fn part1((left, right): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        let list1 = BinaryHeap::from(left.clone());
+        let list2 = BinaryHeap::from(right.clone());
 
-    return map
-        .drain()
-        .map(|(x, (y, z))| x * y * z)
-        .sum::<i32>()
-        .try_into()
-        .unwrap();
-}
+        Ok(zip(PopIterator::new(list1), PopIterator::new(list2))
+            .map(|(a, b)| (a - b).abs())
+            .sum::<i32>()
+            .into())
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    fn part2((left, right): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        let mut map: HashMap<i32, (i32, i32)> = HashMap::new();
+
+        for (&a, &b) in zip(left, right) {
+            map.entry(a).and_modify(|(l, _)| *l += 1).or_insert((1, 0));
+
+            map.entry(b).and_modify(|(_, r)| *r += 1).or_insert((0, 1));
+        }
 
-    const EXAMPLE: &str = "
+        Ok(map.drain().map(|(x, (y, z))| x * y * z).sum::<i32>().into())
+    }
+}
+
+const EXAMPLE: &str = "
 3   4
 4   3
 2   5
@@ -76,15 +79,28 @@ mod tests {
 3   3
 ";
 
+super::register! {
+    year: 2024,
+    day: 1,
+    title: "Historian Hysteria",
+    tags: ["sorting"],
+    implementations: { "default" => Solver },
+    examples: [Example::new(EXAMPLE).part1("11").part2("31")],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 11)
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 31)
     }
 }
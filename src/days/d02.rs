@@ -4,40 +4,37 @@ use std::{
     slice::Iter,
 };
 
+use super::{parse, registry::Example, Answer, Params, ParseError, Solution};
+
 fn is_safe<I>(iter: I) -> bool
 where
     I: Iterator<Item = i8> + Clone,
 {
     let mut diffiter = iter.tuple_windows().map(|(a, b)| a - b);
 
-    match diffiter.next().unwrap() {
-        x if (x.abs() >= 3) => return false,
-        x if (x > 0) => diffiter.all(|x| x > 0 && x <= 3),
-        x if (x < 0) => diffiter.all(|x| x < 0 && x >= -3),
-        _ => return false,
+    match diffiter.next() {
+        // a report with a single level has nothing to be unsafe about
+        None => true,
+        Some(x) if (x.abs() >= 3) => false,
+        Some(x) if (x > 0) => diffiter.all(|x| (1..=3).contains(&x)),
+        Some(x) if (x < 0) => diffiter.all(|x| (-3..0).contains(&x)),
+        _ => false,
     }
 }
 
-pub fn part1(input: &str) -> i64 {
-    return input
-        .lines()
-        .map(|l| l.split_whitespace().map(|n| n.parse::<i8>().unwrap()))
-        .filter_map(|r| match is_safe(r.clone()) {
-            true => Some(()),
-            false => None,
-        })
-        .count()
-        .try_into()
-        .unwrap();
+fn parse_report(input: &str, l: &str) -> Result<Vec<i8>, ParseError> {
+    l.split_whitespace()
+        .map(|n| parse::number(input, n))
+        .collect()
 }
 
 struct TryRemovingEach<'a, T> {
-    slice: &'a Vec<T>,
+    slice: &'a [T],
     current: usize,
 }
 
 impl<'a, T> TryRemovingEach<'a, T> {
-    fn new(slice: &'a Vec<T>) -> Self {
+    fn new(slice: &'a [T]) -> Self {
         TryRemovingEach { slice, current: 0 }
     }
 }
@@ -53,33 +50,37 @@ impl<'a, T> Iterator for TryRemovingEach<'a, T> {
         let after = self.slice[self.current + 1..].iter();
         let combined = before.chain(after);
         self.current += 1;
-        return Some(combined);
+        Some(combined)
     }
 }
 
-pub fn part2(input: &str) -> i64 {
-    let mut working_space: Vec<i8> = vec![];
-    return input
-        .lines()
-        .map(|l| l.split_whitespace().map(|n| n.parse::<i8>().unwrap()))
-        .filter_map(|r| {
-            working_space.clear();
-            working_space.extend(r);
-            match TryRemovingEach::new(&working_space).any(|newr| is_safe(newr.cloned())) {
-                true => Some(()),
-                false => None,
-            }
-        })
-        .count()
-        .try_into()
-        .unwrap();
-}
+pub struct Solver;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl Solution for Solver {
+    type Parsed<'a> = Vec<Vec<i8>>;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        input.lines().map(|l| parse_report(input, l)).collect()
+    }
+
+    fn part1(reports: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        Ok(reports
+            .iter()
+            .filter(|r| is_safe(r.iter().cloned()))
+            .count()
+            .into())
+    }
 
-    const EXAMPLE: &str = "
+    fn part2(reports: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        Ok(reports
+            .iter()
+            .filter(|r| TryRemovingEach::new(r).any(|newr| is_safe(newr.cloned())))
+            .count()
+            .into())
+    }
+}
+
+const EXAMPLE: &str = "
 7 6 4 2 1
 1 2 7 8 9
 9 7 6 2 1
@@ -88,15 +89,27 @@ mod tests {
 1 3 6 7 9
 ";
 
+super::register! {
+    year: 2024,
+    day: 2,
+    title: "Red-Nosed Reports",
+    implementations: { "default" => Solver },
+    examples: [Example::new(EXAMPLE).part1("2").part2("4")],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 2)
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 4)
     }
 }
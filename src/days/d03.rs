@@ -1,90 +1,95 @@
 use regex::Regex;
 use std::sync::LazyLock;
 
-pub fn part1(input: &str) -> i64 {
-    static RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"mul\((\d+),(\d+)\)").unwrap());
-    return RE
-        .captures_iter(input)
-        .map(|m| {
-            m.iter()
-                .skip(1)
-                .map(|d| d.unwrap().as_str().parse::<i32>().unwrap())
-                .product::<i32>()
-        })
-        .sum::<i32>()
-        .try_into()
-        .unwrap();
-}
+use super::{parse, registry::Example, Answer, Params, ParseError, Solution};
 
-struct EnabledMulIterator<'a> {
-    input: &'a str,
-    offset: usize,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Instruction {
+    Mul(i32, i32),
+    Do,
+    Dont,
 }
 
-impl<'a> EnabledMulIterator<'a> {
-    fn new(input: &'a str) -> Self {
-        EnabledMulIterator { input, offset: 0 }
-    }
-}
+pub struct Solver;
+
+impl Solution for Solver {
+    type Parsed<'a> = Vec<Instruction>;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        static RE: LazyLock<Regex> =
+            LazyLock::new(|| Regex::new(r"mul\((\d+),(\d+)\)|do\(\)|don't\(\)").unwrap());
 
-impl Iterator for EnabledMulIterator<'_> {
-    type Item = (i32, i32);
+        RE.captures_iter(input)
+            .map(|m| match m.get(0).unwrap().as_str() {
+                "do()" => Ok(Instruction::Do),
+                "don't()" => Ok(Instruction::Dont),
+                _ => Ok(Instruction::Mul(
+                    parse::number(input, m.get(1).unwrap().as_str())?,
+                    parse::number(input, m.get(2).unwrap().as_str())?,
+                )),
+            })
+            .collect()
+    }
 
-    fn next(&mut self) -> Option<Self::Item> {
-        static MUL_OR_DONT_RE: LazyLock<Regex> =
-            LazyLock::new(|| Regex::new(r"don't\(\)|mul\((\d+),(\d+)\)").unwrap());
-        static DO_RE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"do\(\)").unwrap());
+    fn part1(instructions: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        Ok(instructions
+            .iter()
+            .map(|i| match i {
+                Instruction::Mul(a, b) => a * b,
+                _ => 0,
+            })
+            .sum::<i32>()
+            .into())
+    }
 
-        loop {
-            match MUL_OR_DONT_RE.captures_at(self.input, self.offset) {
-                None => return None,
-                Some(x) if x.get(0).unwrap().as_str().starts_with("m") => {
-                    self.offset = x.get(0).unwrap().end();
-                    return Some((
-                        x.get(1).unwrap().as_str().parse::<i32>().unwrap(),
-                        x.get(2).unwrap().as_str().parse::<i32>().unwrap(),
-                    ));
-                }
-                Some(x) => match DO_RE.find_at(self.input, x.get(0).unwrap().end()) {
-                    None => return None,
-                    Some(next_do) => {
-                        self.offset = next_do.end();
-                    }
-                },
+    fn part2(instructions: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        let mut enabled = true;
+        let mut total = 0;
+        for i in instructions {
+            match i {
+                Instruction::Do => enabled = true,
+                Instruction::Dont => enabled = false,
+                Instruction::Mul(a, b) if enabled => total += a * b,
+                Instruction::Mul(..) => {}
             }
         }
+        Ok(total.into())
     }
 }
 
-pub fn part2(input: &str) -> i64 {
-    EnabledMulIterator::new(input)
-        .map(|(a, b)| a * b)
-        .sum::<i32>()
-        .try_into()
-        .unwrap()
+const EXAMPLE: &str = "
+xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))
+";
+
+const EXAMPLE2: &str = "
+xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))
+";
+
+super::register! {
+    year: 2024,
+    day: 3,
+    title: "Mull It Over",
+    tags: ["parsing"],
+    implementations: { "default" => Solver },
+    examples: [
+        Example::new(EXAMPLE).part1("161"),
+        Example::new(EXAMPLE2).part2("48"),
+    ],
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const EXAMPLE: &str = "
-xmul(2,4)%&mul[3,7]!@^do_not_mul(5,5)+mul(32,64]then(mul(11,8)mul(8,5))
-";
-
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 161)
     }
 
-    const EXAMPLE2: &str = "
-xmul(2,4)&mul[3,7]!^don't()_mul(5,5)+mul(32,64](mul(11,8)undo()?mul(8,5))
-";
-
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE2.trim());
+        let result = Solver::solve_part2(EXAMPLE2.trim()).unwrap();
         assert_eq!(result, 48)
     }
 }
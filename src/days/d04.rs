@@ -1,31 +1,23 @@
 use ndarray::{s, Array2};
 
+use super::{parse, registry::Example, Answer, Params, ParseError, Solution};
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
-enum Val {
+pub enum Val {
     X,
     M,
     A,
     S,
 }
 
-fn build_grid(input: &str) -> Array2<Val> {
-    let ncols = input.lines().next().unwrap().len();
-    let nrows = ((input.len() + 1) / ncols) - 1;
-
-    Array2::from_shape_vec(
-        (nrows, ncols),
-        input
-            .chars()
-            .filter_map(|c| match c {
-                'X' => Some(Val::X),
-                'M' => Some(Val::M),
-                'A' => Some(Val::A),
-                'S' => Some(Val::S),
-                _ => None,
-            })
-            .collect(),
-    )
-    .unwrap()
+fn build_grid(input: &str) -> Result<Array2<Val>, ParseError> {
+    parse::grid(input, input, |c| match c {
+        'X' => Some(Val::X),
+        'M' => Some(Val::M),
+        'A' => Some(Val::A),
+        'S' => Some(Val::S),
+        _ => None,
+    })
 }
 
 const P1_MATCH: [Val; 4] = [Val::X, Val::M, Val::A, Val::S];
@@ -35,12 +27,10 @@ fn p1_check_match<I>(iter: I) -> bool
 where
     I: Iterator<Item = Val> + Clone,
 {
-    iter.clone().eq(P1_MATCH.into_iter()) || iter.eq(P1_REV_MATCH.into_iter())
+    iter.clone().eq(P1_MATCH) || iter.eq(P1_REV_MATCH)
 }
 
-pub fn part1(input: &str) -> i64 {
-    let grid = build_grid(input);
-
+fn part1(grid: &Array2<Val>) -> Result<Answer, ParseError> {
     let horizontal = grid
         .windows((1, 4))
         .into_iter()
@@ -64,7 +54,7 @@ pub fn part1(input: &str) -> i64 {
         })
         .sum();
 
-    return (horizontal + vertical + diags).try_into().unwrap();
+    Ok((horizontal + vertical + diags).into())
 }
 
 const P2_MATCH: [Val; 3] = [Val::M, Val::A, Val::S];
@@ -74,13 +64,12 @@ fn p2_check_match<I>(iter: I) -> bool
 where
     I: Iterator<Item = Val> + Clone,
 {
-    iter.clone().eq(P2_MATCH.into_iter()) || iter.eq(P2_REV_MATCH.into_iter())
+    iter.clone().eq(P2_MATCH) || iter.eq(P2_REV_MATCH)
 }
 
-pub fn part2(input: &str) -> i64 {
-    let grid = build_grid(input);
-
-    grid.windows((3, 3))
+fn part2(grid: &Array2<Val>) -> Result<Answer, ParseError> {
+    Ok(grid
+        .windows((3, 3))
         .into_iter()
         .filter(|w| {
             let diag = p2_check_match(w.diag().into_iter().cloned());
@@ -88,15 +77,28 @@ pub fn part2(input: &str) -> i64 {
             diag && antidiag
         })
         .count()
-        .try_into()
-        .unwrap()
+        .into())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub struct Solver;
+
+impl Solution for Solver {
+    type Parsed<'a> = Array2<Val>;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        build_grid(input)
+    }
+
+    fn part1(grid: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        part1(grid)
+    }
 
-    const EXAMPLE: &str = "
+    fn part2(grid: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        part2(grid)
+    }
+}
+
+const EXAMPLE: &str = "
 MMMSXXMASM
 MSAMXMSMSA
 AMXSXMAAMM
@@ -109,15 +111,28 @@ MAMMMXMMMM
 MXMXAXMASX
 ";
 
+super::register! {
+    year: 2024,
+    day: 4,
+    title: "Ceres Search",
+    tags: ["grid"],
+    implementations: { "default" => Solver },
+    examples: [Example::new(EXAMPLE).part1("18").part2("9")],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 18)
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 9)
     }
 }
@@ -1,14 +1,16 @@
 use std::{cmp::Ordering, collections::HashSet};
 
+use super::{parse, registry::Example, Answer, Params, ParseError, Solution};
+
 type RuleSet = HashSet<(u8, u8)>;
 
-fn build_rules(input: &str) -> RuleSet {
+fn build_rules(input: &str) -> Result<RuleSet, ParseError> {
     input
         .lines()
         .take_while(|line| !line.is_empty())
         .map(|l| {
-            let mut iter = l.split('|').map(|n| n.parse::<u8>().unwrap());
-            (iter.next().unwrap(), iter.next().unwrap())
+            let (a, b) = parse::split_once(input, l, "|")?;
+            Ok((parse::number(input, a)?, parse::number(input, b)?))
         })
         .collect()
 }
@@ -24,71 +26,69 @@ fn compare<'a>(rules: &'a RuleSet) -> impl Fn(&u8, &u8) -> Ordering + 'a {
 }
 
 fn is_sorted_compare(cmp: impl Fn(&u8, &u8) -> Ordering) -> impl Fn(&u8, &u8) -> bool {
-    move |a, b| match cmp(a, b) {
-        Ordering::Greater => false,
-        _ => true,
-    }
+    move |a, b| !matches!(cmp(a, b), Ordering::Greater)
 }
 
 fn update_iter(input: &str) -> impl Iterator<Item = &str> {
     input.lines().skip_while(|line| !line.is_empty()).skip(1)
 }
 
-pub fn part1(input: &str) -> i64 {
-    let rules = build_rules(input);
-    let compare_fn = is_sorted_compare(compare(&rules));
+fn parse_update(input: &str, l: &str) -> Result<Vec<u8>, ParseError> {
+    l.split(',').map(|n| parse::number(input, n)).collect()
+}
 
-    let mut working_space: Vec<u8> = vec![];
+pub struct Solver;
 
-    let result = update_iter(input)
-        .filter_map(|l| {
-            working_space.clear();
-            working_space.extend(l.split(',').map(|n| n.parse::<u8>().unwrap()));
+impl Solution for Solver {
+    /// The ordering rules and the list of updates
+    type Parsed<'a> = (RuleSet, Vec<Vec<u8>>);
 
-            match working_space.is_sorted_by(&compare_fn) {
-                true => Some(working_space.get(working_space.len() / 2).unwrap().clone() as i32),
-                false => None,
-            }
-        })
-        .sum::<i32>();
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        let rules = build_rules(input)?;
+        let updates = update_iter(input)
+            .map(|l| parse_update(input, l))
+            .collect::<Result<_, _>>()?;
+        Ok((rules, updates))
+    }
 
-    return result.try_into().unwrap();
-}
+    fn part1((rules, updates): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        let compare_fn = is_sorted_compare(compare(rules));
 
-pub fn part2(input: &str) -> i64 {
-    let rules = build_rules(input);
-    let compare_fn = compare(&rules);
-    let is_sorted_compare_fn = is_sorted_compare(&compare_fn);
+        let result = updates
+            .iter()
+            .map(|pages| match pages.is_sorted_by(&compare_fn) {
+                true => pages[pages.len() / 2] as i64,
+                false => 0,
+            })
+            .sum::<i64>();
 
-    let mut working_space: Vec<u8> = vec![];
+        Ok(result.into())
+    }
 
-    let result = update_iter(input)
-        .filter_map(|l| {
-            working_space.clear();
-            working_space.extend(l.split(',').map(|n| n.parse::<u8>().unwrap()));
+    fn part2((rules, updates): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        let compare_fn = compare(rules);
+        let is_sorted_compare_fn = is_sorted_compare(&compare_fn);
 
-            let mid = working_space.len() / 2;
+        let mut working_space: Vec<u8> = vec![];
 
-            match working_space.is_sorted_by(&is_sorted_compare_fn) {
-                true => None,
-                false => Some(
-                    working_space
-                        .select_nth_unstable_by(mid, &compare_fn)
-                        .1
-                        .clone() as i32,
-                ),
-            }
-        })
-        .sum::<i32>();
+        let result = updates
+            .iter()
+            .map(|pages| {
+                working_space.clone_from(pages);
+                let mid = working_space.len() / 2;
 
-    return result.try_into().unwrap();
-}
+                match working_space.is_sorted_by(&is_sorted_compare_fn) {
+                    true => 0,
+                    false => *working_space.select_nth_unstable_by(mid, &compare_fn).1 as i64,
+                }
+            })
+            .sum::<i64>();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        Ok(result.into())
+    }
+}
 
-    const EXAMPLE: &str = "
+const EXAMPLE: &str = "
 47|53
 97|13
 97|61
@@ -119,107 +119,156 @@ mod tests {
 97,13,75,29,47
 ";
 
+super::register! {
+    year: 2024,
+    day: 5,
+    title: "Print Queue",
+    tags: ["graph", "sorting"],
+    implementations: { "comparator" => Solver, "toposort" => toposort::Solver },
+    examples: [Example::new(EXAMPLE).part1("143").part2("123")],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 143)
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 123)
     }
 }
 
-pub mod old {
-    use std::{cmp::Ordering, collections::HashSet};
+/// The first version, which sorts each update by a topological sort of the rules between its pages
+pub mod toposort {
+    use std::collections::HashSet;
 
     use petgraph::{
-        algo::{toposort, DfsSpace},
+        algo::{toposort, Cycle, DfsSpace},
         prelude::DiGraphMap,
     };
+
+    use super::{build_rules, parse_update, update_iter, Answer, Params, ParseError, Solution};
+
     type Graph = DiGraphMap<u8, ()>;
     type WorkingSpace = DfsSpace<u8, HashSet<u8>>;
 
-    fn build_graph(input: &str) -> Graph {
-        Graph::from_edges(input.lines().take_while(|line| !line.is_empty()).map(|l| {
-            let mut iter = l.split('|').map(|n| n.parse::<u8>().unwrap());
-            (iter.next().unwrap(), iter.next().unwrap())
-        }))
-    }
-
-    fn get_order(whole_graph: &Graph, vals: &Vec<u8>, space: &mut WorkingSpace) -> Vec<u8> {
+    fn get_order(
+        whole_graph: &Graph,
+        vals: &[u8],
+        space: &mut WorkingSpace,
+    ) -> Result<Vec<u8>, Cycle<u8>> {
         let sub_graph = Graph::from_edges(
             whole_graph
                 .all_edges()
                 .filter(|(a, b, _)| vals.contains(a) && vals.contains(b)),
         );
 
-        toposort(&sub_graph, Some(space)).unwrap()
+        toposort(&sub_graph, Some(space))
     }
 
-    pub fn part1(input: &str) -> i32 {
-        let whole_graph = build_graph(input);
-
-        let mut dfs_space = DfsSpace::new(&whole_graph);
-
-        let result = input
-            .lines()
-            .skip_while(|line| !line.is_empty())
-            .skip(1)
-            .filter_map(|l| {
-                let vals = l
-                    .split(',')
-                    .map(|n| n.parse::<u8>().unwrap())
-                    .collect::<Vec<_>>();
-
-                let order = get_order(&whole_graph, &vals, &mut dfs_space);
-
-                match order.iter().eq(vals.iter()) {
-                    true => Some(vals.get(vals.len() / 2).unwrap().clone() as i32),
-                    false => None,
-                }
-            })
-            .sum();
-
-        return result;
+    fn cycle(input: &str, line: &str) -> ParseError {
+        ParseError::at(input, line, "the rules for these pages have a cycle")
     }
 
-    pub fn part2(input: &str) -> i32 {
-        let whole_graph = build_graph(input);
-
-        let mut dfs_space = DfsSpace::new(&whole_graph);
-
-        let result = input
-            .lines()
-            .skip_while(|line| !line.is_empty())
-            .skip(1)
-            .filter_map(|l| {
-                let mut vals = l
-                    .split(',')
-                    .map(|n| n.parse::<u8>().unwrap())
-                    .collect::<Vec<_>>();
-
-                let order = get_order(&whole_graph, &vals, &mut dfs_space);
-
-                let compare = |a: &u8, b: &u8| {
-                    let first = order.iter().find(|&&n| n == *a || n == *b).unwrap();
-                    match *first {
-                        x if x == *a => Ordering::Less,
-                        x if x == *b => Ordering::Greater,
-                        _ => panic!("weird result for first {}", first),
+    pub struct Solver;
+
+    impl Solution for Solver {
+        /// The input, the graph of ordering rules and the list of updates along with their lines,
+        /// so that the rules can be reported when they have a cycle
+        type Parsed<'a> = (&'a str, Graph, Vec<(&'a str, Vec<u8>)>);
+
+        fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+            let graph = Graph::from_edges(build_rules(input)?);
+            let updates = update_iter(input)
+                .map(|l| Ok((l, parse_update(input, l)?)))
+                .collect::<Result<_, _>>()?;
+            Ok((input, graph, updates))
+        }
+
+        fn part1(
+            (input, whole_graph, updates): &Self::Parsed<'_>,
+            _: &Params,
+        ) -> Result<Answer, ParseError> {
+            let mut dfs_space = DfsSpace::new(whole_graph);
+
+            let result = updates
+                .iter()
+                .map(|(line, vals)| {
+                    let order = get_order(whole_graph, vals, &mut dfs_space)
+                        .map_err(|_| cycle(input, line))?;
+
+                    match order.iter().eq(vals.iter()) {
+                        true => Ok(vals[vals.len() / 2] as i64),
+                        false => Ok(0),
                     }
-                };
+                })
+                .sum::<Result<i64, _>>()?;
+
+            Ok(result.into())
+        }
+
+        fn part2(
+            (input, whole_graph, updates): &Self::Parsed<'_>,
+            _: &Params,
+        ) -> Result<Answer, ParseError> {
+            let mut dfs_space = DfsSpace::new(whole_graph);
+
+            let result = updates
+                .iter()
+                .map(|(line, vals)| {
+                    let mut vals = vals.clone();
+                    let order = get_order(whole_graph, &vals, &mut dfs_space)
+                        .map_err(|_| cycle(input, line))?;
+
+                    // Pages that no rule mentions go last
+                    let position =
+                        |page: &u8| order.iter().position(|n| n == page).unwrap_or(order.len());
+
+                    let mid = vals.len() / 2;
+                    match vals.iter().eq(order.iter()) {
+                        true => Ok(0),
+                        false => Ok(*vals.select_nth_unstable_by_key(mid, position).1 as i64),
+                    }
+                })
+                .sum::<Result<i64, _>>()?;
 
-                let mid = vals.len() / 2;
-                match vals.iter().eq(order.iter()) {
-                    true => None,
-                    false => Some(vals.select_nth_unstable_by(mid, compare).1.clone() as i32),
-                }
-            })
-            .sum();
+            Ok(result.into())
+        }
+    }
 
-        return result;
+    #[cfg(test)]
+    mod tests {
+        use super::super::EXAMPLE;
+        use super::*;
+
+        #[test]
+        fn example_part1() {
+            let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
+            assert_eq!(result, 143)
+        }
+
+        #[test]
+        fn example_part2() {
+            let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
+            assert_eq!(result, 123)
+        }
+
+        #[test]
+        fn cyclic_rules() {
+            let input = "1|2\n2|3\n3|1\n\n1,2\n1,2,3";
+            for err in [
+                Solver::solve_part1(input).unwrap_err(),
+                Solver::solve_part2(input).unwrap_err(),
+            ] {
+                assert_eq!((err.line, err.column), (6, 1));
+            }
+        }
     }
 }
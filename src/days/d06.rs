@@ -2,6 +2,13 @@ use std::collections::HashSet;
 
 use ndarray::Array2;
 
+use super::{
+    cancel, parse,
+    registry::Example,
+    visualize::{self, Colour, Frame},
+    Answer, Params, ParseError, Solution,
+};
+
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
 enum Dir {
     U,
@@ -11,13 +18,13 @@ enum Dir {
 }
 
 #[derive(Copy, Clone, PartialEq, Eq)]
-enum V {
+pub enum V {
     Open,
     Obstacle,
 }
 
-type Pos = (usize, usize);
-type Grid = Array2<V>;
+pub type Pos = (usize, usize);
+pub type Grid = Array2<V>;
 
 impl std::fmt::Debug for V {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -28,39 +35,16 @@ impl std::fmt::Debug for V {
     }
 }
 
-fn build_grid(input: &str) -> (Grid, Pos) {
-    let ncols = input.lines().next().unwrap().len();
-    let nrows = ((input.len() + 1) / ncols) - 1;
-
-    let grid = Grid::from_shape_vec(
-        (nrows, ncols),
-        input
-            .chars()
-            .filter_map(|c| match c {
-                '.' | '^' => Some(V::Open),
-                '#' => Some(V::Obstacle),
-                _ => None,
-            })
-            .collect(),
-    )
-    .unwrap();
-
-    let pos: Pos = input
-        .lines()
-        .enumerate()
-        .find_map(|(i, l)| {
-            l.chars()
-                .enumerate()
-                .find_map(|(j, c)| match c {
-                    '^' => Some(j),
-                    _ => None,
-                })
-                .and_then(|j| j.try_into().ok())
-                .and_then(|j| i.try_into().ok().map(|i| (i, j)))
-        })
-        .unwrap();
-
-    (grid, pos)
+fn build_grid(input: &str) -> Result<(Grid, Pos), ParseError> {
+    let grid = parse::grid(input, input, |c| match c {
+        '.' | '^' => Some(V::Open),
+        '#' => Some(V::Obstacle),
+        _ => None,
+    })?;
+
+    let pos = parse::find_in_grid(input, input, '^')?;
+
+    Ok((grid, pos))
 }
 
 fn next_pos(pos: Pos, dir: Dir) -> Option<Pos> {
@@ -81,8 +65,27 @@ fn next_dir(dir: Dir) -> Dir {
     }
 }
 
-pub fn part1(input: &str) -> i64 {
-    let (grid, mut pos) = build_grid(input);
+/// The guard, the obstacles and where the guard has been
+fn frame(grid: &Grid, pos: Pos, dir: Dir, visited: &HashSet<Pos>) -> Frame {
+    let caption = format!("{} positions visited", visited.len());
+    Frame::grid(caption, grid.nrows(), grid.ncols(), |r, c| {
+        let guard = match dir {
+            Dir::U => '^',
+            Dir::D => 'v',
+            Dir::L => '<',
+            Dir::R => '>',
+        };
+        match grid[(r, c)] {
+            _ if (r, c) == pos => (guard, Colour::Red),
+            V::Obstacle => ('#', Colour::Grey),
+            V::Open if visited.contains(&(r, c)) => ('X', Colour::Yellow),
+            V::Open => ('.', Colour::Plain),
+        }
+    })
+}
+
+fn part1(grid: &Grid, start: Pos) -> Result<Answer, ParseError> {
+    let mut pos = start;
     let mut dir = Dir::U;
     let mut visited = HashSet::new();
 
@@ -90,6 +93,7 @@ pub fn part1(input: &str) -> i64 {
         next_pos(pos, dir).and_then(|next| grid.get(next).map(|v| (next, v)))
     {
         visited.insert(pos);
+        visualize::frame(|| frame(grid, pos, dir, &visited));
 
         match nextv {
             V::Open => pos = nextp,
@@ -99,7 +103,7 @@ pub fn part1(input: &str) -> i64 {
 
     visited.insert(pos);
 
-    return visited.len().try_into().unwrap();
+    Ok(visited.len().into())
 }
 
 struct GridWithObstacle<'a> {
@@ -116,22 +120,30 @@ impl<'a> GridWithObstacle<'a> {
     }
 }
 
+/// Whether the guard walks in a loop. This only ends if loops are detected correctly, so it can be
+/// cancelled, and checks every so many steps to keep the walk fast.
 fn check_loop_in_grid(
     grid: &GridWithObstacle,
     visited: &HashSet<(Pos, Dir)>,
     mut pos: Pos,
     mut dir: Dir,
     loop_visited: &mut HashSet<(Pos, Dir)>,
-) -> bool {
+) -> Result<bool, ParseError> {
     loop_visited.clear();
 
+    let mut steps = 0_u32;
     while let Some((nextp, nextv)) =
         next_pos(pos, dir).and_then(|next| grid.get(next).map(|v| (next, v)))
     {
+        steps = steps.wrapping_add(1);
+        if steps.is_multiple_of(4096) {
+            cancel::check()?;
+        }
+
         loop_visited.insert((pos, dir));
 
         if loop_visited.contains(&(nextp, dir)) || visited.contains(&(nextp, dir)) {
-            return true;
+            return Ok(true);
         }
 
         match nextv {
@@ -140,7 +152,7 @@ fn check_loop_in_grid(
         }
     }
 
-    false
+    Ok(false)
 }
 
 fn contains_any_direction(set: &HashSet<(Pos, Dir)>, pos: Pos) -> bool {
@@ -150,8 +162,8 @@ fn contains_any_direction(set: &HashSet<(Pos, Dir)>, pos: Pos) -> bool {
         || set.contains(&(pos, Dir::R))
 }
 
-pub fn part2(input: &str) -> i64 {
-    let (grid, mut pos) = build_grid(input);
+fn part2(grid: &Grid, start: Pos) -> Result<Answer, ParseError> {
+    let mut pos = start;
     let mut dir = Dir::U;
     let mut visited = HashSet::new();
     let mut working_space = HashSet::new();
@@ -167,10 +179,10 @@ pub fn part2(input: &str) -> i64 {
             && !contains_any_direction(&visited, nextp)
             && {
                 let new_grid = GridWithObstacle {
-                    grid: &grid,
+                    grid,
                     new_obstacle_pos: nextp,
                 };
-                check_loop_in_grid(&new_grid, &visited, pos, dir, &mut working_space)
+                check_loop_in_grid(&new_grid, &visited, pos, dir, &mut working_space)?
             }
         {
             result.insert(nextp);
@@ -182,14 +194,29 @@ pub fn part2(input: &str) -> i64 {
         }
     }
 
-    return result.len().try_into().unwrap();
+    Ok(result.len().into())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub struct Solver;
+
+impl Solution for Solver {
+    /// The grid and the guard's starting position
+    type Parsed<'a> = (Grid, Pos);
 
-    const EXAMPLE: &str = "
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        build_grid(input)
+    }
+
+    fn part1((grid, start): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        part1(grid, *start)
+    }
+
+    fn part2((grid, start): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        part2(grid, *start)
+    }
+}
+
+const EXAMPLE: &str = "
 ....#.....
 .........#
 ..........
@@ -202,15 +229,28 @@ mod tests {
 ......#...
 ";
 
+super::register! {
+    year: 2024,
+    day: 6,
+    title: "Guard Gallivant",
+    tags: ["grid", "simulation"],
+    implementations: { "default" => Solver },
+    examples: [Example::new(EXAMPLE).part1("41").part2("6")],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 41)
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 6)
     }
 }
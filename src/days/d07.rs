@@ -1,4 +1,6 @@
-fn check_equation_p1(test_result: i64, vals: &Vec<i64>, stack: &mut Vec<(i64, usize)>) -> bool {
+use super::{parse, registry::Example, Answer, Params, ParseError, Solution};
+
+fn check_equation_p1(test_result: i64, vals: &[i64], stack: &mut Vec<(i64, usize)>) -> bool {
     stack.clear();
     stack.push((test_result, 0));
 
@@ -30,7 +32,7 @@ fn remove_suffix(a: i64, b: i64) -> Option<i64> {
     }
 }
 
-fn check_equation_p2(test_result: i64, vals: &Vec<i64>, stack: &mut Vec<(i64, usize)>) -> bool {
+fn check_equation_p2(test_result: i64, vals: &[i64], stack: &mut Vec<(i64, usize)>) -> bool {
     stack.clear();
     stack.push((test_result, 0));
 
@@ -38,8 +40,7 @@ fn check_equation_p2(test_result: i64, vals: &Vec<i64>, stack: &mut Vec<(i64, us
         if i == vals.len() && target == 0 {
             return true;
         }
-        if i < vals.len() {
-            let x = vals.get(i).unwrap();
+        if let Some(x) = vals.get(i) {
             if target - x >= 0 {
                 stack.push((target - x, i + 1));
             }
@@ -55,48 +56,65 @@ fn check_equation_p2(test_result: i64, vals: &Vec<i64>, stack: &mut Vec<(i64, us
     false
 }
 
-fn solve(input: &str, check_fn: impl Fn(i64, &Vec<i64>, &mut Vec<(i64, usize)>) -> bool) -> i64 {
-    let mut vals: Vec<i64> = vec![];
-    let mut to_check_stack: Vec<(i64, usize)> = vec![];
-
-    let result: i64 = input
-        .lines()
-        .filter_map(|l| {
-            let mut iter = l.split(":");
-            let testn: i64 = iter.next().unwrap().parse().unwrap();
-
-            vals.clear();
-            vals.extend(
-                iter.next()
-                    .unwrap()
-                    .split_whitespace()
-                    .map(|n| n.parse::<i64>().unwrap())
-                    .rev(),
-            );
-
-            match check_fn(testn, &vals, &mut to_check_stack) {
-                true => Some(testn),
-                false => None,
-            }
+/// A test value and its numbers, which are stored in reverse so the checks can work backwards
+/// from the test value
+pub type Equation = (i64, Vec<i64>);
+
+/// The numbers must be positive, as the checks divide by them, and small enough that the test
+/// value can be split at any of them
+fn parse_equation(input: &str, l: &str) -> Result<Equation, ParseError> {
+    let (test, rest) = parse::split_once(input, l, ":")?;
+    let testn = parse::number::<u64>(input, test)?
+        .try_into()
+        .map_err(|_| ParseError::at(input, test, "test value is too large"))?;
+    let vals = rest
+        .split_whitespace()
+        .rev()
+        .map(|n| match parse::number::<u32>(input, n)? {
+            0 => Err(ParseError::at(input, n, "expected a positive number")),
+            x => Ok(x as i64),
         })
-        .sum();
-
-    result
+        .collect::<Result<_, _>>()?;
+    Ok((testn, vals))
 }
 
-pub fn part1(input: &str) -> i64 {
-    solve(input, check_equation_p1)
-}
+fn solve(
+    equations: &[Equation],
+    check_fn: impl Fn(i64, &[i64], &mut Vec<(i64, usize)>) -> bool,
+) -> i128 {
+    let mut to_check_stack: Vec<(i64, usize)> = vec![];
 
-pub fn part2(input: &str) -> i64 {
-    solve(input, check_equation_p2)
+    equations
+        .iter()
+        .map(
+            |(testn, vals)| match check_fn(*testn, vals, &mut to_check_stack) {
+                // Summed in i128, as enough large test values would overflow an i64
+                true => *testn as i128,
+                false => 0,
+            },
+        )
+        .sum()
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub struct Solver;
+
+impl Solution for Solver {
+    type Parsed<'a> = Vec<Equation>;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        input.lines().map(|l| parse_equation(input, l)).collect()
+    }
 
-    const EXAMPLE: &str = "
+    fn part1(equations: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        Ok(solve(equations, check_equation_p1).into())
+    }
+
+    fn part2(equations: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        Ok(solve(equations, check_equation_p2).into())
+    }
+}
+
+const EXAMPLE: &str = "
 190: 10 19
 3267: 81 40 27
 83: 17 5
@@ -108,15 +126,34 @@ mod tests {
 292: 11 6 16 20
 ";
 
+super::register! {
+    year: 2024,
+    day: 7,
+    title: "Bridge Repair",
+    tags: ["search"],
+    implementations: { "default" => Solver },
+    examples: [Example::new(EXAMPLE).part1("3749").part2("11387")],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 3749)
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 11387)
     }
+
+    #[test]
+    fn zero_in_equation() {
+        let err = Solver::parse("190: 10 19\n10: 0 5").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 5));
+    }
 }
@@ -1,34 +1,38 @@
 use itertools::Itertools;
 use std::collections::{HashMap, HashSet};
 
+use super::{parse, registry::Example, Answer, Params, ParseError, Solution};
+
 fn in_bounds(pos: (i16, i16), bounds: (i16, i16)) -> bool {
     pos.0 >= 0 && pos.1 >= 0 && pos.0 < bounds.0 && pos.1 < bounds.1
 }
 
-// build iterator over pairs of the same type of antennas
-fn build_positions(input: &str) -> ((i16, i16), impl Iterator<Item = ((i16, i16), (i16, i16))>) {
-    let ncols: i16 = (input.lines().next().unwrap().len()).try_into().unwrap();
-    let nrows: i16 = (((input.len() + 1) / (ncols as usize)) - 1)
-        .try_into()
-        .unwrap();
+pub type Positions = ((i16, i16), (i16, i16));
+
+// build the grid bounds and every pair of the same type of antennas
+fn build_positions(input: &str) -> Result<((i16, i16), Vec<Positions>), ParseError> {
+    let grid = parse::grid(input, input, Some)?;
+
+    let too_large = |_| ParseError::at(input, input, "grid is too large");
+    let nrows: i16 = grid.nrows().try_into().map_err(too_large)?;
+    let ncols: i16 = grid.ncols().try_into().map_err(too_large)?;
 
     let mut positions = HashMap::new();
 
-    input
-        .lines()
-        .enumerate()
-        .flat_map(|(i, l)| {
-            l.chars().enumerate().filter_map(move |(j, c)| match c {
-                '.' => None,
-                x => Some((x, (i as i16, j as i16))),
-            })
-        })
-        .for_each(|(c, pos)| positions.entry(c).or_insert_with(Vec::new).push(pos));
+    grid.indexed_iter()
+        .filter(|(_, &c)| c != '.')
+        .for_each(|((i, j), &c)| {
+            positions
+                .entry(c)
+                .or_insert_with(Vec::new)
+                .push((i as i16, j as i16))
+        });
     let positions_iter = positions
         .into_values()
-        .flat_map(|ps| ps.into_iter().tuple_combinations::<(_, _)>());
+        .flat_map(|ps| ps.into_iter().tuple_combinations::<(_, _)>())
+        .collect();
 
-    ((nrows, ncols), positions_iter)
+    Ok(((nrows, ncols), positions_iter))
 }
 
 struct NodeIterator {
@@ -40,12 +44,12 @@ struct NodeIterator {
 
 impl NodeIterator {
     fn new(pos1: (i16, i16), pos2: (i16, i16), bounds: (i16, i16)) -> Self {
-        return Self {
+        Self {
             pos1,
             step: (pos2.0 - pos1.0, pos2.1 - pos1.1),
             bounds,
             coeff: 0,
-        };
+        }
     }
 
     fn calc_next(&self) -> (i16, i16) {
@@ -83,34 +87,37 @@ impl Iterator for NodeIterator {
     }
 }
 
-pub fn part1(input: &str) -> i64 {
-    let ((nrows, ncols), positions) = build_positions(input);
+pub struct Solver;
 
-    positions
-        .flat_map(|((a, b), (x, y))| [(2 * x - a, 2 * y - b), (2 * a - x, 2 * b - y)])
-        .filter(|&p| in_bounds(p, (nrows, ncols)))
-        .collect::<HashSet<(i16, i16)>>()
-        .len()
-        .try_into()
-        .unwrap()
-}
+impl Solution for Solver {
+    /// The grid bounds and every pair of antennas of the same type
+    type Parsed<'a> = ((i16, i16), Vec<Positions>);
 
-pub fn part2(input: &str) -> i64 {
-    let ((nrows, ncols), positions) = build_positions(input);
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        build_positions(input)
+    }
 
-    positions
-        .flat_map(|(p1, p2)| NodeIterator::new(p1, p2, (nrows, ncols)))
-        .collect::<HashSet<(i16, i16)>>()
-        .len()
-        .try_into()
-        .unwrap()
-}
+    fn part1((bounds, positions): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        Ok(positions
+            .iter()
+            .flat_map(|&((a, b), (x, y))| [(2 * x - a, 2 * y - b), (2 * a - x, 2 * b - y)])
+            .filter(|&p| in_bounds(p, *bounds))
+            .collect::<HashSet<(i16, i16)>>()
+            .len()
+            .into())
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    fn part2((bounds, positions): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        Ok(positions
+            .iter()
+            .flat_map(|&(p1, p2)| NodeIterator::new(p1, p2, *bounds))
+            .collect::<HashSet<(i16, i16)>>()
+            .len()
+            .into())
+    }
+}
 
-    const EXAMPLE: &str = "
+const EXAMPLE: &str = "
 ............
 ........0...
 .....0......
@@ -125,15 +132,28 @@ mod tests {
 ............
 ";
 
+super::register! {
+    year: 2024,
+    day: 8,
+    title: "Resonant Collinearity",
+    tags: ["grid"],
+    implementations: { "default" => Solver },
+    examples: [Example::new(EXAMPLE).part1("14").part2("34")],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 14)
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 34)
     }
 }
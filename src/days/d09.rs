@@ -1,71 +1,80 @@
 use itertools::Itertools;
 
+use super::{registry::Example, Answer, Params, ParseError, Solution};
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
-struct Block {
+pub struct Block {
     offset: u32,
     size: u8,
 }
 
-pub fn part1(input: &str) -> i64 {
-    let offsets = input
-        .bytes()
-        .filter_map(|c| match c {
-            x if x >= b'0' && x <= b'9' => Some(c - b'0'),
-            _ => None,
+fn build_blocks(input: &str) -> Result<Vec<Block>, ParseError> {
+    input
+        .trim_end()
+        .char_indices()
+        .map(|(i, c)| match c.to_digit(10) {
+            Some(d) => Ok(d as u8),
+            None => Err(ParseError::at(
+                input,
+                &input[i..],
+                format!("expected a digit, found {:?}", c),
+            )),
         })
         .scan(0_u32, |i, size| {
-            let offset = *i;
-            *i += size as u32;
-            Some(Block { offset, size })
+            Some(size.map(|size| {
+                let offset = *i;
+                *i += size as u32;
+                Block { offset, size }
+            }))
         })
-        .collect_vec();
+        .collect()
+}
 
+fn part1(offsets: &[Block]) -> Result<Answer, ParseError> {
     let final_length: u32 = offsets.iter().step_by(2).map(|b| b.size as u32).sum();
 
     let mut files = offsets.iter().cloned().step_by(2).enumerate().collect_vec();
 
     let mut new_files = vec![];
     let mut old_files = files.iter_mut().rev().peekable();
-    let mut gaps = offsets.iter().skip(1).step_by(2).cloned();
+    let gaps = offsets.iter().skip(1).step_by(2).cloned();
 
-    while let Some(mut gap) = gaps.next() {
+    for mut gap in gaps {
         if gap.offset > final_length {
             break;
         }
         while gap.size > 0 {
-            let last_file = old_files.peek_mut().unwrap();
-            match last_file.1.size {
-                s if s > gap.size => {
-                    new_files.push((
-                        last_file.0,
-                        Block {
-                            offset: gap.offset,
-                            size: gap.size,
-                        },
-                    ));
-                    last_file.1.size -= gap.size;
-                    gap.size = 0;
-                }
-                _ => {
-                    let popped = old_files.next().unwrap();
-                    new_files.push((
-                        popped.0,
-                        Block {
-                            offset: gap.offset,
-                            size: popped.1.size,
-                        },
-                    ));
-                    gap.offset += popped.1.size as u32;
-                    gap.size -= popped.1.size;
-                    popped.1.size = 0;
-                }
+            if let Some(popped) = old_files.next_if(|file| file.1.size <= gap.size) {
+                new_files.push((
+                    popped.0,
+                    Block {
+                        offset: gap.offset,
+                        size: popped.1.size,
+                    },
+                ));
+                gap.offset += popped.1.size as u32;
+                gap.size -= popped.1.size;
+                popped.1.size = 0;
+            } else if let Some(last_file) = old_files.peek_mut() {
+                new_files.push((
+                    last_file.0,
+                    Block {
+                        offset: gap.offset,
+                        size: gap.size,
+                    },
+                ));
+                last_file.1.size -= gap.size;
+                gap.size = 0;
+            } else {
+                // Every file has been moved, which only happens if they're all empty
+                break;
             }
         }
     }
 
     let result: u64 = files
         .into_iter()
-        .chain(new_files.into_iter())
+        .chain(new_files)
         .map(|(id, b)| {
             let n = b.offset as u64;
             let m = n + b.size as u64;
@@ -73,36 +82,24 @@ pub fn part1(input: &str) -> i64 {
         })
         .sum();
 
-    return result.try_into().unwrap();
+    Ok(result.into())
 }
 
-pub fn part2(input: &str) -> i64 {
+fn part2(blocks: &[Block]) -> Result<Answer, ParseError> {
     let mut files = vec![];
     let mut gaps = vec![];
 
-    input
-        .bytes()
-        .filter_map(|c| match c {
-            x if x >= b'0' && x <= b'9' => Some(c - b'0'),
-            _ => None,
-        })
-        .scan(0_u32, |i, size| {
-            let offset = *i;
-            *i += size as u32;
-            Some(Block { offset, size })
-        })
-        .enumerate()
-        .for_each(|(i, b)| {
-            if i % 2 == 0 {
-                files.push(b)
-            } else if b.size > 0 {
-                gaps.push(b)
-            }
-        });
+    blocks.iter().cloned().enumerate().for_each(|(i, b)| {
+        if i % 2 == 0 {
+            files.push(b)
+        } else if b.size > 0 {
+            gaps.push(b)
+        }
+    });
 
     for (i, f) in files.iter_mut().rev().enumerate() {
         // Gaps after the current file can never be used
-        while gaps.last().map_or(false, |g| g.offset > f.offset) {
+        while gaps.last().is_some_and(|g| g.offset > f.offset) {
             gaps.pop();
         }
 
@@ -111,11 +108,11 @@ pub fn part2(input: &str) -> i64 {
             gaps.retain(|&g| g.size > 0)
         }
 
-        gaps.iter_mut().find(|g| g.size >= f.size).map(|gap| {
+        if let Some(gap) = gaps.iter_mut().find(|g| g.size >= f.size) {
             f.offset = gap.offset;
             gap.offset += f.size as u32;
             gap.size -= f.size;
-        });
+        }
     }
 
     let result: u64 = files
@@ -128,26 +125,62 @@ pub fn part2(input: &str) -> i64 {
         })
         .sum();
 
-    result.try_into().unwrap()
+    Ok(result.into())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub struct Solver;
+
+impl Solution for Solver {
+    /// Alternating file and free space blocks
+    type Parsed<'a> = Vec<Block>;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        build_blocks(input)
+    }
+
+    fn part1(blocks: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        part1(blocks)
+    }
+
+    fn part2(blocks: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        part2(blocks)
+    }
+}
 
-    const EXAMPLE: &str = "
+const EXAMPLE: &str = "
 2333133121414131402
 ";
 
+super::register! {
+    year: 2024,
+    day: 9,
+    title: "Disk Fragmenter",
+    implementations: { "default" => Solver },
+    examples: [Example::new(EXAMPLE).part1("1928").part2("2858")],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 1928)
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 2858)
     }
+
+    #[test]
+    fn empty_files() {
+        assert_eq!(Solver::solve_part1("09").unwrap(), 0);
+        assert_eq!(Solver::solve_part2("09").unwrap(), 0);
+
+        let err = Solver::parse("12x4").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 3));
+    }
 }
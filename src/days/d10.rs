@@ -1,27 +1,16 @@
 use itertools::Itertools;
 use ndarray::Array2;
 
+use super::{parse, registry::Example, Answer, Params, ParseError, Solution};
+
 type Pos = (usize, usize);
 
-fn build_grid(input: &str) -> Array2<u8> {
-    let ncols = input.lines().next().unwrap().len();
-    let nrows = ((input.len() + 1) / ncols) - 1;
-
-    Array2::from_shape_vec(
-        (nrows, ncols),
-        input
-            .bytes()
-            .filter_map(|x| match x {
-                x if x >= b'0' && x <= b'9' => Some(x - b'0'),
-                _ => None,
-            })
-            .collect(),
-    )
-    .unwrap()
+fn build_grid(input: &str) -> Result<Array2<u8>, ParseError> {
+    parse::grid(input, input, |c| c.to_digit(10).map(|d| d as u8))
 }
 
 fn neighbours(grid: &Array2<u8>, p: Pos) -> Vec<Pos> {
-    let x = grid.get(p).unwrap();
+    let x = grid[p];
     [
         p.0.checked_sub(1).map(|i| (i, p.1)),
         p.0.checked_add(1).map(|i| (i, p.1)),
@@ -29,33 +18,33 @@ fn neighbours(grid: &Array2<u8>, p: Pos) -> Vec<Pos> {
         p.1.checked_add(1).map(|j| (p.0, j)),
     ]
     .into_iter()
-    .filter_map(move |p| p.filter(|p| grid.get(*p).map_or(false, |y| *y == (x + 1))))
+    .filter_map(move |p| p.filter(|p| grid.get(*p).is_some_and(|&y| y == x + 1)))
     .collect_vec()
 }
 
-fn score_trail(grid: &Array2<u8>, start: Pos, stack: &mut Vec<Pos>, ends: &mut Vec<Pos>) -> u32 {
+fn score_trail(grid: &Array2<u8>, start: Pos, stack: &mut Vec<Pos>, ends: &mut Vec<Pos>) -> usize {
     ends.clear();
     stack.clear();
     stack.push(start);
 
     while let Some(p) = stack.pop() {
-        if *grid.get(p).unwrap() == 9 && !ends.contains(&p) {
+        if grid[p] == 9 && !ends.contains(&p) {
             ends.push(p)
         }
         stack.extend(neighbours(grid, p));
     }
 
-    ends.len().try_into().unwrap()
+    ends.len()
 }
 
-fn rate_trail(grid: &Array2<u8>, start: Pos, stack: &mut Vec<Pos>, _ends: &mut Vec<Pos>) -> u32 {
+fn rate_trail(grid: &Array2<u8>, start: Pos, stack: &mut Vec<Pos>, _ends: &mut Vec<Pos>) -> usize {
     let mut rating = 0;
 
     stack.clear();
     stack.push(start);
 
     while let Some(p) = stack.pop() {
-        if *grid.get(p).unwrap() == 9 {
+        if grid[p] == 9 {
             rating += 1
         }
         stack.extend(neighbours(grid, p));
@@ -64,36 +53,38 @@ fn rate_trail(grid: &Array2<u8>, start: Pos, stack: &mut Vec<Pos>, _ends: &mut V
     rating
 }
 
-pub fn solve(
-    input: &str,
-    score_fn: impl Fn(&Array2<u8>, Pos, &mut Vec<Pos>, &mut Vec<Pos>) -> u32,
-) -> i64 {
-    let grid = build_grid(input);
-
+fn solve(
+    grid: &Array2<u8>,
+    score_fn: impl Fn(&Array2<u8>, Pos, &mut Vec<Pos>, &mut Vec<Pos>) -> usize,
+) -> usize {
     let mut stack = vec![];
     let mut ends = vec![];
 
     grid.indexed_iter()
         .filter_map(|(i, x)| if *x == 0 { Some(i) } else { None })
-        .map(|p| score_fn(&grid, p, &mut stack, &mut ends))
-        .sum::<u32>()
-        .try_into()
-        .unwrap()
+        .map(|p| score_fn(grid, p, &mut stack, &mut ends))
+        .sum()
 }
 
-pub fn part1(input: &str) -> i64 {
-    solve(input, score_trail)
-}
+pub struct Solver;
 
-pub fn part2(input: &str) -> i64 {
-    solve(input, rate_trail)
-}
+impl Solution for Solver {
+    type Parsed<'a> = Array2<u8>;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        build_grid(input)
+    }
+
+    fn part1(grid: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        Ok(solve(grid, score_trail).into())
+    }
+
+    fn part2(grid: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        Ok(solve(grid, rate_trail).into())
+    }
+}
 
-    const EXAMPLE: &str = "
+const EXAMPLE: &str = "
 89010123
 78121874
 87430965
@@ -104,7 +95,7 @@ mod tests {
 10456732
 ";
 
-    const EXAMPLE2: &str = "
+const EXAMPLE2: &str = "
 1110111
 1111111
 1112111
@@ -114,21 +105,43 @@ mod tests {
 9111119
 ";
 
+super::register! {
+    year: 2024,
+    day: 10,
+    title: "Hoof It",
+    tags: ["grid", "pathfinding"],
+    implementations: { "default" => Solver },
+    examples: [
+        Example::new(EXAMPLE).part1("36").part2("81"),
+        Example::new(EXAMPLE2).part1("2"),
+    ],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example2_part1() {
-        let result = part1(EXAMPLE2.trim());
+        let result = Solver::solve_part1(EXAMPLE2.trim()).unwrap();
         assert_eq!(result, 2)
     }
 
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 36)
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 81)
     }
+
+    #[test]
+    fn impassable_tiles() {
+        let err = Solver::parse("0123\n1.34").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 2));
+    }
 }
@@ -1,60 +1,153 @@
-use cached::{proc_macro::cached, Cached};
+use std::collections::HashMap;
 
-#[cached]
-fn count_stones(s: u64, depth: u8) -> u64 {
+use super::{
+    cancel, params::Value, parse, registry::Example, Answer, Param, Params, ParseError, Solution,
+};
+
+type Cache = HashMap<(u64, u8), u64>;
+
+/// How many stones `s` turns into after `depth` blinks, or `None` if a stone's number or the count
+/// no longer fits in a u64
+fn count_stones(s: u64, depth: u8, cache: &mut Cache) -> Option<u64> {
     if depth == 0 {
-        return 1;
+        return Some(1);
+    }
+
+    if let Some(&count) = cache.get(&(s, depth)) {
+        return Some(count);
     }
 
+    let count = blink(s, depth, cache)?;
+    cache.insert((s, depth), count);
+    Some(count)
+}
+
+fn blink(s: u64, depth: u8, cache: &mut Cache) -> Option<u64> {
     if s == 0 {
-        return count_stones(1, depth - 1);
+        return count_stones(1, depth - 1, cache);
     }
 
-    // Calculate ceil rounded log 10 to get the number of digits. Add 0.1 to ensure that exact
-    // powers of 10 round up to the next one.
-    let log_s = (s as f64 + 0.1).log10().ceil() as u32;
-    if log_s != 0 && log_s % 2 == 0 {
-        let split_factor = 10_u64.pow(log_s / 2);
-        return count_stones(s / split_factor, depth - 1)
-            + count_stones(s % split_factor, depth - 1);
+    // In integers, as a float can't tell large powers of 10 from the numbers just below them
+    let digits = s.ilog10() + 1;
+    if digits.is_multiple_of(2) {
+        let split_factor = 10_u64.pow(digits / 2);
+        return count_stones(s / split_factor, depth - 1, cache)?.checked_add(count_stones(
+            s % split_factor,
+            depth - 1,
+            cache,
+        )?);
     }
 
-    return count_stones(s * 2024, depth - 1);
+    count_stones(s.checked_mul(2024)?, depth - 1, cache)
 }
 
-fn solve(input: &str, depth: u8) -> i64 {
-    // Clear the cache each time so we have fair timings
-    COUNT_STONES.lock().unwrap().cache_clear();
+fn solve(input: &str, stones: &[(&str, u64)], depth: u8) -> Result<u64, ParseError> {
+    // A fresh cache for each call so that timings are fair and concurrent runs don't share state
+    let mut cache = Cache::new();
+
+    stones.iter().try_fold(0_u64, |total, &(at, s)| {
+        cancel::check()?;
+        count_stones(s, depth, &mut cache)
+            .and_then(|count| total.checked_add(count))
+            .ok_or_else(|| ParseError::at(input, at, "too many stones to count in a u64"))
+    })
+}
 
-    let res = input
-        .split_whitespace()
-        .map(|s| count_stones(s.parse().unwrap(), depth))
-        .sum::<u64>()
-        .try_into()
-        .unwrap();
+/// Blinks are counted in a u8. The stones overflow a u64 long before that many, which is reported
+/// as an error.
+const MAX_BLINKS: u64 = u8::MAX as u64;
 
-    res
+fn blinks(params: &Params, name: &str) -> u8 {
+    u8::try_from(params.int(name)).expect("blinks are limited to MAX_BLINKS")
 }
 
-pub fn part1(input: &str) -> i64 {
-    solve(input, 25)
+pub struct Solver;
+
+impl Solution for Solver {
+    /// The input, and each stone with where it is in the input
+    type Parsed<'a> = (&'a str, Vec<(&'a str, u64)>);
+
+    const PARAMS: &'static [Param] = &[
+        Param::int(
+            "part1_blinks",
+            25,
+            "how many times the stones blink in part 1",
+        )
+        .range(0, MAX_BLINKS),
+        Param::int(
+            "part2_blinks",
+            75,
+            "how many times the stones blink in part 2",
+        )
+        .range(0, MAX_BLINKS),
+    ];
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        let stones = input
+            .split_whitespace()
+            .map(|s| Ok((s, parse::number(input, s)?)))
+            .collect::<Result<_, _>>()?;
+        Ok((input, stones))
+    }
+
+    fn part1((input, stones): &Self::Parsed<'_>, params: &Params) -> Result<Answer, ParseError> {
+        Ok(solve(input, stones, blinks(params, "part1_blinks"))?.into())
+    }
+
+    fn part2((input, stones): &Self::Parsed<'_>, params: &Params) -> Result<Answer, ParseError> {
+        Ok(solve(input, stones, blinks(params, "part2_blinks"))?.into())
+    }
 }
 
-pub fn part2(input: &str) -> i64 {
-    solve(input, 75)
+const EXAMPLE: &str = "
+125 17
+";
+
+super::register! {
+    year: 2024,
+    day: 11,
+    title: "Plutonian Pebbles",
+    tags: ["memoization"],
+    implementations: { "default" => Solver },
+    examples: [
+        Example::new(EXAMPLE).part1("55312"),
+        // The puzzle also shows the stones after six blinks
+        Example::new(EXAMPLE)
+            .part1("22")
+            .params(&[("part1_blinks", Value::Int(6))]),
+    ],
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const EXAMPLE: &str = "
-125 17
-";
-
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 55312)
     }
+
+    #[test]
+    fn fewer_blinks() {
+        let mut params = Params::defaults(Solver::PARAMS);
+        params.set_str("part1_blinks", "6").unwrap();
+        let stones = Solver::parse(EXAMPLE.trim()).unwrap();
+        assert_eq!(Solver::part1(&stones, &params).unwrap(), 22);
+    }
+
+    #[test]
+    fn too_many_stones() {
+        let mut params = Params::defaults(Solver::PARAMS);
+        params.set_str("part1_blinks", "255").unwrap();
+        let stones = Solver::parse("125 17").unwrap();
+        let err = Solver::part1(&stones, &params).unwrap_err();
+        assert_eq!((err.line, err.column), (1, 1));
+
+        // Too large to multiply by 2024, and with an odd number of digits so that it isn't split
+        params.set_str("part1_blinks", "1").unwrap();
+        let stones = Solver::parse("0 10000000000000000").unwrap();
+        let err = Solver::part1(&stones, &params).unwrap_err();
+        assert_eq!((err.line, err.column), (1, 3));
+    }
 }
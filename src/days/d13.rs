@@ -1,23 +1,39 @@
-pub fn solve(input: &str, offset: i64) -> i64 {
-    input
-        .split("\n\n")
-        .map(|machine_input| {
-            let mut ls = machine_input.lines();
-            let a = ls.next().unwrap()[12..].split_once(", Y+").unwrap();
-            let b = ls.next().unwrap()[12..].split_once(", Y+").unwrap();
-            let x = ls.next().unwrap()[9..].split_once(", Y=").unwrap();
-            (
-                a.0.parse::<i64>().unwrap(),
-                a.1.parse::<i64>().unwrap(),
-                b.0.parse::<i64>().unwrap(),
-                b.1.parse::<i64>().unwrap(),
-                x.0.parse::<i64>().unwrap(),
-                x.1.parse::<i64>().unwrap(),
-            )
-        })
-        .map(|(x1, y1, x2, y2, x3, y3)| {
-            let x3offset = x3 + offset;
-            let y3offset = y3 + offset;
+use super::{parse, registry::Example, Answer, Param, Params, ParseError, Solution};
+
+fn parse_line(
+    input: &str,
+    l: &str,
+    prefix: &str,
+    separator: &str,
+) -> Result<(i64, i64), ParseError> {
+    let (x, y) = parse::split_once(input, parse::strip_prefix(input, l, prefix)?, separator)?;
+    let x: u32 = parse::number(input, x)?;
+    let y: u32 = parse::number(input, y)?;
+    Ok((x.into(), y.into()))
+}
+
+/// The movements of buttons A and B, and the position of the prize, each below 2^32
+pub type Machine = [(i64, i64); 3];
+
+fn parse_machine(input: &str, machine_input: &str) -> Result<Machine, ParseError> {
+    let mut ls = machine_input.lines();
+    let mut next = |expected| parse::next(input, machine_input, &mut ls, expected);
+    Ok([
+        parse_line(input, next("button A")?, "Button A: X+", ", Y+")?,
+        parse_line(input, next("button B")?, "Button B: X+", ", Y+")?,
+        parse_line(input, next("prize")?, "Prize: X=", ", Y=")?,
+    ])
+}
+
+/// The fewest tokens that win every prize that can be won. Moves and prizes are below 2^32 and the
+/// offset below 2^63, so in i128 none of the products below reach 2^97.
+fn solve(machines: &[Machine], offset: i64) -> i128 {
+    machines
+        .iter()
+        .map(|&[(x1, y1), (x2, y2), (x3, y3)]| {
+            let [x1, y1, x2, y2] = [x1, y1, x2, y2].map(i128::from);
+            let x3offset = i128::from(x3) + i128::from(offset);
+            let y3offset = i128::from(y3) + i128::from(offset);
 
             let det = x1 * y2 - x2 * y1;
 
@@ -25,31 +41,49 @@ pub fn solve(input: &str, offset: i64) -> i64 {
                 return 0;
             }
 
-            let a = (x3offset * y2 - x2 * y3offset) / det;
-            let b = (y3offset * x1 - x3offset * y1) / det;
+            // Cramer's rule, which only gives whole numbers of presses if both divide exactly
+            let a = x3offset * y2 - x2 * y3offset;
+            let b = y3offset * x1 - x3offset * y1;
 
-            if a * x1 + b * x2 == x3offset && a * y1 + b * y2 == y3offset {
-                a * 3 + b
+            if a % det == 0 && b % det == 0 {
+                a / det * 3 + b / det
             } else {
                 0
             }
         })
-        .sum::<i64>()
+        .sum()
 }
 
-pub fn part1(input: &str) -> i64 {
-    solve(input, 0)
-}
+pub struct Solver;
 
-pub fn part2(input: &str) -> i64 {
-    solve(input, 10000000000000)
-}
+impl Solution for Solver {
+    type Parsed<'a> = Vec<Machine>;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    // Anything an i64 holds, which solve allows for
+    const PARAMS: &'static [Param] = &[Param::int(
+        "offset",
+        10000000000000,
+        "how far the prizes are moved in part 2",
+    )
+    .range(0, i64::MAX as u64)];
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        input
+            .split("\n\n")
+            .map(|machine_input| parse_machine(input, machine_input))
+            .collect()
+    }
+
+    fn part1(machines: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        Ok(solve(machines, 0).into())
+    }
 
-    const EXAMPLE: &str = "
+    fn part2(machines: &Self::Parsed<'_>, params: &Params) -> Result<Answer, ParseError> {
+        Ok(solve(machines, params.int("offset") as i64).into())
+    }
+}
+
+const EXAMPLE: &str = "
 Button A: X+94, Y+34
 Button B: X+22, Y+67
 Prize: X=8400, Y=5400
@@ -67,9 +101,40 @@ Button B: X+27, Y+71
 Prize: X=18641, Y=10279
 ";
 
+super::register! {
+    year: 2024,
+    day: 13,
+    title: "Claw Contraption",
+    tags: ["math"],
+    implementations: { "default" => Solver },
+    examples: [Example::new(EXAMPLE).part1("480")],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 480)
     }
+    #[test]
+    fn malformed_prize() {
+        let input = "Button A: X+94, Y+34\nButton B: X+22, Y+67\nPrize: X=8400, Y=54x0";
+        let err = Solver::parse(input).unwrap_err();
+        assert_eq!((err.line, err.column), (3, 18));
+    }
+
+    #[test]
+    fn largest_offset() {
+        let mut params = Params::defaults(Solver::PARAMS);
+        params.set_str("offset", &i64::MAX.to_string()).unwrap();
+        let machines = Solver::parse(EXAMPLE.trim()).unwrap();
+        assert!(Solver::part2(&machines, &params).is_ok());
+
+        let err = Solver::parse("Button A: X+4294967296, Y+1\nButton B: X+1, Y+1\nPrize: X=1, Y=1")
+            .unwrap_err();
+        assert_eq!((err.line, err.column), (1, 13));
+    }
 }
@@ -1,21 +1,69 @@
 use std::collections::HashSet;
 
-fn solve_p1(input: &str, size: [i32; 2]) -> i64 {
-    let mut quadrants = [0, 0, 0, 0];
+use super::{
+    cancel,
+    params::Value,
+    parse,
+    registry::Example,
+    visualize::{self, Colour, Frame},
+    Answer, Param, Params, ParseError, Solution,
+};
+
+/// A robot's position and velocity
+pub type Robot = ([i32; 2], [i32; 2]);
+
+fn parse_pair(input: &str, s: &str, prefix: &str) -> Result<[i32; 2], ParseError> {
+    let (a, b) = parse::split_once(input, parse::strip_prefix(input, s, prefix)?, ",")?;
+    Ok([parse::number(input, a)?, parse::number(input, b)?])
+}
 
+fn parse_robots(input: &str) -> Result<Vec<Robot>, ParseError> {
     input
         .lines()
         .map(|l| {
-            let (ps, vs) = l.split_once(' ').unwrap();
+            let (ps, vs) = parse::split_once(input, l, " ")?;
+            Ok((parse_pair(input, ps, "p=")?, parse_pair(input, vs, "v=")?))
+        })
+        .collect()
+}
 
-            let p = ps[2..].split_once(',').unwrap();
-            let v = vs[2..].split_once(',').unwrap();
+/// The longest side a room may have. A frame of the largest room has a million tiles, and as robots
+/// move less than the room's size each second, a hundred seconds of movement fits in an i32.
+const MAX_SIZE: u64 = 1000;
+
+const _: () = assert!(101 * MAX_SIZE <= i32::MAX as u64);
+
+/// Check that every robot starts in the room, and moves less than its size each second so that
+/// [`solve_p1`] can't overflow
+fn check_robots(robots: &[Robot], size: [i32; 2]) -> Result<(), ParseError> {
+    for (i, (p, v)) in robots.iter().enumerate() {
+        // Each robot is on a line of its own
+        let at = |message| ParseError {
+            line: i + 1,
+            column: 1,
+            message,
+        };
+        if (0..2).any(|k| !(0..size[k]).contains(&p[k])) {
+            return Err(at(format!(
+                "robot at {},{} is outside the {}x{} room",
+                p[0], p[1], size[0], size[1]
+            )));
+        }
+        if (0..2).any(|k| v[k].unsigned_abs() >= size[k] as u32) {
+            return Err(at(format!(
+                "robot moving {},{} a second is faster than the {}x{} room allows",
+                v[0], v[1], size[0], size[1]
+            )));
+        }
+    }
+    Ok(())
+}
 
-            (
-                [p.0.parse::<i32>().unwrap(), p.1.parse::<i32>().unwrap()],
-                [v.0.parse::<i32>().unwrap(), v.1.parse::<i32>().unwrap()],
-            )
-        })
+fn solve_p1(robots: &[Robot], size: [i32; 2]) -> i64 {
+    let mut quadrants = [0, 0, 0, 0];
+
+    robots
+        .iter()
         .map(|(p, v)| {
             [
                 (p[0] + 100 * v[0]).rem_euclid(size[0]),
@@ -23,7 +71,6 @@ fn solve_p1(input: &str, size: [i32; 2]) -> i64 {
             ]
         })
         .for_each(|p| {
-            // println!("{:?}", p);
             if p[0] != size[0] / 2 && p[1] != size[1] / 2 {
                 match (p[0] < size[0] / 2, p[1] < size[1] / 2) {
                     (false, false) => quadrants[0] += 1,
@@ -37,53 +84,90 @@ fn solve_p1(input: &str, size: [i32; 2]) -> i64 {
     quadrants.into_iter().product()
 }
 
-pub fn part1(input: &str) -> i64 {
-    solve_p1(input, [101, 103])
+/// How many robots are on each tile of the room
+fn frame(robots: &[Robot], size: [i32; 2], seconds: u64) -> Frame {
+    let (w, h) = (size[0] as usize, size[1] as usize);
+    let mut counts = vec![0_u32; w * h];
+    for (p, _) in robots {
+        counts[p[1] as usize * w + p[0] as usize] += 1;
+    }
+    Frame::grid(
+        format!("after {} seconds", seconds),
+        h,
+        w,
+        |r, c| match counts[r * w + c] {
+            0 => ('.', Colour::Grey),
+            n @ 1..=9 => (char::from_digit(n, 10).unwrap(), Colour::Green),
+            _ => ('+', Colour::Green),
+        },
+    )
 }
 
-pub fn part2(input: &str) -> i64 {
-    let size = [101, 103];
-    let mut robots = input
-        .lines()
-        .map(|l| {
-            let (ps, vs) = l.split_once(' ').unwrap();
-
-            let p = ps[2..].split_once(',').unwrap();
-            let v = vs[2..].split_once(',').unwrap();
-
-            (
-                [p.0.parse::<i32>().unwrap(), p.1.parse::<i32>().unwrap()],
-                [v.0.parse::<i32>().unwrap(), v.1.parse::<i32>().unwrap()],
-            )
-        })
-        .collect::<Vec<_>>();
+/// Find the first frame after `skip` seconds with every robot in a different place. There may not
+/// be one, so this runs until it's cancelled.
+fn solve_p2(robots: &[Robot], size: [i32; 2], skip: u64) -> Result<Answer, ParseError> {
+    let mut robots = robots.to_vec();
 
     let mut uniques = HashSet::new();
-    let mut i = 0;
+    let mut i: u64 = 0;
     loop {
+        cancel::check()?;
         uniques.clear();
 
         robots.iter_mut().for_each(|(p, v)| {
             p[0] = (p[0] + v[0]).rem_euclid(size[0]);
             p[1] = (p[1] + v[1]).rem_euclid(size[1]);
 
-            if i > 8000 {
+            if i >= skip {
                 uniques.insert(*p);
             }
         });
 
         i += 1;
-        if i > 8000 && robots.len() == uniques.len() {
-            return i;
+        visualize::frame(|| frame(&robots, size, i));
+        if i > skip && robots.len() == uniques.len() {
+            return Ok(i.into());
         }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub struct Solver;
+
+impl Solution for Solver {
+    type Parsed<'a> = Vec<Robot>;
+
+    const PARAMS: &'static [Param] = &[
+        Param::size("size", 101, 103, "the size of the room").range(1, MAX_SIZE),
+        Param::int(
+            "part2_skip",
+            8000,
+            "how many seconds part 2 lets pass before it starts looking",
+        ),
+    ];
 
-    const EXAMPLE: &str = "
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        parse_robots(input)
+    }
+
+    fn part1(robots: &Self::Parsed<'_>, params: &Params) -> Result<Answer, ParseError> {
+        let size = room_size(params);
+        check_robots(robots, size)?;
+        Ok(solve_p1(robots, size).into())
+    }
+
+    fn part2(robots: &Self::Parsed<'_>, params: &Params) -> Result<Answer, ParseError> {
+        let size = room_size(params);
+        check_robots(robots, size)?;
+        solve_p2(robots, size, params.int("part2_skip"))
+    }
+}
+
+fn room_size(params: &Params) -> [i32; 2] {
+    let (w, h) = params.size("size");
+    [w as i32, h as i32]
+}
+
+const EXAMPLE: &str = "
 p=0,4 v=3,-3
 p=6,3 v=-1,-3
 p=10,3 v=-1,2
@@ -98,9 +182,43 @@ p=2,4 v=2,-3
 p=9,5 v=-3,-3
 ";
 
+super::register! {
+    year: 2024,
+    day: 14,
+    title: "Restroom Redoubt",
+    tags: ["grid", "simulation"],
+    implementations: { "default" => Solver },
+    examples: [
+        Example::new(EXAMPLE)
+            .part1("12")
+            .params(&[("size", Value::Size(11, 7))]),
+    ],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1() {
-        let result = solve_p1(EXAMPLE.trim(), [11, 7]);
+        let result = solve_p1(&parse_robots(EXAMPLE.trim()).unwrap(), [11, 7]);
         assert_eq!(result, 12)
     }
+
+    #[test]
+    fn part2_without_skipping() {
+        let robots = parse_robots(EXAMPLE.trim()).unwrap();
+        assert_eq!(solve_p2(&robots, [11, 7], 0).unwrap(), 1);
+    }
+
+    #[test]
+    fn robots_outside_the_room() {
+        let robots = parse_robots("p=0,0 v=1,1\np=11,0 v=1,1").unwrap();
+        let err = check_robots(&robots, [11, 7]).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+
+        let robots = parse_robots("p=0,0 v=1,-7").unwrap();
+        let err = check_robots(&robots, [11, 7]).unwrap_err();
+        assert_eq!((err.line, err.column), (1, 1));
+    }
 }
@@ -2,58 +2,71 @@ use std::collections::HashSet;
 
 use ndarray::{s, Array2};
 
+use super::{
+    parse,
+    registry::Example,
+    visualize::{self, Colour, Frame},
+    Answer, Params, ParseError, Solution,
+};
+
 #[derive(Clone, Copy, Debug, PartialEq)]
-enum Map {
+pub enum Map {
     Open,
     Box,
     Wall,
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
-enum Dir {
+pub enum Dir {
     U,
     D,
     L,
     R,
 }
 
-type Pos = (usize, usize);
-type Grid = Array2<Map>;
-
-fn build_grid(input: &str) -> (Grid, Pos) {
-    let ncols = input.lines().next().unwrap().len();
-    let nrows = ((input.len() + 1) / ncols) - 1;
-
-    let grid = Array2::from_shape_vec(
-        (nrows, ncols),
-        input
-            .chars()
-            .filter_map(|c| match c {
-                '#' => Some(Map::Wall),
-                '.' | '@' => Some(Map::Open),
-                'O' => Some(Map::Box),
-                _ => None,
-            })
-            .collect(),
-    )
-    .unwrap();
+pub type Pos = (usize, usize);
+pub type Grid = Array2<Map>;
 
-    let pos: Pos = input
-        .lines()
-        .enumerate()
-        .find_map(|(i, l)| {
-            l.chars()
-                .enumerate()
-                .find_map(|(j, c)| match c {
-                    '@' => Some(j),
-                    _ => None,
-                })
-                .and_then(|j| j.try_into().ok())
-                .and_then(|j| i.try_into().ok().map(|i| (i, j)))
+fn parse_input(input: &str) -> Result<(Grid, Pos, Vec<Dir>), ParseError> {
+    let (map, commands) = parse::split_once(input, input, "\n\n")?;
+
+    let grid = parse::grid(input, map, |c| match c {
+        '#' => Some(Map::Wall),
+        '.' | '@' => Some(Map::Open),
+        'O' => Some(Map::Box),
+        _ => None,
+    })?;
+
+    // Moves rely on the walls around the edge to keep everything in the warehouse. The map starts
+    // the input, so its cells are located like the input's.
+    let (rows, cols) = grid.dim();
+    let edge = |(r, c): Pos| r == 0 || c == 0 || r == rows - 1 || c == cols - 1;
+    if let Some(((r, c), _)) = grid
+        .indexed_iter()
+        .find(|&(p, &m)| edge(p) && m != Map::Wall)
+    {
+        return Err(ParseError::at_cell(r, c, "expected a wall around the edge"));
+    }
+
+    let pos = parse::find_in_grid(input, map, '@')?;
+
+    let commands = commands
+        .char_indices()
+        .filter(|(_, c)| *c != '\n')
+        .map(|(i, c)| match c {
+            '^' => Ok(Dir::U),
+            'v' => Ok(Dir::D),
+            '<' => Ok(Dir::L),
+            '>' => Ok(Dir::R),
+            _ => Err(ParseError::at(
+                input,
+                &commands[i..],
+                format!("invalid move {:?}", c),
+            )),
         })
-        .unwrap();
+        .collect::<Result<_, _>>()?;
 
-    (grid, pos)
+    Ok((grid, pos, commands))
 }
 
 fn incr_pos(pos: Pos, dir: Dir) -> Pos {
@@ -73,14 +86,15 @@ fn do_move_p1(grid: &mut Grid, pos: Pos, dir: Dir) -> Pos {
         Dir::R => grid.slice_mut(s![pos.0, (pos.1 + 1)..]),
     };
 
+    // There's always one, around the edge
     let first_wall = full_slice
         .iter()
         .position(|x| matches!(*x, Map::Wall))
-        .unwrap();
+        .unwrap_or(full_slice.len());
 
     let mut move_slice = full_slice.slice_mut(s![..first_wall]);
 
-    if move_slice.len() == 0 {
+    if move_slice.is_empty() {
         return pos;
     }
 
@@ -96,20 +110,36 @@ fn do_move_p1(grid: &mut Grid, pos: Pos, dir: Dir) -> Pos {
     }
 }
 
-pub fn part1(input: &str) -> i64 {
-    let (map, commands) = input.split_once("\n\n").unwrap();
-    let (mut grid, mut pos) = build_grid(map);
+fn caption(moves: usize, commands: &[Dir]) -> String {
+    format!("{} of {} moves", moves, commands.len())
+}
 
-    let command_iter = commands.chars().filter_map(|c| match c {
-        '^' => Some(Dir::U),
-        'v' => Some(Dir::D),
-        '<' => Some(Dir::L),
-        '>' => Some(Dir::R),
-        _ => None,
-    });
+fn robot(pos: Pos, r: usize, c: usize) -> Option<(char, Colour)> {
+    ((r, c) == pos).then_some(('@', Colour::Red))
+}
 
-    for c in command_iter {
+fn frame_p1(grid: &Grid, pos: Pos, moves: usize, commands: &[Dir]) -> Frame {
+    Frame::grid(
+        caption(moves, commands),
+        grid.nrows(),
+        grid.ncols(),
+        |r, c| {
+            robot(pos, r, c).unwrap_or(match grid[(r, c)] {
+                Map::Open => ('.', Colour::Plain),
+                Map::Box => ('O', Colour::Yellow),
+                Map::Wall => ('#', Colour::Grey),
+            })
+        },
+    )
+}
+
+fn part1(grid: &Grid, start: Pos, commands: &[Dir]) -> Result<Answer, ParseError> {
+    let mut grid = grid.clone();
+    let mut pos = start;
+
+    for (i, &c) in commands.iter().enumerate() {
         pos = do_move_p1(&mut grid, pos, c);
+        visualize::frame(|| frame_p1(&grid, pos, i + 1, commands));
     }
 
     let result = grid
@@ -118,14 +148,14 @@ pub fn part1(input: &str) -> i64 {
         .map(|((x, y), _)| x * 100 + y)
         .sum::<usize>();
 
-    result.try_into().unwrap()
+    Ok(result.into())
 }
 
 fn wide_box(pos: Pos) -> Pos {
     (pos.0, pos.1 + 1)
 }
 
-fn do_move_p2(pos: Pos, dir: Dir, walls: &HashSet<Pos>, boxes: &mut Vec<Pos>) -> Pos {
+fn do_move_p2(pos: Pos, dir: Dir, walls: &HashSet<Pos>, boxes: &mut [Pos]) -> Pos {
     let new_pos = incr_pos(pos, dir);
 
     if walls.contains(&new_pos) {
@@ -183,58 +213,79 @@ fn do_move_p2(pos: Pos, dir: Dir, walls: &HashSet<Pos>, boxes: &mut Vec<Pos>) ->
     }
 }
 
-pub fn part2(input: &str) -> i64 {
-    let (map, commands) = input.split_once("\n\n").unwrap();
+/// The wide warehouse, which is `size` before it's widened
+fn frame_p2(
+    size: (usize, usize),
+    walls: &HashSet<Pos>,
+    boxes: &[Pos],
+    pos: Pos,
+    moves: usize,
+    commands: &[Dir],
+) -> Frame {
+    let mut tiles = Array2::from_elem((size.0, size.1 * 2), ('.', Colour::Plain));
+    for &w in walls {
+        tiles[w] = ('#', Colour::Grey);
+    }
+    for &b in boxes {
+        tiles[b] = ('[', Colour::Yellow);
+        tiles[wide_box(b)] = (']', Colour::Yellow);
+    }
+    Frame::grid(
+        caption(moves, commands),
+        tiles.nrows(),
+        tiles.ncols(),
+        |r, c| robot(pos, r, c).unwrap_or(tiles[(r, c)]),
+    )
+}
 
+fn part2(grid: &Grid, start: Pos, commands: &[Dir]) -> Result<Answer, ParseError> {
     let mut walls = HashSet::new();
     let mut boxes = Vec::new();
-    let mut pos = (0, 0);
-
-    for (i, l) in map.lines().enumerate() {
-        for (j, c) in l.chars().enumerate() {
-            match c {
-                '#' => {
-                    walls.insert((i, j * 2));
-                    walls.insert((i, j * 2 + 1));
-                }
-                'O' => {
-                    boxes.push((i, j * 2));
-                }
-                '@' => {
-                    pos = (i, j * 2);
-                }
-                _ => {}
+    let mut pos = (start.0, start.1 * 2);
+
+    for ((i, j), v) in grid.indexed_iter() {
+        match v {
+            Map::Wall => {
+                walls.insert((i, j * 2));
+                walls.insert((i, j * 2 + 1));
+            }
+            Map::Box => {
+                boxes.push((i, j * 2));
             }
+            Map::Open => {}
         }
     }
 
-    let command_iter = commands.chars().filter_map(|c| match c {
-        '^' => Some(Dir::U),
-        'v' => Some(Dir::D),
-        '<' => Some(Dir::L),
-        '>' => Some(Dir::R),
-        _ => None,
-    });
-
-    for c in command_iter {
+    for (i, &c) in commands.iter().enumerate() {
         pos = do_move_p2(pos, c, &walls, &mut boxes);
+        visualize::frame(|| frame_p2(grid.dim(), &walls, &boxes, pos, i + 1, commands));
     }
 
-    let result = boxes
-        .into_iter()
-        .map(|(x, y)| x * 100 + y)
-        .sum::<usize>()
-        .try_into()
-        .unwrap();
+    let result = boxes.into_iter().map(|(x, y)| x * 100 + y).sum::<usize>();
 
-    result
+    Ok(result.into())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub struct Solver;
+
+impl Solution for Solver {
+    /// The warehouse, the robot's starting position and its moves
+    type Parsed<'a> = (Grid, Pos, Vec<Dir>);
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1((grid, start, commands): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        part1(grid, *start, commands)
+    }
 
-    const EXAMPLE: &str = "
+    fn part2((grid, start, commands): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        part2(grid, *start, commands)
+    }
+}
+
+const EXAMPLE: &str = "
 ##########
 #..O..O.O#
 #......O.#
@@ -258,7 +309,7 @@ vvv<<^>^v^^><<>>><>^<<><^vv^^<>vvv<>><^^v>^>vv<>v<<<<v<^v>^<^^>>>^<v<v
 v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^
 ";
 
-    const EXAMPLE_2: &str = "
+const EXAMPLE_2: &str = "
 ########
 #..O.O.#
 ##@.O..#
@@ -271,7 +322,7 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^
 <^^>>>vv<v>>v<<
 ";
 
-    const EXAMPLE_3: &str = "
+const EXAMPLE_3: &str = "
 #######
 #...#.#
 #.....#
@@ -283,27 +334,50 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^
 <vv<<^^<<^^
 ";
 
+super::register! {
+    year: 2024,
+    day: 15,
+    title: "Warehouse Woes",
+    tags: ["grid", "simulation"],
+    implementations: { "default" => Solver },
+    examples: [
+        Example::new(EXAMPLE).part1("10092").part2("9021"),
+        Example::new(EXAMPLE_2).part1("2028"),
+        Example::new(EXAMPLE_3).part2("618"),
+    ],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 10092)
     }
 
     #[test]
     fn example2_part1() {
-        let result = part1(EXAMPLE_2.trim());
+        let result = Solver::solve_part1(EXAMPLE_2.trim()).unwrap();
         assert_eq!(result, 2028)
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 9021)
     }
 
     #[test]
     fn example2_part2() {
-        let result = part2(EXAMPLE_3.trim());
-        assert_eq!(result, (1 * 100 + 5) + (2 * 100 + 7) + (3 * 100 + 6))
+        let result = Solver::solve_part2(EXAMPLE_3.trim()).unwrap();
+        assert_eq!(result, (100 + 5) + (2 * 100 + 7) + (3 * 100 + 6))
+    }
+
+    #[test]
+    fn open_edge() {
+        let err = Solver::parse("#####\n#.@..\n#####\n\n>>").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 5));
     }
 }
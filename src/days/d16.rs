@@ -1,61 +1,50 @@
 use ndarray::Array2;
 use std::collections::HashMap;
 
-use super::utils;
-
-type Grid = Array2<V>;
-type Pos = (u8, u8);
+use super::{
+    parse,
+    registry::Example,
+    utils,
+    visualize::{self, Colour, Frame},
+    Answer, Params, ParseError, Solution,
+};
+
+pub type Grid = Array2<V>;
+pub type Pos = (u8, u8);
 type Dir = (i16, i16);
 type Cost = u64;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum V {
+pub enum V {
     Open,
     Wall,
 }
 
-fn build_grid(input: &str) -> Grid {
-    let ncols = input.lines().next().unwrap().len();
-    let nrows = ((input.len() + 1) / ncols) - 1;
-
-    let grid = Grid::from_shape_vec(
-        (nrows, ncols),
-        input
-            .chars()
-            .filter_map(|c| match c {
-                '.' | 'S' | 'E' => Some(V::Open),
-                '#' => Some(V::Wall),
-                _ => None,
-            })
-            .collect(),
-    )
-    .unwrap();
-
-    grid
-}
-
-fn find_pos(input: &str, to_find: char) -> Pos {
-    input
-        .lines()
-        .enumerate()
-        .find_map(|(i, l)| {
-            l.chars()
-                .enumerate()
-                .find_map(|(j, c)| match c {
-                    c if c == to_find => Some(j),
-                    _ => None,
-                })
-                .and_then(|j| j.try_into().ok())
-                .and_then(|j| i.try_into().ok().map(|i| (i, j)))
-        })
-        .unwrap()
+fn build_grid(input: &str) -> Result<Grid, ParseError> {
+    let grid = parse::grid(input, input, |c| match c {
+        '.' | 'S' | 'E' => Some(V::Open),
+        '#' => Some(V::Wall),
+        _ => None,
+    })?;
+
+    // positions are stored as u8 to keep the cost maps small
+    if grid.nrows() > u8::MAX as usize || grid.ncols() > u8::MAX as usize {
+        return Err(ParseError::at(input, input, "maze is too large"));
+    }
+
+    Ok(grid)
+}
+
+fn find_pos(input: &str, to_find: char) -> Result<Pos, ParseError> {
+    let (i, j) = parse::find_in_grid(input, input, to_find)?;
+    Ok((i as u8, j as u8))
 }
 
+/// The directions after turning either way, where `dir` is one of the four
 fn next_dirs(dir: Dir) -> impl Iterator<Item = Dir> {
     match dir {
         (0, _) => [(-1, 0), (1, 0)].into_iter(),
-        (_, 0) => [(0, -1), (0, 1)].into_iter(),
-        _ => panic!("invalid direction: {:?}", dir),
+        _ => [(0, -1), (0, 1)].into_iter(),
     }
 }
 
@@ -66,50 +55,77 @@ fn increment_pos(p: Pos, d: Dir) -> Option<Pos> {
 }
 
 fn neighbours(p: Pos, d: Dir, grid: &Grid) -> impl Iterator<Item = ((Pos, Dir), Cost)> + use<'_> {
-    let forward = std::iter::once(
-        increment_pos(p, d)
-            .filter(|p| {
-                grid.get((p.0 as usize, p.1 as usize))
-                    .map_or(false, |&v| v == V::Open)
-            })
-            .map(|p| ((p, d), 1 as Cost)),
-    )
-    .filter_map(|x| x);
+    let forward = increment_pos(p, d)
+        .filter(|p| {
+            grid.get((p.0 as usize, p.1 as usize))
+                .is_some_and(|&v| v == V::Open)
+        })
+        .map(|p| ((p, d), 1 as Cost))
+        .into_iter();
 
-    let turning = next_dirs(d).map(move |d| ((p, d), 1000 as Cost));
+    let turning = next_dirs(d).map(move |d| ((p, d), 1000));
     forward.chain(turning)
 }
 
-pub fn part1(input: &str) -> i64 {
-    let grid = build_grid(input);
-    let start = find_pos(input, 'S');
-    let end = find_pos(input, 'E');
+/// The maze, with the tiles for which `marked` is true picked out
+fn frame(
+    grid: &Grid,
+    start: Pos,
+    end: Pos,
+    caption: String,
+    marked: impl Fn(Pos) -> bool,
+) -> Frame {
+    Frame::grid(caption, grid.nrows(), grid.ncols(), |r, c| {
+        let p = (r as u8, c as u8);
+        match grid[(r, c)] {
+            _ if p == start => ('S', Colour::Red),
+            _ if p == end => ('E', Colour::Red),
+            V::Wall => ('#', Colour::Grey),
+            V::Open if marked(p) => ('O', Colour::Cyan),
+            V::Open => ('.', Colour::Plain),
+        }
+    })
+}
 
+fn part1(grid: &Grid, start: Pos, end: Pos) -> Result<Answer, ParseError> {
     let mut working_space = utils::DijkstraWorkingSpace::new();
 
     let costs = utils::dijkstra_cost_map(
         (start, (0, 1)),
-        |(p, d)| neighbours(p, d, &grid),
+        |(p, d)| neighbours(p, d, grid),
         &mut working_space,
-        45_000
-    );
+        45_000,
+    )?;
 
     let result = get_min_cost(&costs, end);
+    visualize::frame(|| {
+        let caption = "tiles explored while finding the lowest score".to_string();
+        frame(grid, start, end, caption, |p| {
+            get_min_cost(&costs, p).is_some()
+        })
+    });
 
-    result.unwrap().try_into().unwrap()
+    result.map(Answer::from).ok_or_else(|| unreachable(end))
+}
+
+fn unreachable(end: Pos) -> ParseError {
+    ParseError::at_cell(
+        end.0 as usize,
+        end.1 as usize,
+        "the end can't be reached from the start",
+    )
 }
 
 fn get_min_cost(map: &HashMap<(Pos, Dir), Cost>, p: Pos) -> Option<Cost> {
     [(0, 1), (0, -1), (1, 0), (-1, 0)]
-        .map(|d| map.get(&(p, d)))
         .into_iter()
-        .filter_map(|x| x)
+        .filter_map(|d| map.get(&(p, d)))
         .cloned()
         .min()
 }
 
 fn reverse_dir(d: Dir) -> Dir {
-    (d.0 * -1, d.1 * -1)
+    (-d.0, -d.1)
 }
 
 fn is_on_shortest_path(
@@ -124,55 +140,75 @@ fn is_on_shortest_path(
         .any(|(f_dir, r_dir)| {
             let f = forward_costs.get(&(p, *f_dir));
             let r = reverse_costs.get(&(p, r_dir));
-            f.zip(r).map(|(&f, &r)| f + r == goal).unwrap_or(false)
+            f.zip(r).is_some_and(|(&f, &r)| f + r == goal)
         })
 }
 
-pub fn part2(input: &str) -> i64 {
-    let grid = build_grid(input);
-    let start = find_pos(input, 'S');
-    let end = find_pos(input, 'E');
-
+fn part2(grid: &Grid, start: Pos, end: Pos) -> Result<Answer, ParseError> {
     let mut working_space = utils::DijkstraWorkingSpace::new();
 
     let forward_costs = utils::dijkstra_cost_map(
         (start, (0, 1)),
-        |(p, d)| neighbours(p, d, &grid),
+        |(p, d)| neighbours(p, d, grid),
         &mut working_space,
         45_000,
-    );
+    )?;
 
-    let lowest_end_cost = get_min_cost(&forward_costs, end).unwrap();
+    let lowest_end_cost = get_min_cost(&forward_costs, end).ok_or_else(|| unreachable(end))?;
 
     let ((_, d), _) = forward_costs
         .iter()
         .find(|((p, _), c)| *p == end && **c == lowest_end_cost)
-        .unwrap();
-    let reversed_dir = (d.0 * -1, d.1 * -1);
+        .ok_or_else(|| unreachable(end))?;
+    let reversed_dir = reverse_dir(*d);
 
     let reverse_costs = utils::dijkstra_cost_map(
         (end, reversed_dir),
-        |(p, d)| neighbours(p, d, &grid),
+        |(p, d)| neighbours(p, d, grid),
         &mut working_space,
-        forward_costs.len()
-    );
+        forward_costs.len(),
+    )?;
 
+    let on_path = |p| is_on_shortest_path(&forward_costs, &reverse_costs, p, lowest_end_cost);
     let count = grid
         .indexed_iter()
         .filter(|(_, v)| **v == V::Open)
         .map(|(p, _)| (p.0 as u8, p.1 as u8))
-        .filter(|&p| is_on_shortest_path(&forward_costs, &reverse_costs, p, lowest_end_cost))
+        .filter(|&p| on_path(p))
         .count()
         + 1;
+    visualize::frame(|| {
+        let caption = format!("{} tiles on the best paths", count);
+        frame(grid, start, end, caption, on_path)
+    });
 
-    count.try_into().unwrap()
+    Ok(count.into())
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub struct Solver;
+
+impl Solution for Solver {
+    /// The maze and its start and end positions
+    type Parsed<'a> = (Grid, Pos, Pos);
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        Ok((
+            build_grid(input)?,
+            find_pos(input, 'S')?,
+            find_pos(input, 'E')?,
+        ))
+    }
+
+    fn part1(&(ref grid, start, end): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        part1(grid, start, end)
+    }
+
+    fn part2(&(ref grid, start, end): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        part2(grid, start, end)
+    }
+}
 
-    const EXAMPLE: &str = "
+const EXAMPLE: &str = "
 ###############
 #.......#....E#
 #.#.###.#.###.#
@@ -190,7 +226,7 @@ mod tests {
 ###############
 ";
 
-    const EXAMPLE2: &str = "
+const EXAMPLE2: &str = "
 #################
 #...#...#...#..E#
 #.#.#.#.#.#.#.#.#
@@ -210,27 +246,54 @@ mod tests {
 #################
 ";
 
+super::register! {
+    year: 2024,
+    day: 16,
+    title: "Reindeer Maze",
+    tags: ["grid", "pathfinding"],
+    implementations: { "default" => Solver },
+    examples: [
+        Example::new(EXAMPLE).part1("7036").part2("45"),
+        Example::new(EXAMPLE2).part1("11048").part2("64"),
+    ],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1_1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 7036)
     }
 
     #[test]
     fn example_part1_2() {
-        let result = part1(EXAMPLE2.trim());
+        let result = Solver::solve_part1(EXAMPLE2.trim()).unwrap();
         assert_eq!(result, 11048)
     }
 
     #[test]
     fn example_part2_1() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 45)
     }
 
     #[test]
     fn example_part2_2() {
-        let result = part2(EXAMPLE2.trim());
+        let result = Solver::solve_part2(EXAMPLE2.trim()).unwrap();
         assert_eq!(result, 64)
     }
+
+    #[test]
+    fn unreachable_end() {
+        let input = "#####\n#S#E#\n#####";
+        for err in [
+            Solver::solve_part1(input).unwrap_err(),
+            Solver::solve_part2(input).unwrap_err(),
+        ] {
+            assert_eq!((err.line, err.column), (2, 4));
+        }
+    }
 }
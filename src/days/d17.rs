@@ -1,3 +1,7 @@
+use itertools::Itertools;
+
+use super::{cancel, parse, registry::Example, Answer, Params, ParseError, Solution};
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 enum Op {
     Adv,
@@ -10,24 +14,26 @@ enum Op {
     Cdv,
 }
 
-impl From<u8> for Op {
-    fn from(source: u8) -> Self {
+impl TryFrom<u8> for Op {
+    type Error = ();
+
+    fn try_from(source: u8) -> Result<Self, ()> {
         match source {
-            0 => Op::Adv,
-            1 => Op::Bxl,
-            2 => Op::Bst,
-            3 => Op::Jnz,
-            4 => Op::Bxc,
-            5 => Op::Out,
-            6 => Op::Bdv,
-            7 => Op::Cdv,
-            _ => panic!("invalid op code: {}", source),
+            0 => Ok(Op::Adv),
+            1 => Ok(Op::Bxl),
+            2 => Ok(Op::Bst),
+            3 => Ok(Op::Jnz),
+            4 => Ok(Op::Bxc),
+            5 => Ok(Op::Out),
+            6 => Ok(Op::Bdv),
+            7 => Ok(Op::Cdv),
+            _ => Err(()),
         }
     }
 }
 
 #[derive(Debug, Copy, Clone)]
-struct Machine {
+pub struct Machine {
     a: u64,
     b: u64,
     c: u64,
@@ -45,19 +51,59 @@ impl Machine {
     }
 }
 
-fn parse_input(input: &str) -> (Machine, Vec<u8>) {
+/// The program's 3-bit values, with where each of them is in the input for errors
+#[derive(Debug)]
+pub struct Program<'a> {
+    input: &'a str,
+    values: Vec<u8>,
+    sources: Vec<&'a str>,
+}
+
+impl Program<'_> {
+    fn error(&self, i: usize, message: impl Into<String>) -> ParseError {
+        ParseError::at(self.input, self.sources[i], message)
+    }
+}
+
+fn parse_program<'a>(input: &'a str, l: &'a str) -> Result<Program<'a>, ParseError> {
+    let sources: Vec<_> = l.split(',').collect();
+    let values = sources
+        .iter()
+        .map(|&x| match parse::number::<u8>(input, x)? {
+            x if x < 8 => Ok(x),
+            _ => Err(ParseError::at(
+                input,
+                x,
+                format!("invalid 3-bit value {:?}", x),
+            )),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok(Program {
+        input,
+        values,
+        sources,
+    })
+}
+
+fn parse_input(input: &str) -> Result<(Machine, Program<'_>), ParseError> {
     let mut ls = input.lines();
-    let a = ls.next().unwrap()[12..].parse::<u64>().unwrap();
-    let b = ls.next().unwrap()[12..].parse::<u64>().unwrap();
-    let c = ls.next().unwrap()[12..].parse::<u64>().unwrap();
+    let mut register = |name: &str| {
+        let l = parse::next(input, input, &mut ls, &format!("register {}", name))?;
+        parse::number::<u64>(
+            input,
+            parse::strip_prefix(input, l, &format!("Register {}: ", name))?,
+        )
+    };
+    let a = register("A")?;
+    let b = register("B")?;
+    let c = register("C")?;
+
+    parse::next(input, input, &mut ls, "blank line")?;
+    let l = parse::next(input, input, &mut ls, "program")?;
 
-    let program: Vec<_> = ls.skip(1).next().unwrap()[9..]
-        .bytes()
-        .step_by(2)
-        .map(|x| x - b'0')
-        .collect();
+    let program = parse_program(input, parse::strip_prefix(input, l, "Program: ")?)?;
 
-    (
+    Ok((
         Machine {
             a,
             b,
@@ -65,120 +111,156 @@ fn parse_input(input: &str) -> (Machine, Vec<u8>) {
             pointer: 0,
         },
         program,
-    )
+    ))
 }
 
-const fn combo(m: &Machine, literal: u8) -> u64 {
+/// The value of a combo operand, or `None` for the reserved operand 7
+const fn combo(m: &Machine, literal: u8) -> Option<u64> {
     match literal {
-        x if x <= 3 => x as u64,
-        4 => m.a,
-        5 => m.b,
-        6 => m.c,
-        _ => panic!("invalid op code"),
+        x if x <= 3 => Some(x as u64),
+        4 => Some(m.a),
+        5 => Some(m.b),
+        6 => Some(m.c),
+        _ => None,
     }
 }
 
-fn run(mut m: Machine, program: &Vec<u8>) -> (Machine, Vec<u8>) {
-    let end = program.len();
+/// `a` divided by 2 to the power of `exponent`, which is 0 once the divisor is past `u64`
+fn divide(a: u64, exponent: u64) -> u64 {
+    u32::try_from(exponent)
+        .ok()
+        .and_then(|e| a.checked_shr(e))
+        .unwrap_or(0)
+}
+
+/// Run the program until it halts, or until the run is cancelled since a program may never halt
+fn run(mut m: Machine, program: &Program) -> Result<(Machine, Vec<u8>), ParseError> {
+    let values = &program.values;
     let mut out = Vec::new();
-    while m.pointer < end - 1 {
-        let opcode: Op = program[m.pointer].into();
-        let operand = program[m.pointer + 1];
+    while m.pointer + 1 < values.len() {
+        let opcode = Op::try_from(values[m.pointer])
+            .map_err(|_| program.error(m.pointer, "invalid opcode"))?;
+        let operand = values[m.pointer + 1];
+        let combo = || {
+            combo(&m, operand)
+                .ok_or_else(|| program.error(m.pointer + 1, "combo operand 7 is reserved"))
+        };
         match opcode {
-            Op::Adv => m.a = m.a / (1 << combo(&m, operand)),
-            Op::Bxl => m.b = m.b ^ operand as u64,
-            Op::Bst => m.b = combo(&m, operand) % 8,
+            Op::Adv => m.a = divide(m.a, combo()?),
+            Op::Bxl => m.b ^= operand as u64,
+            Op::Bst => m.b = combo()? % 8,
             Op::Jnz => {
                 if m.a != 0 {
+                    cancel::check()?;
                     m.pointer = operand as usize
                 }
             }
-            Op::Bxc => m.b = m.b ^ m.c,
+            Op::Bxc => m.b ^= m.c,
             Op::Out => {
-                let x = (combo(&m, operand) % 8) as u8;
+                let x = (combo()? % 8) as u8;
                 out.push(x);
             }
-            Op::Bdv => m.b = m.a / (1 << combo(&m, operand)),
-            Op::Cdv => m.c = m.a / (1 << combo(&m, operand)),
+            Op::Bdv => m.b = divide(m.a, combo()?),
+            Op::Cdv => m.c = divide(m.a, combo()?),
         }
         if !(opcode == Op::Jnz && m.a != 0) {
             m.pointer += 2;
         }
     }
-    (m, out)
+    Ok((m, out))
 }
 
-pub fn part1(input: &str) -> i64 {
-    let (m, program) = parse_input(input);
-    let (_, out) = run(m, &program);
-
-    // print as string
-    // println!(
-    //     "{:?}",
-    //     out[1..].iter().fold(out[0].to_string(), |mut acc, x| {
-    //         acc.push(',');
-    //         acc.push_str(x.to_string().as_str());
-    //         acc
-    //     })
-    // );
-
-    // convert the output to a number because that's what my types expect
-    let numeric: i64 = out.iter().fold(0, |acc, &x| acc * 10 + x as i64);
-    return numeric;
+pub struct Solver;
+
+impl Solution for Solver {
+    /// The initial state of the machine and its program
+    type Parsed<'a> = (Machine, Program<'a>);
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1((m, program): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        let (_, out) = run(*m, program)?;
+
+        Ok(out.iter().join(",").into())
+    }
+
+    fn part2((_, program): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        find_quine(program).map(Answer::from)
+    }
 }
 
-pub fn part2(input: &str) -> i64 {
-    let (_, program) = parse_input(input);
+const EXAMPLE: &str = "
+Register A: 729
+Register B: 0
+Register C: 0
 
+Program: 0,1,5,4,3,0
+";
+
+const EXAMPLE_2: &str = "
+Register A: 2024
+Register B: 0
+Register C: 0
+
+Program: 0,3,5,4,3,0
+";
+
+super::register! {
+    year: 2024,
+    day: 17,
+    title: "Chronospatial Computer",
+    tags: ["vm"],
+    implementations: { "default" => Solver },
+    examples: [
+        Example::new(EXAMPLE).part1("4,6,3,5,6,3,5,2,1,0"),
+        Example::new(EXAMPLE_2).part2("117440"),
+    ],
+}
+
+/// Find the lowest value of register A that makes the program output itself
+fn find_quine(program: &Program) -> Result<u64, ParseError> {
+    let values = &program.values;
     let mut stack = vec![(0, 0)];
 
     let result = loop {
-        let (x, n) = stack.pop().unwrap();
-        if n == program.len() {
+        let (x, n) = stack.pop().ok_or_else(|| {
+            program.error(0, "no value of register A makes the program output itself")
+        })?;
+        if n == values.len() {
             break x;
         }
         for to_test in (0..8).rev() {
             let a = (x << 3) + to_test;
-            let (_, out) = run(Machine::new(a), &program);
-            if out.iter().eq(program.iter().skip(program.len() - n - 1)) {
+            let (_, out) = run(Machine::new(a), program)?;
+            if out.iter().eq(values.iter().skip(values.len() - n - 1)) {
                 stack.push((a, n + 1));
             }
         }
     };
-    return result.try_into().unwrap();
+    Ok(result)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const EXAMPLE: &str = "
-Register A: 729
-Register B: 0
-Register C: 0
-
-Program: 0,1,5,4,3,0
-";
-
-    const EXAMPLE_2: &str = "
-Register A: 2024
-Register B: 0
-Register C: 0
-
-Program: 0,3,5,4,3,0
-";
+    fn program(text: &str) -> Program<'_> {
+        parse_program(text, text).unwrap()
+    }
 
     #[test]
     fn example_part1() {
-        let (m, program) = parse_input(EXAMPLE.trim());
-        let (_, out) = run(m, &program);
+        let (m, program) = parse_input(EXAMPLE.trim()).unwrap();
+        let (_, out) = run(m, &program).unwrap();
         assert_eq!(out, vec![4, 6, 3, 5, 6, 3, 5, 2, 1, 0])
     }
 
     #[test]
     fn example_part1_parsed() {
-        let result = part1(EXAMPLE.trim());
-        assert_eq!(result, 4635635210)
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
+        assert_eq!(result, "4,6,3,5,6,3,5,2,1,0")
     }
 
     #[test]
@@ -190,8 +272,9 @@ Program: 0,3,5,4,3,0
                 c: 9,
                 pointer: 0,
             },
-            &vec![2, 6],
-        );
+            &program("2,6"),
+        )
+        .unwrap();
 
         assert_eq!(m.b, 1)
     }
@@ -205,14 +288,30 @@ Program: 0,3,5,4,3,0
                 c: 0,
                 pointer: 0,
             },
-            &vec![5, 0, 5, 1, 5, 4],
-        );
+            &program("5,0,5,1,5,4"),
+        )
+        .unwrap();
         assert_eq!(out, vec![0, 1, 2])
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE_2.trim());
+        let result = Solver::solve_part2(EXAMPLE_2.trim()).unwrap();
         assert_eq!(result, 117440)
     }
+
+    #[test]
+    fn truncated_input() {
+        let err = Solver::parse("Register A: 729\nRegister B: 0").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 14));
+        assert_eq!(err.message, "missing register C");
+    }
+
+    #[test]
+    fn reserved_combo_operand() {
+        let input = "Register A: 0\nRegister B: 0\nRegister C: 0\n\nProgram: 2,7";
+        let err = Solver::solve_part1(input).unwrap_err();
+        assert_eq!((err.line, err.column), (5, 12));
+        assert_eq!(err.message, "combo operand 7 is reserved");
+    }
 }
@@ -1,6 +1,13 @@
 use std::collections::HashSet;
 
-use super::utils;
+use super::{
+    params::Value,
+    parse,
+    registry::Example,
+    utils,
+    visualize::{self, Colour, Frame},
+    Answer, Param, Params, ParseError, Solution,
+};
 
 fn neighbours(
     p: (usize, usize),
@@ -21,19 +28,44 @@ fn neighbours(
     })
 }
 
-fn build_walls(input: &str) -> Vec<(usize, usize)> {
+/// The positions the bytes fall at, in order, along with the input so that part 1 can report
+/// when there aren't enough of them
+pub struct Bytes<'a> {
+    input: &'a str,
+    walls: Vec<(usize, usize)>,
+}
+
+fn build_walls(input: &str) -> Result<Vec<(usize, usize)>, ParseError> {
     input
         .lines()
         .map(|x| {
-            let (a, b) = x.split_once(',').unwrap();
-            (a.parse().unwrap(), b.parse().unwrap())
+            let (a, b) = parse::split_once(input, x, ",")?;
+            Ok((parse::number(input, a)?, parse::number(input, b)?))
         })
         .collect()
 }
 
-fn solve_p1(input: &str, size: (usize, usize), n: usize) -> i64 {
-    let all_walls = build_walls(input);
+/// The memory space with the fallen bytes, as `(x, y)`
+fn frame(size: (usize, usize), walls: &HashSet<(usize, usize)>, caption: String) -> Frame {
+    Frame::grid(caption, size.1, size.0, |y, x| {
+        match walls.contains(&(x, y)) {
+            true => ('#', Colour::Red),
+            false => ('.', Colour::Grey),
+        }
+    })
+}
+
+fn solve_p1(bytes: &Bytes, size: (usize, usize), n: usize) -> Result<i64, ParseError> {
+    let all_walls = &bytes.walls;
+    if all_walls.len() < n {
+        return Err(ParseError::at(
+            bytes.input,
+            parse::end(bytes.input),
+            format!("expected at least {} bytes, found {}", n, all_walls.len()),
+        ));
+    }
     let walls = all_walls[..n].iter().cloned().collect::<HashSet<_>>();
+    visualize::frame(|| frame(size, &walls, format!("after {} bytes", n)));
     let s = (0, 0);
     let e = (size.0 - 1, size.1 - 1);
 
@@ -49,13 +81,22 @@ fn solve_p1(input: &str, size: (usize, usize), n: usize) -> i64 {
         },
         &mut working_space,
     )
-    .unwrap();
+    .ok_or_else(|| {
+        // Point at the byte that closed the exit off, which is among the first `n`
+        let last = n.checked_sub(1).and_then(|i| bytes.input.lines().nth(i));
+        ParseError::at(
+            bytes.input,
+            last.unwrap_or(parse::end(bytes.input)),
+            format!("the exit can't be reached after {} bytes", n),
+        )
+    })?;
 
-    result.try_into().unwrap()
+    i64::try_from(result)
+        .map_err(|_| ParseError::at(bytes.input, bytes.input, "the path is too long"))
 }
 
-fn solve_p2(input: &str, size: (usize, usize)) -> (usize, usize) {
-    let all_walls = build_walls(input);
+fn solve_p2(bytes: &Bytes, size: (usize, usize)) -> Result<(usize, usize), ParseError> {
+    let all_walls = &bytes.walls;
     let s = (0, 0);
     let e = (size.0 - 1, size.1 - 1);
 
@@ -63,7 +104,7 @@ fn solve_p2(input: &str, size: (usize, usize)) -> (usize, usize) {
 
     let mut walls = HashSet::new();
 
-    let byte_that_breaks = (1..all_walls.len())
+    let byte_that_breaks = (1..=all_walls.len())
         .collect::<Vec<_>>()
         .partition_point(|&i| {
             if walls.len() > i {
@@ -73,7 +114,7 @@ fn solve_p2(input: &str, size: (usize, usize)) -> (usize, usize) {
                 walls.extend(all_walls[walls.len()..i].iter());
             }
 
-            utils::astar(
+            let reachable = utils::astar(
                 s,
                 |p| p == e,
                 |p| neighbours(p, size, &walls).map(|n| (n, 1)),
@@ -83,26 +124,64 @@ fn solve_p2(input: &str, size: (usize, usize)) -> (usize, usize) {
                 },
                 &mut working_space,
             )
-            .is_some()
+            .is_some();
+            visualize::frame(|| {
+                let exit = match reachable {
+                    true => "reachable",
+                    false => "blocked",
+                };
+                frame(
+                    size,
+                    &walls,
+                    format!("after {} bytes the exit is {}", i, exit),
+                )
+            });
+            reachable
         });
 
-    all_walls[byte_that_breaks]
+    all_walls.get(byte_that_breaks).copied().ok_or_else(|| {
+        ParseError::at(
+            bytes.input,
+            parse::end(bytes.input),
+            "the exit is still reachable after every byte has fallen",
+        )
+    })
 }
 
-pub fn part1(input: &str) -> i64 {
-    solve_p1(input, (71, 71), 1024)
-}
+pub struct Solver;
 
-pub fn part2(input: &str) -> i64 {
-    let result = solve_p2(input, (71, 71));
-    (result.0 * result.1).try_into().unwrap()
+impl Solution for Solver {
+    type Parsed<'a> = Bytes<'a>;
+
+    const PARAMS: &'static [Param] = &[
+        Param::size("size", 71, 71, "the size of the memory space").range(1, 1 << 16),
+        Param::int("bytes", 1024, "how many bytes have fallen in part 1"),
+    ];
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        Ok(Bytes {
+            input,
+            walls: build_walls(input)?,
+        })
+    }
+
+    fn part1(bytes: &Self::Parsed<'_>, params: &Params) -> Result<Answer, ParseError> {
+        let n = params.int("bytes") as usize;
+        solve_p1(bytes, memory_size(params), n).map(Answer::from)
+    }
+
+    fn part2(bytes: &Self::Parsed<'_>, params: &Params) -> Result<Answer, ParseError> {
+        let (x, y) = solve_p2(bytes, memory_size(params))?;
+        Ok(format!("{},{}", x, y).into())
+    }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+fn memory_size(params: &Params) -> (usize, usize) {
+    let (w, h) = params.size("size");
+    (w as usize, h as usize)
+}
 
-    const EXAMPLE: &str = "
+const EXAMPLE: &str = "
 5,4
 4,2
 4,5
@@ -130,15 +209,49 @@ mod tests {
 2,0
 ";
 
+super::register! {
+    year: 2024,
+    day: 18,
+    title: "RAM Run",
+    tags: ["grid", "pathfinding"],
+    implementations: { "default" => Solver },
+    examples: [
+        Example::new(EXAMPLE)
+            .part1("22")
+            .part2("6,1")
+            .params(&[("size", Value::Size(7, 7)), ("bytes", Value::Int(12))]),
+    ],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1() {
-        let result = solve_p1(EXAMPLE.trim(), (7, 7), 12);
+        let bytes = Solver::parse(EXAMPLE.trim()).unwrap();
+        let result = solve_p1(&bytes, (7, 7), 12).unwrap();
         assert_eq!(result, 22)
     }
 
     #[test]
     fn example_part2() {
-        let result = solve_p2(EXAMPLE.trim(), (7, 7));
+        let bytes = Solver::parse(EXAMPLE.trim()).unwrap();
+        let result = solve_p2(&bytes, (7, 7)).unwrap();
         assert_eq!(result, (6, 1))
     }
+
+    #[test]
+    fn blocked_exit() {
+        let bytes = Solver::parse("1,0\n0,1").unwrap();
+        let err = solve_p1(&bytes, (3, 3), 2).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+    }
+
+    #[test]
+    fn exit_never_blocked() {
+        let bytes = Solver::parse("1,1\n0,2").unwrap();
+        let err = solve_p2(&bytes, (3, 3)).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 4));
+    }
 }
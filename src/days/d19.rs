@@ -1,81 +1,92 @@
-use cached::{proc_macro::cached, stores::UnboundCache, Cached};
+use std::collections::HashMap;
 
-fn parse(input: &str) -> (Vec<&str>, impl Iterator<Item = &str>) {
-    let (towels_in, patterns_in) = input.split_once("\n\n").unwrap();
+use super::{parse, registry::Example, Answer, Params, ParseError, Solution};
+
+fn parse_input(input: &str) -> Result<(Vec<&str>, Vec<&str>), ParseError> {
+    let (towels_in, patterns_in) = parse::split_once(input, input, "\n\n")?;
 
     let towels: Vec<_> = towels_in.split(", ").collect();
 
-    let patterns = patterns_in.lines();
+    // an empty towel would match forever without consuming any of the pattern
+    if let Some(empty) = towels.iter().find(|t| t.is_empty()) {
+        return Err(ParseError::at(input, empty, "empty towel"));
+    }
+
+    let patterns = patterns_in.lines().collect();
 
-    (towels, patterns)
+    Ok((towels, patterns))
 }
 
-#[cached(
-    ty = "UnboundCache<String, bool>",
-    create = "{ UnboundCache::new() }",
-    convert = r#"{ pattern.to_string() }"#
-)]
-fn is_possible(towels: &Vec<&str>, pattern: &str) -> bool {
+fn is_possible<'a>(towels: &[&str], pattern: &'a str, cache: &mut HashMap<&'a str, bool>) -> bool {
     if pattern.is_empty() {
         return true;
     }
-    towels
+    if let Some(&possible) = cache.get(pattern) {
+        return possible;
+    }
+    let possible = towels
         .iter()
         .any(|towel| match pattern.strip_prefix(towel) {
             None => false,
-            Some(new) => is_possible(&towels, new),
-        })
+            Some(new) => is_possible(towels, new, cache),
+        });
+    cache.insert(pattern, possible);
+    possible
 }
 
-pub fn part1(input: &str) -> i64 {
-    let (towels, patterns) = parse(input);
-
-    // Clear the cache each time so we have fair timings
-    IS_POSSIBLE.lock().unwrap().cache_clear();
-
-    let result = patterns
-        .filter(|&pattern| is_possible(&towels, pattern))
-        .count();
-
-    result.try_into().unwrap()
-}
-
-#[cached(
-    ty = "UnboundCache<String, u64>",
-    create = "{ UnboundCache::new() }",
-    convert = r#"{ pattern.to_string() }"#
-)]
-fn count_ways(towels: &Vec<&str>, pattern: &str) -> u64 {
+fn count_ways<'a>(towels: &[&str], pattern: &'a str, cache: &mut HashMap<&'a str, u64>) -> u64 {
     if pattern.is_empty() {
         return 1;
     }
-    towels
+    if let Some(&ways) = cache.get(pattern) {
+        return ways;
+    }
+    let ways = towels
         .iter()
         .map(|towel| match pattern.strip_prefix(towel) {
             None => 0,
-            Some(new) => count_ways(&towels, new),
+            Some(new) => count_ways(towels, new, cache),
         })
-        .sum()
+        .sum();
+    cache.insert(pattern, ways);
+    ways
 }
 
-pub fn part2(input: &str) -> i64 {
-    let (towels, patterns) = parse(input);
+pub struct Solver;
 
-    // Clear the cache each time so we have fair timings
-    COUNT_WAYS.lock().unwrap().cache_clear();
+impl Solution for Solver {
+    type Parsed<'a> = (Vec<&'a str>, Vec<&'a str>);
 
-    let result = patterns
-        .map(|pattern| count_ways(&towels, pattern))
-        .sum::<u64>();
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        parse_input(input)
+    }
 
-    result.try_into().unwrap()
-}
+    fn part1((towels, patterns): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        // A fresh cache for each call so that timings are fair and concurrent runs don't share state
+        let mut cache = HashMap::new();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+        let result = patterns
+            .iter()
+            .filter(|&pattern| is_possible(towels, pattern, &mut cache))
+            .count();
+
+        Ok(result.into())
+    }
+
+    fn part2((towels, patterns): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        // A fresh cache for each call so that timings are fair and concurrent runs don't share state
+        let mut cache = HashMap::new();
+
+        let result = patterns
+            .iter()
+            .map(|pattern| count_ways(towels, pattern, &mut cache))
+            .sum::<u64>();
+
+        Ok(result.into())
+    }
+}
 
-    const EXAMPLE: &str = "
+const EXAMPLE: &str = "
 r, wr, b, g, bwu, rb, gb, br
 
 brwrr
@@ -88,15 +99,28 @@ brgr
 bbrgwb
 ";
 
+super::register! {
+    year: 2024,
+    day: 19,
+    title: "Linen Layout",
+    tags: ["memoization"],
+    implementations: { "default" => Solver },
+    examples: [Example::new(EXAMPLE).part1("6").part2("16")],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 6)
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 16)
     }
 }
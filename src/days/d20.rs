@@ -1,49 +1,24 @@
 use ndarray::Array2;
 
-type Pos = (usize, usize);
-type Grid = Array2<V>;
+use super::{
+    cancel, params::Value, parse, registry::Example, Answer, Param, Params, ParseError, Solution,
+};
+
+pub type Pos = (usize, usize);
+pub type Grid = Array2<V>;
 
 #[derive(Debug, PartialEq, Eq, Clone, Copy)]
-enum V {
+pub enum V {
     Open,
     Wall,
 }
 
-fn find_pos(input: &str, to_find: char) -> Pos {
-    input
-        .lines()
-        .enumerate()
-        .find_map(|(i, l)| {
-            l.chars()
-                .enumerate()
-                .find_map(|(j, c)| match c {
-                    c if c == to_find => Some(j),
-                    _ => None,
-                })
-                .and_then(|j| j.try_into().ok())
-                .and_then(|j| i.try_into().ok().map(|i| (i, j)))
-        })
-        .unwrap()
-}
-
-fn build_grid(input: &str) -> Grid {
-    let ncols = input.lines().next().unwrap().len();
-    let nrows = ((input.len() + 1) / ncols) - 1;
-
-    let grid = Grid::from_shape_vec(
-        (nrows, ncols),
-        input
-            .chars()
-            .filter_map(|c| match c {
-                '.' | 'S' | 'E' => Some(V::Open),
-                '#' => Some(V::Wall),
-                _ => None,
-            })
-            .collect(),
-    )
-    .unwrap();
-
-    grid
+fn build_grid(input: &str) -> Result<Grid, ParseError> {
+    parse::grid(input, input, |c| match c {
+        '.' | 'S' | 'E' => Some(V::Open),
+        '#' => Some(V::Wall),
+        _ => None,
+    })
 }
 
 fn neighbours(grid: &Grid, p: Pos) -> impl Iterator<Item = Pos> + use<'_> {
@@ -54,10 +29,12 @@ fn neighbours(grid: &Grid, p: Pos) -> impl Iterator<Item = Pos> + use<'_> {
         p.1.checked_add(1).map(|j| (p.0, j)),
     ]
     .into_iter()
-    .filter_map(move |p| p.filter(|p| grid.get(*p).map_or(false, |&y| y == V::Open)))
+    .filter_map(move |p| p.filter(|p| grid.get(*p).is_some_and(|&y| y == V::Open)))
 }
 
-fn first_pass(grid: &Grid, start: Pos, end: Pos) -> Vec<(usize, Pos)> {
+/// Follow the track from `start` to `end`. The track can't be longer than the grid has cells, so a
+/// longer walk is reported as a loop rather than followed forever.
+fn first_pass(grid: &Grid, start: Pos, end: Pos) -> Result<Vec<(usize, Pos)>, ParseError> {
     let mut vals = Vec::with_capacity(10_000);
 
     let mut i = 0;
@@ -67,67 +44,114 @@ fn first_pass(grid: &Grid, start: Pos, end: Pos) -> Vec<(usize, Pos)> {
     loop {
         vals.push((i, current));
         i += 1;
+        if i % 4096 == 0 {
+            cancel::check()?;
+        }
 
         if current == end {
             break;
         }
 
-        let prev_copy = prev.clone();
-        let mut ns =
-            neighbours(&grid, current).filter(|n| prev_copy.map_or(true, |prev| *n != prev));
+        let (row, column) = current;
+        let at = |message| ParseError::at_cell(row, column, message);
+        if vals.len() >= grid.len() {
+            return Err(at("the track loops without reaching E"));
+        }
+
+        let mut ns = neighbours(grid, current).filter(move |n| prev != Some(*n));
         prev = Some(current);
-        current = ns.next().unwrap();
-        assert!(ns.next().is_none());
+        current = ns
+            .next()
+            .ok_or_else(|| at("the track ends before reaching E"))?;
+        if ns.next().is_some() {
+            return Err(at("the track forks"));
+        }
     }
-    vals
+    Ok(vals)
 }
 
-pub fn solve(input: &str, cheat_duration: u32, threshold: u32) -> i64 {
-    let grid = build_grid(input);
-    let start = find_pos(input, 'S');
-    let end = find_pos(input, 'E');
+/// The positions along the racetrack, in order, with their distance from the start
+fn build_path(input: &str) -> Result<Vec<(usize, Pos)>, ParseError> {
+    let grid = build_grid(input)?;
+    let start = parse::find_in_grid(input, input, 'S')?;
+    let end = parse::find_in_grid(input, input, 'E')?;
 
-    let path = first_pass(&grid, start, end);
+    first_pass(&grid, start, end)
+}
 
-    let result = path
-        .iter()
-        .flat_map(|(i, start)| {
-            path.iter()
+pub fn solve(
+    path: &[(usize, Pos)],
+    cheat_duration: u32,
+    threshold: u32,
+) -> Result<usize, ParseError> {
+    path.iter()
+        .map(|(i, start)| {
+            cancel::check()?;
+
+            let count = path
+                .iter()
                 .skip(i + threshold as usize)
-                .map(move |(j, end)| ((i, start), (j, end)))
-        })
-        .filter(|((i, start), (j, end))| {
-            let xdiff = (start.0 as i32 - end.0 as i32).unsigned_abs();
+                .filter(|(j, end)| {
+                    let xdiff = start.0.abs_diff(end.0) as u64;
 
-            // this saves some time
-            if xdiff > cheat_duration {
-                return false;
-            }
+                    // this saves some time
+                    if xdiff > cheat_duration as u64 {
+                        return false;
+                    }
 
-            let ydiff = (start.1 as i32 - end.1 as i32).unsigned_abs();
-            let shortcut_dist = xdiff + ydiff;
+                    let ydiff = start.1.abs_diff(end.1) as u64;
+                    let shortcut_dist = xdiff + ydiff;
 
-            shortcut_dist <= cheat_duration
-                && ((**j) as i32 - (**i) as i32 - shortcut_dist as i32) >= threshold as i32
+                    // in i64 so that neither the distances nor the parameters can overflow
+                    shortcut_dist <= cheat_duration as u64
+                        && (*j as i64 - *i as i64 - shortcut_dist as i64) >= threshold as i64
+                })
+                .count();
+            Ok(count)
         })
-        .count();
-
-    result.try_into().unwrap()
+        .sum()
 }
 
-pub fn part1(input: &str) -> i64 {
-    solve(input, 2, 100)
-}
+pub struct Solver;
 
-pub fn part2(input: &str) -> i64 {
-    solve(input, 20, 100)
-}
+impl Solution for Solver {
+    type Parsed<'a> = Vec<(usize, Pos)>;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    const PARAMS: &'static [Param] = &[
+        Param::int(
+            "threshold",
+            100,
+            "the fewest picoseconds a cheat must save to be counted",
+        )
+        .range(0, u32::MAX as u64),
+        Param::int("part1_cheat", 2, "how long a cheat lasts in part 1").range(0, u32::MAX as u64),
+        Param::int("part2_cheat", 20, "how long a cheat lasts in part 2").range(0, u32::MAX as u64),
+    ];
 
-    const EXAMPLE: &str = "
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        build_path(input)
+    }
+
+    fn part1(path: &Self::Parsed<'_>, params: &Params) -> Result<Answer, ParseError> {
+        Ok(solve(
+            path,
+            params.int("part1_cheat") as u32,
+            params.int("threshold") as u32,
+        )?
+        .into())
+    }
+
+    fn part2(path: &Self::Parsed<'_>, params: &Params) -> Result<Answer, ParseError> {
+        Ok(solve(
+            path,
+            params.int("part2_cheat") as u32,
+            params.int("threshold") as u32,
+        )?
+        .into())
+    }
+}
+
+const EXAMPLE: &str = "
 ###############
 #...#...#.....#
 #.#.#.#.#.###.#
@@ -145,39 +169,78 @@ mod tests {
 ###############
 ";
 
+super::register! {
+    year: 2024,
+    day: 20,
+    title: "Race Condition",
+    tags: ["grid", "pathfinding"],
+    implementations: { "default" => Solver },
+    examples: [
+        Example::new(EXAMPLE)
+            .part1("1")
+            .part2("86")
+            .params(&[("threshold", Value::Int(64))]),
+    ],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1_64() {
-        let result = solve(EXAMPLE.trim(), 2, 64);
+        let result = solve(&build_path(EXAMPLE.trim()).unwrap(), 2, 64).unwrap();
         assert_eq!(result, 1)
     }
 
     #[test]
     fn example_part1_40() {
-        let result = solve(EXAMPLE.trim(), 2, 40);
+        let result = solve(&build_path(EXAMPLE.trim()).unwrap(), 2, 40).unwrap();
         assert_eq!(result, 2)
     }
 
     #[test]
     fn example_part1_38() {
-        let result = solve(EXAMPLE.trim(), 2, 38);
+        let result = solve(&build_path(EXAMPLE.trim()).unwrap(), 2, 38).unwrap();
         assert_eq!(result, 3)
     }
 
     #[test]
     fn example_part1_2() {
-        let result = solve(EXAMPLE.trim(), 2, 2);
+        let result = solve(&build_path(EXAMPLE.trim()).unwrap(), 2, 2).unwrap();
         assert_eq!(result, 44)
     }
 
     #[test]
     fn example_part2_76() {
-        let result = solve(EXAMPLE.trim(), 20, 76);
+        let result = solve(&build_path(EXAMPLE.trim()).unwrap(), 20, 76).unwrap();
         assert_eq!(result, 3)
     }
 
     #[test]
     fn example_part2_64() {
-        let result = solve(EXAMPLE.trim(), 20, 64);
+        let result = solve(&build_path(EXAMPLE.trim()).unwrap(), 20, 64).unwrap();
         assert_eq!(result, 86)
     }
+
+    #[test]
+    fn largest_parameters() {
+        let path = build_path(EXAMPLE.trim()).unwrap();
+        assert_eq!(solve(&path, u32::MAX, u32::MAX), Ok(0));
+        assert_eq!(
+            solve(&path, u32::MAX, 0),
+            Ok(path.len() * (path.len() + 1) / 2)
+        );
+    }
+
+    #[test]
+    fn broken_track() {
+        let err = build_path("#####\n#S.##\n###E#").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+        assert_eq!(err.message, "the track ends before reaching E");
+
+        let err = build_path("#####\n#S..#\n##.E#").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+        assert_eq!(err.message, "the track forks");
+    }
 }
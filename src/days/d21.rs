@@ -3,10 +3,11 @@ use petgraph::algo;
 use petgraph::graphmap::DiGraphMap;
 use std::collections::HashMap;
 use std::iter;
-use std::sync::LazyLock;
+
+use super::{cancel, parse, registry::Example, Answer, Param, Params, ParseError, Solution};
 
 #[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
-enum NPad {
+pub enum NPad {
     A,
     N0,
     N1,
@@ -20,21 +21,23 @@ enum NPad {
     N9,
 }
 
-impl From<u8> for NPad {
-    fn from(value: u8) -> Self {
+impl TryFrom<u8> for NPad {
+    type Error = u8;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
         match value {
-            b'0' => N0,
-            b'1' => N1,
-            b'2' => N2,
-            b'3' => N3,
-            b'4' => N4,
-            b'5' => N5,
-            b'6' => N6,
-            b'7' => N7,
-            b'8' => N8,
-            b'9' => N9,
-            b'A' => NPad::A,
-            _ => panic!("invalid input {}", value),
+            b'0' => Ok(N0),
+            b'1' => Ok(N1),
+            b'2' => Ok(N2),
+            b'3' => Ok(N3),
+            b'4' => Ok(N4),
+            b'5' => Ok(N5),
+            b'6' => Ok(N6),
+            b'7' => Ok(N7),
+            b'8' => Ok(N8),
+            b'9' => Ok(N9),
+            b'A' => Ok(NPad::A),
+            _ => Err(value),
         }
     }
 }
@@ -91,33 +94,32 @@ const ALL_DPADS: [DPad; 5] = [DPad::A, Up, Down, Left, Right];
 // Manually calculated shortest paths for each pair of DPad buttons. There are alternate paths, but
 // the ones listed here give the shortest responses (as determined by trial and error). A rigorous
 // solution would return all paths and then calculate the cost of each one, choosing the minimum
-static DPAD_PATHS: LazyLock<HashMap<(DPad, DPad), Vec<DPad>>> = LazyLock::new(|| {
-    HashMap::from_iter(
-        [
-            ((DPad::A, Up), vec![Left]),
-            ((DPad::A, Down), vec![Left, Down]), // or down, left
-            ((DPad::A, Left), vec![Down, Left, Left]),
-            ((DPad::A, Right), vec![Down]),
-            ((Up, DPad::A), vec![Right]),
-            ((Up, Down), vec![Down]),
-            ((Up, Left), vec![Down, Left]),
-            ((Up, Right), vec![Down, Right]),
-            ((Down, DPad::A), vec![Up, Right]), // or right, up
-            ((Down, Up), vec![Up]),
-            ((Down, Left), vec![Left]),
-            ((Down, Right), vec![Right]),
-            ((Left, DPad::A), vec![Right, Right, Up]),
-            ((Left, Up), vec![Right, Up]),
-            ((Left, Down), vec![Right]),
-            ((Left, Right), vec![Right, Right]),
-            ((Right, DPad::A), vec![Up]),
-            ((Right, Up), vec![Left, Up]), // or up, left
-            ((Right, Down), vec![Left]),
-            ((Right, Left), vec![Left, Left]),
-        ]
-        .into_iter(),
-    )
-});
+fn dpad_path(edge: (DPad, DPad)) -> &'static [DPad] {
+    match edge {
+        (DPad::A, Up) => &[Left],
+        (DPad::A, Down) => &[Left, Down], // or down, left
+        (DPad::A, Left) => &[Down, Left, Left],
+        (DPad::A, Right) => &[Down],
+        (Up, DPad::A) => &[Right],
+        (Up, Down) => &[Down],
+        (Up, Left) => &[Down, Left],
+        (Up, Right) => &[Down, Right],
+        (Down, DPad::A) => &[Up, Right], // or right, up
+        (Down, Up) => &[Up],
+        (Down, Left) => &[Left],
+        (Down, Right) => &[Right],
+        (Left, DPad::A) => &[Right, Right, Up],
+        (Left, Up) => &[Right, Up],
+        (Left, Down) => &[Right],
+        (Left, Right) => &[Right, Right],
+        (Right, DPad::A) => &[Up],
+        (Right, Up) => &[Left, Up], // or up, left
+        (Right, Down) => &[Left],
+        (Right, Left) => &[Left, Left],
+        // Pressing the same button again needs no moves
+        (DPad::A, DPad::A) | (Up, Up) | (Down, Down) | (Left, Left) | (Right, Right) => &[],
+    }
+}
 
 fn build_npad_graph(depth: u32) -> DiGraphMap<(NPad, DPad), u64> {
     let mut cache = HashMap::new();
@@ -156,7 +158,7 @@ fn path_cost(edge: (DPad, DPad), depth: u32, cache: &mut HashMap<((DPad, DPad),
         return 1;
     }
 
-    let path = DPAD_PATHS.get(&edge).unwrap();
+    let path = dpad_path(edge);
 
     let result = iter::once(DPad::A)
         .chain(path.iter().cloned())
@@ -169,46 +171,99 @@ fn path_cost(edge: (DPad, DPad), depth: u32, cache: &mut HashMap<((DPad, DPad),
     result
 }
 
-fn solve(input: &str, depth: u32) -> i64 {
+fn parse_code(input: &str, s: &str) -> Result<(u64, Vec<NPad>), ParseError> {
+    let digits = s
+        .strip_suffix('A')
+        .ok_or_else(|| ParseError::at(input, parse::end(s), "expected code to end with 'A'"))?;
+    let n = parse::number(input, digits)?;
+
+    let keys = s
+        .bytes()
+        .enumerate()
+        .map(|(i, c)| {
+            NPad::try_from(c)
+                .map_err(|c| ParseError::at(input, &s[i..], format!("invalid key {:?}", c as char)))
+        })
+        .collect::<Result<_, _>>()?;
+
+    Ok((n, keys))
+}
+
+fn parse_codes(input: &str) -> Result<Vec<(u64, Vec<NPad>)>, ParseError> {
+    input.lines().map(|s| parse_code(input, s)).collect()
+}
+
+/// The sum of the codes' complexities, failing at the first code that takes it past a u64
+fn solve(codes: &[(u64, Vec<NPad>)], depth: u32) -> Result<u64, ParseError> {
     let g = build_npad_graph(depth);
 
-    let result = input
-        .lines()
-        .map(|s| {
-            let n = s[..(s.len() - 1)].parse::<u64>().unwrap();
+    codes
+        .iter()
+        .enumerate()
+        .try_fold(0_u64, |total, (i, (n, keys))| {
+            cancel::check()?;
 
             let cost = iter::once(NPad::A)
-                .chain(s.bytes().map(|c| c.into()))
+                .chain(keys.iter().copied())
                 .tuple_windows()
                 .map(|(a, b)| {
                     let start = (a, DPad::A);
                     let end = (b, DPad::A);
                     let result = algo::dijkstra(&g, start, Some(end), |(_, _, &c)| c);
 
-                    result.get(&end).unwrap().clone()
+                    result[&end]
+                })
+                .try_fold(0_u64, |cost, presses| cost.checked_add(presses));
+
+            cost.and_then(|cost| n.checked_mul(cost))
+                .and_then(|complexity| total.checked_add(complexity))
+                // Each code is on a line of its own
+                .ok_or_else(|| ParseError {
+                    line: i + 1,
+                    column: 1,
+                    message: "the complexities add up to more than a u64 holds".to_string(),
                 })
-                .sum::<u64>();
-
-            n * cost
         })
-        .sum::<u64>();
-
-    result.try_into().unwrap()
 }
 
-pub fn part1(input: &str) -> i64 {
-    solve(input, 2)
-}
+/// The most robots allowed. Each one multiplies the presses by about two and a half, so more than
+/// this overflows a u64.
+const MAX_DEPTH: u64 = 35;
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Parsed<'a> = Vec<(u64, Vec<NPad>)>;
+
+    const PARAMS: &'static [Param] = &[
+        Param::int(
+            "part1_depth",
+            2,
+            "how many robots use directional keypads in part 1",
+        )
+        .range(0, MAX_DEPTH),
+        Param::int(
+            "part2_depth",
+            25,
+            "how many robots use directional keypads in part 2",
+        )
+        .range(0, MAX_DEPTH),
+    ];
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        parse_codes(input)
+    }
 
-pub fn part2(input: &str) -> i64 {
-    solve(input, 25)
-}
+    fn part1(codes: &Self::Parsed<'_>, params: &Params) -> Result<Answer, ParseError> {
+        Ok(solve(codes, params.int("part1_depth") as u32)?.into())
+    }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    fn part2(codes: &Self::Parsed<'_>, params: &Params) -> Result<Answer, ParseError> {
+        Ok(solve(codes, params.int("part2_depth") as u32)?.into())
+    }
+}
 
-    const EXAMPLE: &str = "
+const EXAMPLE: &str = "
 029A
 980A
 179A
@@ -216,57 +271,82 @@ mod tests {
 379A
 ";
 
+super::register! {
+    year: 2024,
+    day: 21,
+    title: "Keypad Conundrum",
+    tags: ["memoization"],
+    implementations: { "default" => Solver },
+    examples: [Example::new(EXAMPLE).part1("126384").part2("154115708116294")],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 126384)
     }
 
+    #[test]
+    fn missing_a() {
+        let err = Solver::parse("029A\n379").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 4));
+    }
+
+    #[test]
+    fn complexity_overflows() {
+        let err = solve(&parse_codes("029A\n99999999A").unwrap(), 35).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+    }
+
     #[test]
     fn short_2() {
-        let result = solve("029A", 2);
+        let result = solve(&parse_codes("029A").unwrap(), 2).unwrap();
         assert_eq!(result, 1972)
     }
 
     #[test]
     fn short_25() {
-        let result = solve("029A", 25);
+        let result = solve(&parse_codes("029A").unwrap(), 25).unwrap();
         assert_eq!(result, 2379451789590)
     }
 
     #[test]
     fn shorter_0() {
-        let result = solve("1A", 0);
+        let result = solve(&parse_codes("1A").unwrap(), 0).unwrap();
         assert_eq!(result, 8)
     }
 
     #[test]
     fn shorter_1() {
-        let result = solve("1A", 1);
+        let result = solve(&parse_codes("1A").unwrap(), 1).unwrap();
         assert_eq!(result, 18)
     }
 
     #[test]
     fn shorter_2() {
-        let result = solve("1A", 2);
+        let result = solve(&parse_codes("1A").unwrap(), 2).unwrap();
         assert_eq!(result, 48)
     }
 
     #[test]
     fn shorter_3() {
-        let result = solve("1A", 3);
+        let result = solve(&parse_codes("1A").unwrap(), 3).unwrap();
         assert_eq!(result, 118)
     }
 
     #[test]
     fn shorter_25() {
-        let result = solve("1A", 25);
+        let result = solve(&parse_codes("1A").unwrap(), 25).unwrap();
         assert_eq!(result, 58472946734)
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 154115708116294)
     }
 }
@@ -1,6 +1,8 @@
 use std::collections::{HashMap, HashSet};
 use std::iter;
 
+use super::{cancel, parse, registry::Example, Answer, Params, ParseError, Solution};
+
 const K: u64 = 16777216;
 
 fn next_secret(mut n: u64) -> u64 {
@@ -10,14 +12,29 @@ fn next_secret(mut n: u64) -> u64 {
     n
 }
 
-pub fn part1(input: &str) -> i64 {
+/// Secrets are kept below `K`, which also keeps [`next_secret`] from overflowing
+fn parse_secrets(input: &str) -> Result<Vec<u64>, ParseError> {
     input
         .lines()
-        .map(|l| l.parse::<u64>().unwrap())
-        .map(|n| (0..2000).fold(n, |acc, _| next_secret(acc)))
-        .sum::<u64>()
-        .try_into()
-        .unwrap()
+        .map(|l| match parse::number(input, l)? {
+            n if n < K => Ok(n),
+            n => Err(ParseError::at(
+                input,
+                l,
+                format!("secret {} is not below {}", n, K),
+            )),
+        })
+        .collect()
+}
+
+fn part1(secrets: &[u64]) -> Result<u64, ParseError> {
+    secrets
+        .iter()
+        .map(|&n| {
+            cancel::check()?;
+            Ok((0..2000).fold(n, |acc, _| next_secret(acc)))
+        })
+        .sum()
 }
 
 fn seq_to_key(a: i8, b: i8, c: i8, d: i8) -> u32 {
@@ -28,92 +45,120 @@ fn seq_to_key(a: i8, b: i8, c: i8, d: i8) -> u32 {
     au | bu | cu | du
 }
 
-pub fn part2(input: &str) -> i64 {
+fn part2(secrets: &[u64]) -> Result<u32, ParseError> {
     let mut patterns = HashMap::with_capacity(50_000);
     let mut nums = Vec::with_capacity(2000);
     let mut locally_found = HashSet::with_capacity(4000);
 
-    input
-        .lines()
-        .map(|l| l.parse::<u64>().unwrap())
-        .for_each(|n| {
-            nums.clear();
-            locally_found.clear();
-            nums.extend(
-                iter::successors(Some((n, 0)), |(n, _)| {
-                    let next = next_secret(*n);
-                    let diff = (next % 10) as i8 - (*n % 10) as i8;
-                    Some((next, diff))
-                })
-                .take(2000),
-            );
-
-            for w in nums.windows(4) {
-                let pattern = seq_to_key(w[0].1, w[1].1, w[2].1, w[3].1);
-
-                if locally_found.contains(&pattern) {
-                    continue;
-                }
-
-                let val = (w[3].0 % 10) as u16;
-
-                patterns
-                    .entry(pattern)
-                    .and_modify(|e| *e += val)
-                    .or_insert(val);
-
-                locally_found.insert(pattern);
+    for &n in secrets {
+        cancel::check()?;
+
+        nums.clear();
+        locally_found.clear();
+        nums.extend(
+            iter::successors(Some((n, 0)), |(n, _)| {
+                let next = next_secret(*n);
+                let diff = (next % 10) as i8 - (*n % 10) as i8;
+                Some((next, diff))
+            })
+            .take(2000),
+        );
+
+        for w in nums.windows(4) {
+            let pattern = seq_to_key(w[0].1, w[1].1, w[2].1, w[3].1);
+
+            if locally_found.contains(&pattern) {
+                continue;
             }
-        });
 
-    patterns.into_values().max().unwrap().try_into().unwrap()
+            let val = (w[3].0 % 10) as u32;
+
+            patterns
+                .entry(pattern)
+                .and_modify(|e| *e += val)
+                .or_insert(val);
+
+            locally_found.insert(pattern);
+        }
+    }
+
+    Ok(patterns.into_values().max().unwrap_or(0))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub struct Solver;
+
+impl Solution for Solver {
+    type Parsed<'a> = Vec<u64>;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        parse_secrets(input)
+    }
 
-    const EXAMPLE: &str = "
+    fn part1(secrets: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        Ok(part1(secrets)?.into())
+    }
+
+    fn part2(secrets: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        Ok(part2(secrets)?.into())
+    }
+}
+
+const EXAMPLE: &str = "
 1
 10
 100
 2024
 ";
 
-    const EXAMPLE2: &str = "
+const EXAMPLE2: &str = "
 1
 2
 3
 2024
 ";
 
+super::register! {
+    year: 2024,
+    day: 22,
+    title: "Monkey Market",
+    implementations: { "default" => Solver },
+    examples: [
+        Example::new(EXAMPLE).part1("37327623"),
+        Example::new(EXAMPLE2).part2("23"),
+    ],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 37327623)
     }
 
     #[test]
     fn example_part1_1() {
-        let result = part1("1");
+        let result = Solver::solve_part1("1").unwrap();
         assert_eq!(result, 8685429)
     }
 
     #[test]
     fn example_part1_2() {
-        let result = part1("10");
+        let result = Solver::solve_part1("10").unwrap();
         assert_eq!(result, 4700978)
     }
 
     #[test]
     fn example_part1_3() {
-        let result = part1("100");
+        let result = Solver::solve_part1("100").unwrap();
         assert_eq!(result, 15273692)
     }
 
     #[test]
     fn example_part1_4() {
-        let result = part1("2024");
+        let result = Solver::solve_part1("2024").unwrap();
         assert_eq!(result, 8667524)
     }
 
@@ -131,7 +176,13 @@ mod tests {
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE2.trim());
+        let result = Solver::solve_part2(EXAMPLE2.trim()).unwrap();
         assert_eq!(result, 23)
     }
+
+    #[test]
+    fn secret_too_large() {
+        let err = Solver::parse("1\n16777216").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+    }
 }
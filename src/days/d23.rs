@@ -1,34 +1,61 @@
-use core::str;
 use std::collections::HashSet;
 
 use itertools::Itertools;
 use petgraph::graphmap::UnGraphMap;
 
-fn str_to_key(s: &str) -> u16 {
-    let mut i = s.bytes();
-    let a = i.next().unwrap() as u16;
-    let b = i.next().unwrap() as u16;
-    (a << 8) | b
+use super::{parse, registry::Example, Answer, Params, ParseError, Solution};
+
+fn key_to_str(x: u16) -> String {
+    let a = (x >> 8) as u8;
+    let b = (x % (1 << 8)) as u8;
+    [a as char, b as char].into_iter().collect()
+}
+
+fn parse_key(input: &str, s: &str) -> Result<u16, ParseError> {
+    match s.as_bytes() {
+        &[a, b] if a.is_ascii_lowercase() && b.is_ascii_lowercase() => {
+            Ok(((a as u16) << 8) | b as u16)
+        }
+        _ => Err(ParseError::at(
+            input,
+            s,
+            format!("expected a two letter computer name, found {:?}", s),
+        )),
+    }
+}
+
+fn build_graph(input: &str) -> Result<UnGraphMap<u16, ()>, ParseError> {
+    let edges = input
+        .lines()
+        .map(|x| {
+            let (a, b) = parse::split_once(input, x, "-")?;
+            // A computer connected to itself would be in a clique with itself forever
+            if a == b {
+                return Err(ParseError::at(
+                    input,
+                    x,
+                    format!("{} is connected to itself", a),
+                ));
+            }
+            Ok((parse_key(input, a)?, parse_key(input, b)?))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(UnGraphMap::from_edges(edges))
 }
 
 fn key_starts_with_t(n: u16) -> bool {
     (n >> 8) as u8 == b't'
 }
 
-pub fn part1(input: &str) -> i64 {
-    let g = UnGraphMap::<_, ()>::from_edges(input.lines().map(|x| {
-        let (a, b) = x.split_once('-').unwrap();
-        (str_to_key(a), str_to_key(b))
-    }));
-    let result = g
-        .nodes()
+fn part1(g: &UnGraphMap<u16, ()>) -> usize {
+    g.nodes()
         .tuple_combinations()
         .filter(|&(a, b, c)| key_starts_with_t(a) || key_starts_with_t(b) || key_starts_with_t(c))
         .filter(|&(a, b, c)| {
             g.contains_edge(a, b) && g.contains_edge(a, c) && g.contains_edge(b, c)
         })
-        .count();
-    return result.try_into().unwrap();
+        .count()
 }
 
 fn bron_kerbosch(
@@ -36,9 +63,9 @@ fn bron_kerbosch(
     current: HashSet<u16>,
     mut potential: HashSet<u16>,
     mut excluded: HashSet<u16>,
-    mut max_cliques: &mut Vec<HashSet<u16>>,
+    max_cliques: &mut Vec<HashSet<u16>>,
 ) {
-    if potential.len() == 0 && excluded.len() == 0 {
+    if potential.is_empty() && excluded.is_empty() {
         max_cliques.push(current);
     } else {
         let vs = potential.clone();
@@ -47,11 +74,11 @@ fn bron_kerbosch(
             next.insert(v);
             let v_ns = HashSet::from_iter(g.neighbors(v));
             bron_kerbosch(
-                &g,
+                g,
                 next,
                 potential.intersection(&v_ns).cloned().collect(),
                 excluded.intersection(&v_ns).cloned().collect(),
-                &mut max_cliques,
+                max_cliques,
             );
 
             potential.remove(&v);
@@ -71,16 +98,12 @@ fn max_cliques(g: &UnGraphMap<u16, ()>) -> Vec<HashSet<u16>> {
     max_cliques
 }
 
-fn solve_p2(input: &str) -> Vec<u16> {
-    let g = UnGraphMap::<_, ()>::from_edges(input.lines().map(|x| {
-        let (a, b) = x.split_once('-').unwrap();
-        (str_to_key(a), str_to_key(b))
-    }));
-
-    let mut result = max_cliques(&g)
+fn solve_p2(g: &UnGraphMap<u16, ()>) -> Vec<u16> {
+    // With no computers the only clique is the empty one
+    let mut result = max_cliques(g)
         .into_iter()
         .max_by_key(|s| s.len())
-        .unwrap()
+        .unwrap_or_default()
         .into_iter()
         .collect::<Vec<_>>();
 
@@ -89,37 +112,25 @@ fn solve_p2(input: &str) -> Vec<u16> {
     result
 }
 
-pub fn part2(input: &str) -> i64 {
-    let result = solve_p2(input);
-
-    // Uncomment to print the required answer
-
-    // fn key_to_str(x: u16) -> String {
-    //     let a = (x >> 8) as u8;
-    //     let b = (x % (1 << 8)) as u8;
-    //     str::from_utf8(&[a, b]).unwrap().to_owned()
-    // }
-    // let x = result
-    //     .iter()
-    //     .map(|&x| key_to_str(x))
-    //     .collect::<Vec<_>>()
-    //     .join(",");
-    // println!("{}", x);
-
-    // Convert into a numeric result for my interfaces
-    return result
-        .into_iter()
-        .map(|x| x as i64)
-        .sum::<i64>()
-        .try_into()
-        .unwrap();
-}
+pub struct Solver;
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl Solution for Solver {
+    type Parsed<'a> = UnGraphMap<u16, ()>;
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        build_graph(input)
+    }
+
+    fn part1(g: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        Ok(part1(g).into())
+    }
 
-    const EXAMPLE: &str = "
+    fn part2(g: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        Ok(solve_p2(g).into_iter().map(key_to_str).join(",").into())
+    }
+}
+
+const EXAMPLE: &str = "
 kh-tc
 qp-kh
 de-cg
@@ -154,23 +165,44 @@ tb-vc
 td-yn
 ";
 
+super::register! {
+    year: 2024,
+    day: 23,
+    title: "LAN Party",
+    tags: ["graph"],
+    implementations: { "default" => Solver },
+    examples: [Example::new(EXAMPLE).part1("7").part2("co,de,ka,ta")],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(s: &str) -> u16 {
+        parse_key(s, s).unwrap()
+    }
+
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 7)
     }
 
     #[test]
     fn example_part2() {
-        let result = solve_p2(EXAMPLE.trim());
-        assert_eq!(
-            result,
-            vec![
-                str_to_key("co"),
-                str_to_key("de"),
-                str_to_key("ka"),
-                str_to_key("ta")
-            ]
-        )
+        let result = solve_p2(&build_graph(EXAMPLE.trim()).unwrap());
+        assert_eq!(result, vec![key("co"), key("de"), key("ka"), key("ta")])
+    }
+
+    #[test]
+    fn example_part2_joined() {
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
+        assert_eq!(result, "co,de,ka,ta")
+    }
+
+    #[test]
+    fn connected_to_itself() {
+        let err = Solver::parse("kh-tc\nab-ab").unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
     }
 }
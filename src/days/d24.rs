@@ -1,89 +1,157 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use itertools::Itertools;
 
-#[derive(Debug, PartialEq, Eq)]
-enum GateType {
+use super::{parse, registry::Example, Answer, Params, ParseError, Solution};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GateType {
     And,
     Or,
     Xor,
 }
 
 #[derive(Debug, PartialEq, Eq)]
-struct Gate<'a> {
+pub struct Gate<'a> {
     gate_type: GateType,
     in1: &'a str,
     in2: &'a str,
 }
 
+/// The value of `wire`, resolving the gates it depends on first. This works through a stack rather
+/// than recursing, since a long chain of gates would overflow the call stack, and fails if the
+/// gates have a cycle. Every wire must be an initial value or a gate's output, as `parse` checks.
 fn resolve<'a>(
-    mut cache: &mut HashMap<&'a str, bool>,
+    input: &str,
+    cache: &mut HashMap<&'a str, bool>,
     gates: &HashMap<&'a str, Gate<'a>>,
-    gate: &'a str,
-) -> bool {
-    if let Some(initial_value) = cache.get(gate) {
-        return *initial_value;
+    wire: &'a str,
+) -> Result<bool, ParseError> {
+    let mut stack = vec![wire];
+    // Gates whose inputs are being resolved, which are all below the top of the stack
+    let mut visiting = HashSet::new();
+
+    while let Some(&wire) = stack.last() {
+        if cache.contains_key(wire) {
+            stack.pop();
+            continue;
+        }
+        let gate = &gates[wire];
+        match (cache.get(gate.in1), cache.get(gate.in2)) {
+            (Some(&a), Some(&b)) => {
+                let result = match gate.gate_type {
+                    GateType::And => a && b,
+                    GateType::Or => a || b,
+                    GateType::Xor => a ^ b,
+                };
+                cache.insert(wire, result);
+                visiting.remove(wire);
+                stack.pop();
+            }
+            _ => {
+                for next in [gate.in1, gate.in2] {
+                    if cache.contains_key(next) {
+                        continue;
+                    }
+                    if visiting.contains(next) {
+                        return Err(ParseError::at(
+                            input,
+                            next,
+                            format!("wire {} depends on itself", next),
+                        ));
+                    }
+                    stack.push(next);
+                }
+                visiting.insert(wire);
+            }
+        }
     }
+    Ok(cache[wire])
+}
 
-    let outputting_gate = gates.get(gate).expect("output gate not found");
-
-    let a = resolve(&mut cache, &gates, outputting_gate.in1);
-    let b = resolve(&mut cache, &gates, outputting_gate.in2);
-
-    let result = match outputting_gate.gate_type {
-        GateType::And => a && b,
-        GateType::Or => a || b,
-        GateType::Xor => a ^ b,
+fn parse_gate<'a>(input: &str, g: &'a str) -> Result<(&'a str, Gate<'a>), ParseError> {
+    let mut iter = g.split(' ');
+    let in1 = parse::next(input, g, &mut iter, "first input")?;
+    let gate_type_raw = parse::next(input, g, &mut iter, "gate type")?;
+    let in2 = parse::next(input, g, &mut iter, "second input")?;
+    let arrow = parse::next(input, g, &mut iter, "\"->\"")?;
+    if arrow != "->" {
+        return Err(ParseError::at(input, arrow, "expected \"->\""));
+    }
+    let out = parse::next(input, g, &mut iter, "output")?;
+
+    let gate_type = match gate_type_raw {
+        "AND" => GateType::And,
+        "OR" => GateType::Or,
+        "XOR" => GateType::Xor,
+        _ => {
+            return Err(ParseError::at(
+                input,
+                gate_type_raw,
+                format!("invalid gate type {:?}", gate_type_raw),
+            ))
+        }
     };
 
-    cache.insert(gate, result);
-    result
+    Ok((
+        out,
+        Gate {
+            in1,
+            in2,
+            gate_type,
+        },
+    ))
 }
 
-fn parse(input: &str) -> (HashMap<&str, bool>, HashMap<&str, Gate>) {
-    let (init_input, gates_input) = input.split_once("\n\n").unwrap();
-    let init = init_input
+pub type Wires<'a> = HashMap<&'a str, bool>;
+pub type Gates<'a> = HashMap<&'a str, Gate<'a>>;
+
+fn parse_input(input: &str) -> Result<(&str, Wires<'_>, Gates<'_>), ParseError> {
+    let (init_input, gates_input) = parse::split_once(input, input, "\n\n")?;
+    let init: Wires = init_input
         .lines()
         .map(|i| {
-            let (gate, value) = i.split_once(": ").unwrap();
-            (gate, value == "1")
+            let (gate, value) = parse::split_once(input, i, ": ")?;
+            match value {
+                "0" | "1" => Ok((gate, value == "1")),
+                _ => Err(ParseError::at(
+                    input,
+                    value,
+                    format!("expected 0 or 1, found {:?}", value),
+                )),
+            }
         })
-        .collect::<HashMap<_, _>>();
+        .collect::<Result<_, _>>()?;
 
     let gates = gates_input
         .lines()
-        .map(|g| {
-            let mut iter = g.split(' ');
-            let in1 = iter.next().unwrap();
-            let gate_type_raw = iter.next().unwrap();
-            let in2 = iter.next().unwrap();
-            let out = iter.nth(1).unwrap();
-
-            let gate_type = match gate_type_raw {
-                "AND" => GateType::And,
-                "OR" => GateType::Or,
-                "XOR" => GateType::Xor,
-                _ => panic!("invalid gate type {}", gate_type_raw),
-            };
-
-            (
-                out,
-                Gate {
-                    in1,
-                    in2,
-                    gate_type,
-                },
-            )
-        })
-        .collect::<HashMap<_, _>>();
+        .map(|g| parse_gate(input, g))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let outputs: HashSet<_> = gates.iter().map(|(out, _)| *out).collect();
+    for (_, gate) in &gates {
+        for wire in [gate.in1, gate.in2] {
+            if !init.contains_key(wire) && !outputs.contains(wire) {
+                return Err(ParseError::at(
+                    input,
+                    wire,
+                    format!(
+                        "wire {} is neither an initial value nor a gate's output",
+                        wire
+                    ),
+                ));
+            }
+        }
+    }
 
-    (init, gates)
+    Ok((input, init, gates.into_iter().collect()))
 }
 
-pub fn part1(input: &str) -> i64 {
-    let (mut init, gates) = parse(input);
+fn part1(input: &str, init: &Wires, gates: &Gates) -> Result<u64, ParseError> {
+    // Resolved wires are cached alongside the initial values, so start from a copy
+    let mut wires = init.clone();
 
-    let result = gates
+    gates
         .keys()
         .filter_map(|&g| match g {
             o if o.starts_with('z') => Some(o),
@@ -91,13 +159,13 @@ pub fn part1(input: &str) -> i64 {
         })
         .sorted()
         .rev()
-        .map(|g| resolve(&mut init, &gates, g))
-        .fold(0, |acc, b| (acc << 1) + (b as u64));
-
-    result.try_into().unwrap()
+        .try_fold(0, |acc, g| {
+            let b = resolve(input, &mut wires, gates, g)?;
+            Ok((acc << 1) + (b as u64))
+        })
 }
 
-pub fn part2<'a>(input: &'a str) -> i64 {
+fn part2<'a>(gates: &'a Gates<'a>) -> String {
     // Full adder
     // 1. (X,  Y) XOR -> A
     // 2. (X,  Y) AND -> B
@@ -105,28 +173,10 @@ pub fn part2<'a>(input: &'a str) -> i64 {
     // 4. (A, CI) AND -> C
     // 5. (C,  B)  OR -> CO
 
-    let gates = input
-        .split_once("\n\n")
-        .unwrap()
-        .1
-        .lines()
-        .map(|g| {
-            let mut iter = g.split(' ');
-            let in1 = iter.next().unwrap();
-            let gate_type_raw = iter.next().unwrap();
-            let in2 = iter.next().unwrap();
-            let out = iter.nth(1).unwrap();
-
-            let gate_type = match gate_type_raw {
-                "AND" => GateType::And,
-                "OR" => GateType::Or,
-                "XOR" => GateType::Xor,
-                _ => panic!("invalid gate type {}", gate_type_raw),
-            };
-
-            (gate_type, in1, in2, out)
-        })
-        .collect::<Vec<_>>();
+    let gates: Vec<_> = gates
+        .iter()
+        .map(|(&out, gate)| (gate.gate_type, gate.in1, gate.in2, out))
+        .collect();
 
     let find_by_in = |input: &'a str| {
         gates
@@ -134,7 +184,7 @@ pub fn part2<'a>(input: &'a str) -> i64 {
             .filter(move |(_, in1, in2, _)| *in1 == input || *in2 == input)
     };
 
-    let broken = gates
+    gates
         .iter()
         .filter_map(|(ty, in1, in2, out)| {
             // If the gate is an XOR then either its inputs are x?? and y?? or the output is z??. If
@@ -148,7 +198,7 @@ pub fn part2<'a>(input: &'a str) -> i64 {
 
                 let mut out_gates = find_by_in(out).map(|(ty, _, _, _)| ty);
                 let gates = out_gates.next().zip(out_gates.next());
-                if gates.map_or(true, |(a, b)| {
+                if gates.is_none_or(|(a, b)| {
                     *a == *b
                         || !(*a == GateType::Xor || *a == GateType::And)
                         || !(*b == GateType::Xor || *b == GateType::And)
@@ -161,7 +211,7 @@ pub fn part2<'a>(input: &'a str) -> i64 {
             // The exception is the LSB
             if *ty == GateType::And && (*in1 != "x00" && *in2 != "x00") {
                 let mut out_gates = find_by_in(out).map(|(ty, _, _, _)| ty);
-                if !(out_gates.next().map_or(false, |ty| *ty == GateType::Or)
+                if !(out_gates.next().is_some_and(|ty| *ty == GateType::Or)
                     && out_gates.next().is_none())
                 {
                     return Some(out);
@@ -173,7 +223,7 @@ pub fn part2<'a>(input: &'a str) -> i64 {
             if *ty == GateType::Or && *out != "z45" {
                 let mut out_gates = find_by_in(out).map(|(ty, _, _, _)| ty);
                 let gates = out_gates.next().zip(out_gates.next());
-                if gates.map_or(true, |(a, b)| {
+                if gates.is_none_or(|(a, b)| {
                     *a == *b
                         || !(*a == GateType::Xor || *a == GateType::And)
                         || !(*b == GateType::Xor || *b == GateType::And)
@@ -185,20 +235,29 @@ pub fn part2<'a>(input: &'a str) -> i64 {
             None
         })
         .sorted()
-        .join(",");
+        .join(",")
+}
 
-    // Uncomment to print the required answer
-    // println!("{}", broken);
+pub struct Solver;
 
-    // Return a numeric answer for the templates
-    broken.len().try_into().unwrap()
-}
+impl Solution for Solver {
+    /// The input, for reporting cycles, the initial values and the gates by their output
+    type Parsed<'a> = (&'a str, Wires<'a>, Gates<'a>);
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        parse_input(input)
+    }
+
+    fn part1((input, init, gates): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        part1(input, init, gates).map(Answer::from)
+    }
 
-    const EXAMPLE: &str = "
+    fn part2((_, _, gates): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        Ok(part2(gates).into())
+    }
+}
+
+const EXAMPLE: &str = "
 x00: 1
 x01: 1
 x02: 1
@@ -211,7 +270,7 @@ x01 XOR y01 -> z01
 x02 OR y02 -> z02
 ";
 
-    const EXAMPLE2: &str = "
+const EXAMPLE2: &str = "
 x00: 1
 x01: 0
 x02: 1
@@ -261,15 +320,49 @@ tgd XOR rvg -> z12
 tnw OR pbm -> gnj
 ";
 
+super::register! {
+    year: 2024,
+    day: 24,
+    title: "Crossed Wires",
+    tags: ["graph"],
+    implementations: { "default" => Solver },
+    examples: [
+        Example::new(EXAMPLE).part1("4"),
+        Example::new(EXAMPLE2).part1("2024"),
+    ],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 4)
     }
 
     #[test]
     fn example_part1_2() {
-        let result = part1(EXAMPLE2.trim());
+        let result = Solver::solve_part1(EXAMPLE2.trim()).unwrap();
         assert_eq!(result, 2024)
     }
+
+    #[test]
+    fn undefined_wire() {
+        let err = Solver::parse("x00: 1\n\nx00 AND y00 -> z00").unwrap_err();
+        assert_eq!((err.line, err.column), (3, 9));
+    }
+
+    #[test]
+    fn cyclic_gates() {
+        let input = "x00: 1\n\nx00 AND b -> a\na OR x00 -> b\na XOR b -> z00";
+        let err = Solver::solve_part1(input).unwrap_err();
+        assert_eq!(err.line, 3);
+        assert!(
+            err.message.ends_with("depends on itself"),
+            "{}",
+            err.message
+        );
+    }
 }
@@ -1,44 +1,77 @@
 use itertools::Itertools;
 
-pub fn part1(input: &str) -> i64 {
-    let mut locks: Vec<[u8; 5]> = Vec::new();
-    let mut keys: Vec<[u8; 5]> = Vec::new();
+use super::{parse, registry::Example, Answer, Params, ParseError, Solution};
+
+pub type Heights = [u8; 5];
+
+fn parse_schematics(input: &str) -> Result<(Vec<Heights>, Vec<Heights>), ParseError> {
+    let mut locks: Vec<Heights> = Vec::new();
+    let mut keys: Vec<Heights> = Vec::new();
 
     for block in input.split("\n\n") {
-        let out = if block.chars().next().unwrap() == '#' {
-            &mut locks
-        } else {
-            &mut keys
-        };
+        let grid = parse::grid(input, block, |c| match c {
+            '#' => Some(true),
+            '.' => Some(false),
+            _ => None,
+        })?;
 
-        out.push([0; 5]);
-        let heights = out.last_mut().unwrap();
+        if grid.ncols() != 5 {
+            return Err(ParseError::at(
+                input,
+                block,
+                format!("expected 5 columns, found {}", grid.ncols()),
+            ));
+        }
 
-        for line in block.split('\n') {
-            for (i, _) in line.chars().enumerate().filter(|(_, c)| *c == '#') {
-                heights[i] += 1
-            }
+        if grid.nrows() != 7 {
+            return Err(ParseError::at(
+                input,
+                block,
+                format!("expected 7 rows, found {}", grid.nrows()),
+            ));
         }
-    }
 
-    let result = locks
-        .into_iter()
-        .cartesian_product(keys.into_iter())
-        .filter(|(lock, key)| lock.iter().zip(key.iter()).all(|(x, y)| (*x + *y) < 8))
-        .count();
+        let mut heights = [0; 5];
 
-    result.try_into().unwrap()
-}
+        for ((_, i), _) in grid.indexed_iter().filter(|(_, filled)| **filled) {
+            heights[i] += 1
+        }
+
+        if grid[(0, 0)] {
+            locks.push(heights);
+        } else {
+            keys.push(heights);
+        }
+    }
 
-pub fn part2(_input: &str) -> i64 {
-    0
+    Ok((locks, keys))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+pub struct Solver;
+
+impl Solution for Solver {
+    type Parsed<'a> = (Vec<Heights>, Vec<Heights>);
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        parse_schematics(input)
+    }
+
+    fn part1((locks, keys): &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        let result = locks
+            .iter()
+            .cartesian_product(keys)
+            .filter(|(lock, key)| lock.iter().zip(key.iter()).all(|(x, y)| (*x + *y) < 8))
+            .count();
+
+        Ok(result.into())
+    }
 
-    const EXAMPLE: &str = "
+    fn part2(_: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        Ok(Answer::NotImplemented)
+    }
+}
+
+const EXAMPLE: &str = "
 #####
 .####
 .####
@@ -80,15 +113,36 @@ mod tests {
 #####
 ";
 
+super::register! {
+    year: 2024,
+    day: 25,
+    title: "Code Chronicle",
+    tags: ["grid"],
+    implementations: { "default" => Solver },
+    examples: [Example::new(EXAMPLE).part1("3")],
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
         assert_eq!(result, 3)
     }
 
+    #[test]
+    fn short_schematic() {
+        let input =
+            "#####\n.####\n.####\n.#.#.\n.#...\n.....\n.....\n\n.....\n#....\n#...#\n#.#.#\n#####";
+        let err = Solver::parse(input).unwrap_err();
+        assert_eq!((err.line, err.column), (9, 1));
+    }
+
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
-        assert_eq!(result, 0)
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
+        assert_eq!(result, Answer::NotImplemented)
     }
 }
@@ -0,0 +1,115 @@
+use std::{error::Error, fmt};
+
+/// A problem with a puzzle input, located by 1-based line and column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl ParseError {
+    /// Build an error pointing at the start of `at`, which should be a slice of `input`. If it is
+    /// not, the error points at the end of the input instead.
+    pub fn at(input: &str, at: &str, message: impl Into<String>) -> Self {
+        let start = input.as_ptr() as usize;
+        let offset = (at.as_ptr() as usize)
+            .checked_sub(start)
+            .filter(|&o| o <= input.len() && input.is_char_boundary(o))
+            .unwrap_or(input.len());
+
+        let before = &input[..offset];
+        let line = before.matches('\n').count() + 1;
+        let column = before[before.rfind('\n').map_or(0, |i| i + 1)..]
+            .chars()
+            .count()
+            + 1;
+
+        ParseError {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    /// Build an error pointing at a cell of a grid, given by its 0-based row and column, for grids
+    /// that make up the whole input
+    pub fn at_cell(row: usize, column: usize, message: impl Into<String>) -> Self {
+        ParseError {
+            line: row + 1,
+            column: column + 1,
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// A failure to solve one part of a day, with the location in the input that caused it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SolveError {
+    pub day: String,
+    /// The part that failed, or 0 if the input couldn't be parsed
+    pub part: u8,
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+impl SolveError {
+    pub fn new(day: &str, part: u8, source: ParseError) -> Self {
+        SolveError {
+            day: day.to_string(),
+            part,
+            line: source.line,
+            column: source.column,
+            message: source.message,
+        }
+    }
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.part {
+            0 => write!(f, "{} parse", self.day)?,
+            part => write!(f, "{} part {}", self.day, part)?,
+        }
+        write!(
+            f,
+            ": line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl Error for SolveError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn location_of_slice() {
+        let input = "abc\ndef\nghi";
+        let err = ParseError::at(input, &input[9..], "oops");
+        assert_eq!((err.line, err.column), (3, 2));
+    }
+
+    #[test]
+    fn location_of_foreign_str_is_end() {
+        let input = "abc\ndef";
+        let other = String::from("elsewhere");
+        let err = ParseError::at(input, &other, "oops");
+        assert_eq!((err.line, err.column), (2, 4));
+    }
+}
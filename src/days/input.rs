@@ -0,0 +1,206 @@
+use std::{
+    env, fmt, fs,
+    io::{self, Read},
+    path::{Path, PathBuf},
+};
+
+use super::{
+    crypt::{self, CryptError},
+    registry,
+};
+
+mod embedded {
+    // The inputs in `data/YYYY/` and the answers file, decrypted if there was a key, when built
+    // with the `embed-inputs` feature, and nothing otherwise
+    include!(concat!(env!("OUT_DIR"), "/embedded_inputs.rs"));
+}
+
+/// Where to read puzzle inputs from
+#[derive(Debug, Clone)]
+pub enum InputSource {
+    /// A directory with a `YYYY/dNN.txt` file for each day, see [`InputSource::path`]
+    DataDir(PathBuf),
+    /// A single file, used for whichever day is being run
+    File(PathBuf),
+    Stdin,
+    /// The inputs built into the binary with the `embed-inputs` feature
+    Embedded,
+    /// An input that has already been read, e.g. from a request to `serve`, used for whichever day
+    /// is being run
+    Text(String),
+}
+
+#[derive(Debug)]
+pub enum InputError {
+    /// There is no input for this day. The runner skips the day rather than failing
+    Missing(PathBuf),
+    Io(String, io::Error),
+    /// The input is transcrypt ciphertext that couldn't be decrypted
+    Decrypt(String, CryptError),
+}
+
+impl fmt::Display for InputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            InputError::Missing(path) => write!(f, "no input at {}", path.display()),
+            InputError::Io(from, e) => write!(f, "failed to read {}: {}", from, e),
+            InputError::Decrypt(from, e) => write!(f, "{}: {}", from, e),
+        }
+    }
+}
+
+impl std::error::Error for InputError {}
+
+impl InputSource {
+    /// The file the input for day `number` of `year` is read from, if it comes from a file. Days of
+    /// [`registry::DEFAULT_YEAR`] are also read from `dNN.txt` directly in a data directory, as
+    /// they were before there were other years, if that's where their input is.
+    pub fn path(&self, year: u16, number: u8) -> Option<PathBuf> {
+        match self {
+            InputSource::DataDir(dir) => {
+                let path = dir.join(relative_path(year, number));
+                let flat = dir.join(format!("d{:02}.txt", number));
+                match year == registry::DEFAULT_YEAR && !path.exists() && flat.exists() {
+                    true => Some(flat),
+                    false => Some(path),
+                }
+            }
+            InputSource::File(path) => Some(path.clone()),
+            InputSource::Stdin | InputSource::Embedded | InputSource::Text(_) => None,
+        }
+    }
+
+    /// Read the input for day `number` of `year`, decrypting it first if it is still transcrypt
+    /// ciphertext
+    pub fn load(&self, year: u16, number: u8) -> Result<String, InputError> {
+        match self {
+            InputSource::Embedded => return read_embedded(&relative_path(year, number)),
+            InputSource::Text(input) => return Ok(input.clone()),
+            _ => {}
+        }
+        match self.path(year, number) {
+            Some(path) => read_decrypted(&path),
+            None => {
+                let mut input = String::new();
+                io::stdin()
+                    .read_to_string(&mut input)
+                    .map_err(|e| InputError::Io("stdin".to_string(), e))?;
+                decrypt_if_needed("stdin", input)
+            }
+        }
+    }
+}
+
+/// Where the input for day `number` of `year` is in a data directory, e.g. `2024/d05.txt`
+pub fn relative_path(year: u16, number: u8) -> PathBuf {
+    Path::new(&year.to_string()).join(format!("d{:02}.txt", number))
+}
+
+/// Read a file built in from the data directory with the `embed-inputs` feature, e.g.
+/// `2024/d05.txt`, decrypting it if it wasn't decrypted while building
+pub fn read_embedded(relative: &Path) -> Result<String, InputError> {
+    match embedded::FILES
+        .iter()
+        .find(|(embedded, _)| Path::new(embedded) == relative)
+    {
+        Some((_, content)) => decrypt_if_needed(
+            &format!("embedded {}", relative.display()),
+            content.to_string(),
+        ),
+        // Where the file would have been embedded from
+        None => Err(InputError::Missing(
+            Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("data")
+                .join(relative),
+        )),
+    }
+}
+
+/// Read a file from the data directory, decrypting it if it is still transcrypt ciphertext
+pub fn read_decrypted(path: &Path) -> Result<String, InputError> {
+    decrypt_if_needed(&path.display().to_string(), read_file(path)?)
+}
+
+fn decrypt_if_needed(from: &str, content: String) -> Result<String, InputError> {
+    if !crypt::is_encrypted(&content) {
+        return Ok(content);
+    }
+    crypt::key_from_env()
+        .and_then(|key| crypt::decrypt(&content, &key))
+        .map_err(|e| InputError::Decrypt(from.to_string(), e))
+}
+
+fn read_file(path: &Path) -> Result<String, InputError> {
+    fs::read_to_string(path).map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => InputError::Missing(path.to_path_buf()),
+        _ => InputError::Io(path.display().to_string(), e),
+    })
+}
+
+/// The data directory to use when none is given explicitly. `AOC_DATA_DIR` takes priority, then
+/// `data` in the current directory, falling back to the `data` directory of this checkout so the
+/// binary still works when run from elsewhere.
+pub fn default_data_dir() -> PathBuf {
+    if let Some(dir) = env::var_os("AOC_DATA_DIR") {
+        return PathBuf::from(dir);
+    }
+
+    let local = PathBuf::from("data");
+    if local.is_dir() {
+        return local;
+    }
+
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("data")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_day_is_reported_as_missing() {
+        let dir = env::temp_dir().join("aoc-input-test-empty");
+        fs::create_dir_all(&dir).unwrap();
+        let err = InputSource::DataDir(dir).load(2024, 99).unwrap_err();
+        assert!(matches!(err, InputError::Missing(_)));
+    }
+
+    #[test]
+    fn embeds_inputs_with_the_feature() {
+        let loaded = InputSource::Embedded.load(2024, 1);
+        match cfg!(feature = "embed-inputs") {
+            true => assert!(!matches!(loaded, Err(InputError::Missing(_)))),
+            false => assert!(matches!(loaded, Err(InputError::Missing(_)))),
+        }
+    }
+
+    #[test]
+    fn reads_from_data_dir() {
+        let dir = env::temp_dir().join("aoc-input-test-data");
+        fs::create_dir_all(dir.join("2023")).unwrap();
+        fs::write(dir.join("2023/d01.txt"), "3   4\n").unwrap();
+        let source = InputSource::DataDir(dir);
+        assert_eq!(source.load(2023, 1).unwrap(), "3   4\n");
+        assert!(matches!(
+            source.load(2022, 1).unwrap_err(),
+            InputError::Missing(path) if path.ends_with("2022/d01.txt")
+        ));
+    }
+
+    #[test]
+    fn reads_the_default_year_from_the_flat_layout() {
+        let dir = env::temp_dir().join("aoc-input-test-flat");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("d01.txt"), "flat\n").unwrap();
+        let source = InputSource::DataDir(dir.clone());
+        assert_eq!(source.load(registry::DEFAULT_YEAR, 1).unwrap(), "flat\n");
+        assert!(source.load(2023, 1).is_err());
+
+        // But the input in the year's directory comes first
+        let year = dir.join(registry::DEFAULT_YEAR.to_string());
+        fs::create_dir_all(&year).unwrap();
+        fs::write(year.join("d01.txt"), "nested\n").unwrap();
+        assert_eq!(source.load(registry::DEFAULT_YEAR, 1).unwrap(), "nested\n");
+    }
+}
@@ -0,0 +1,206 @@
+//! The known-correct answers for the real inputs, stored in `answers.toml` in the data directory
+//! as one table per day:
+//!
+//! ```toml
+//! [d01]
+//! part1 = 1151792
+//! part2 = 21790168
+//! ```
+
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+
+use toml::{Table, Value};
+
+use super::{
+    input::{self, InputError},
+    Answer,
+};
+
+pub const FILENAME: &str = "answers.toml";
+
+#[derive(Debug)]
+pub enum AnswersError {
+    Read(InputError),
+    Parse(String),
+    Write(String),
+}
+
+impl fmt::Display for AnswersError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AnswersError::Read(e) => write!(f, "{}", e),
+            AnswersError::Parse(e) => write!(f, "invalid answers file: {}", e),
+            AnswersError::Write(e) => write!(f, "failed to write answers file: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AnswersError {}
+
+/// How an answer compares to the recorded one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Pass,
+    Fail {
+        expected: Answer,
+    },
+    /// Nothing has been recorded for this part yet
+    Unknown,
+}
+
+impl fmt::Display for Verdict {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Verdict::Pass => write!(f, "PASS"),
+            Verdict::Fail { expected } => write!(f, "FAIL (expected {})", expected),
+            Verdict::Unknown => write!(f, "UNKNOWN"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KnownAnswers {
+    answers: BTreeMap<(String, u8), Answer>,
+}
+
+impl KnownAnswers {
+    /// Load the answers file at `path`. A file that doesn't exist yet has no answers in it.
+    pub fn load(path: &Path) -> Result<Self, AnswersError> {
+        match input::read_decrypted(path) {
+            Ok(content) => Self::parse(&content),
+            Err(InputError::Missing(_)) => Ok(Self::default()),
+            Err(e) => Err(AnswersError::Read(e)),
+        }
+    }
+
+    /// Load the answers file built in with the `embed-inputs` feature, which has no answers if
+    /// there was none to build in
+    pub fn load_embedded() -> Result<Self, AnswersError> {
+        match input::read_embedded(Path::new(FILENAME)) {
+            Ok(content) => Self::parse(&content),
+            Err(InputError::Missing(_)) => Ok(Self::default()),
+            Err(e) => Err(AnswersError::Read(e)),
+        }
+    }
+
+    pub fn parse(content: &str) -> Result<Self, AnswersError> {
+        let table: Table = content
+            .parse()
+            .map_err(|e: toml::de::Error| AnswersError::Parse(e.to_string()))?;
+
+        let mut answers = BTreeMap::new();
+        for (day, parts) in table {
+            let parts = parts
+                .as_table()
+                .ok_or_else(|| AnswersError::Parse(format!("{} is not a table", day)))?;
+            for (key, value) in parts {
+                let part = match key.as_str() {
+                    "part1" => 1,
+                    "part2" => 2,
+                    _ => return Err(AnswersError::Parse(format!("unknown key {}.{}", day, key))),
+                };
+                let answer = match value {
+                    Value::Integer(x) => Answer::from(*x),
+                    Value::String(s) => s
+                        .parse::<i128>()
+                        .map_or_else(|_| s.as_str().into(), Answer::from),
+                    _ => {
+                        return Err(AnswersError::Parse(format!(
+                            "{}.{} must be an integer or string",
+                            day, key
+                        )))
+                    }
+                };
+                answers.insert((day.clone(), part), answer);
+            }
+        }
+
+        Ok(KnownAnswers { answers })
+    }
+
+    pub fn get(&self, day: &str, part: u8) -> Option<&Answer> {
+        self.answers.get(&(day.to_string(), part))
+    }
+
+    /// Record `answer` as correct. Unimplemented parts are not recorded.
+    pub fn set(&mut self, day: &str, part: u8, answer: Answer) {
+        if answer != Answer::NotImplemented {
+            self.answers.insert((day.to_string(), part), answer);
+        }
+    }
+
+    pub fn check(&self, day: &str, part: u8, actual: &Answer) -> Verdict {
+        match self.get(day, part) {
+            None => Verdict::Unknown,
+            Some(expected) if expected == actual => Verdict::Pass,
+            Some(expected) => Verdict::Fail {
+                expected: expected.clone(),
+            },
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.answers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.answers.is_empty()
+    }
+
+    pub fn to_toml(&self) -> String {
+        let mut table = Table::new();
+        for ((day, part), answer) in &self.answers {
+            let value = match answer {
+                Answer::Int(x) => Value::Integer(*x),
+                other => Value::String(other.to_string()),
+            };
+            table
+                .entry(day.clone())
+                .or_insert_with(|| Value::Table(Table::new()))
+                .as_table_mut()
+                .unwrap()
+                .insert(format!("part{}", part), value);
+        }
+        toml::to_string(&table).unwrap()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), AnswersError> {
+        fs::write(path, self.to_toml()).map_err(|e| AnswersError::Write(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut answers = KnownAnswers::default();
+        answers.set("d01", 1, Answer::from(11));
+        answers.set("d17", 2, Answer::from("4,6,3,5"));
+        answers.set("d22", 1, Answer::from(i128::MAX));
+        answers.set("d25", 2, Answer::NotImplemented);
+
+        let parsed = KnownAnswers::parse(&answers.to_toml()).unwrap();
+        assert_eq!(parsed, answers);
+        assert_eq!(parsed.len(), 3);
+    }
+
+    #[test]
+    fn verdicts() {
+        let answers = KnownAnswers::parse("[d01]\npart1 = 11\n").unwrap();
+        assert_eq!(answers.check("d01", 1, &Answer::from(11)), Verdict::Pass);
+        assert_eq!(
+            answers.check("d01", 1, &Answer::from(12)),
+            Verdict::Fail {
+                expected: Answer::from(11)
+            }
+        );
+        assert_eq!(answers.check("d01", 2, &Answer::from(31)), Verdict::Unknown);
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        assert!(KnownAnswers::parse("[d01]\npart3 = 1\n").is_err());
+    }
+}
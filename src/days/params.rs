@@ -0,0 +1,273 @@
+//! Named puzzle parameters. Some puzzles are stated with constants that differ between the example
+//! and the real input, such as the size of a grid, so a solution declares those as parameters with
+//! defaults for the real input, and examples override them.
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    Int(u64),
+    /// A width and height, written `WxH`
+    Size(u64, u64),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(x) => write!(f, "{}", x),
+            Value::Size(w, h) => write!(f, "{}x{}", w, h),
+        }
+    }
+}
+
+impl Value {
+    fn kind(&self) -> &'static str {
+        match self {
+            Value::Int(_) => "an integer",
+            Value::Size(_, _) => "a size",
+        }
+    }
+
+    /// Parse `s` as the same type of value as this one
+    fn parse_like(&self, s: &str) -> Option<Value> {
+        match self {
+            Value::Int(_) => s.parse().ok().map(Value::Int),
+            Value::Size(_, _) => {
+                let (w, h) = s.split_once('x')?;
+                Some(Value::Size(w.parse().ok()?, h.parse().ok()?))
+            }
+        }
+    }
+
+    fn numbers(&self) -> impl Iterator<Item = u64> {
+        match *self {
+            Value::Int(x) => vec![x],
+            Value::Size(w, h) => vec![w, h],
+        }
+        .into_iter()
+    }
+}
+
+/// A parameter that a solution accepts, with the value it has for the real input
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Param {
+    pub name: &'static str,
+    pub default: Value,
+    pub help: &'static str,
+    /// The smallest and largest allowed numbers, which for a size apply to both dimensions
+    pub range: (u64, u64),
+}
+
+impl Param {
+    pub const fn int(name: &'static str, default: u64, help: &'static str) -> Self {
+        Param {
+            name,
+            default: Value::Int(default),
+            help,
+            range: (0, u64::MAX),
+        }
+    }
+
+    pub const fn size(name: &'static str, width: u64, height: u64, help: &'static str) -> Self {
+        Param {
+            name,
+            default: Value::Size(width, height),
+            help,
+            range: (0, u64::MAX),
+        }
+    }
+
+    /// Limit the values the parameter can be set to, so that solutions can convert it safely
+    pub const fn range(self, min: u64, max: u64) -> Self {
+        Param {
+            range: (min, max),
+            ..self
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParamError {
+    Unknown(String),
+    WrongType {
+        name: String,
+        expected: &'static str,
+    },
+    OutOfRange {
+        name: String,
+        range: (u64, u64),
+    },
+    /// Not written as `name=value`
+    Syntax(String),
+}
+
+impl fmt::Display for ParamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParamError::Unknown(name) => write!(f, "unknown parameter {:?}", name),
+            ParamError::WrongType { name, expected } => {
+                write!(f, "parameter {:?} must be {}", name, expected)
+            }
+            ParamError::OutOfRange { name, range } => write!(
+                f,
+                "parameter {:?} must be from {} to {}",
+                name, range.0, range.1
+            ),
+            ParamError::Syntax(s) => write!(f, "expected name=value, found {:?}", s),
+        }
+    }
+}
+
+impl std::error::Error for ParamError {}
+
+/// Split a `name=value` assignment, as given on the command line
+pub fn parse_assignment(s: &str) -> Result<(String, String), ParamError> {
+    s.split_once('=')
+        .filter(|(name, _)| !name.is_empty())
+        .map(|(name, value)| (name.to_string(), value.to_string()))
+        .ok_or_else(|| ParamError::Syntax(s.to_string()))
+}
+
+/// The value of every parameter a solution declares
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Params {
+    values: Vec<(Param, Value)>,
+}
+
+impl Params {
+    /// The default value of each of `params`
+    pub fn defaults(params: &[Param]) -> Self {
+        Params {
+            values: params.iter().map(|&p| (p, p.default)).collect(),
+        }
+    }
+
+    pub fn declares(&self, name: &str) -> bool {
+        self.values.iter().any(|(p, _)| p.name == name)
+    }
+
+    /// Override a declared parameter with a value of the same type
+    pub fn set(&mut self, name: &str, value: Value) -> Result<(), ParamError> {
+        let (param, current) = self
+            .values
+            .iter_mut()
+            .find(|(p, _)| p.name == name)
+            .ok_or_else(|| ParamError::Unknown(name.to_string()))?;
+        if std::mem::discriminant(current) != std::mem::discriminant(&value) {
+            return Err(ParamError::WrongType {
+                name: name.to_string(),
+                expected: current.kind(),
+            });
+        }
+        let (min, max) = param.range;
+        if value.numbers().any(|x| x < min || x > max) {
+            return Err(ParamError::OutOfRange {
+                name: name.to_string(),
+                range: param.range,
+            });
+        }
+        *current = value;
+        Ok(())
+    }
+
+    /// Override a declared parameter with a value written as text, like `40` or `11x7`
+    pub fn set_str(&mut self, name: &str, value: &str) -> Result<(), ParamError> {
+        let (_, current) = self
+            .values
+            .iter()
+            .find(|(p, _)| p.name == name)
+            .ok_or_else(|| ParamError::Unknown(name.to_string()))?;
+        let value = current
+            .parse_like(value)
+            .ok_or_else(|| ParamError::WrongType {
+                name: name.to_string(),
+                expected: current.kind(),
+            })?;
+        self.set(name, value)
+    }
+
+    /// Apply `overrides`, given as `(name, value)`, to the parameters that are declared. Overrides
+    /// for other parameters are ignored, so that the same ones can be given to every day.
+    pub fn apply(&mut self, overrides: &[(String, String)]) -> Result<(), ParamError> {
+        for (name, value) in overrides {
+            if self.declares(name) {
+                self.set_str(name, value)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn get(&self, name: &str) -> Value {
+        self.values
+            .iter()
+            .find(|(p, _)| p.name == name)
+            .map(|&(_, value)| value)
+            .unwrap_or_else(|| panic!("parameter {:?} is not declared", name))
+    }
+
+    /// The value of an integer parameter. Panics if the solution didn't declare it as one.
+    pub fn int(&self, name: &str) -> u64 {
+        match self.get(name) {
+            Value::Int(x) => x,
+            other => panic!("parameter {:?} is {}, not an integer", name, other.kind()),
+        }
+    }
+
+    /// The value of a size parameter. Panics if the solution didn't declare it as one.
+    pub fn size(&self, name: &str) -> (u64, u64) {
+        match self.get(name) {
+            Value::Size(w, h) => (w, h),
+            other => panic!("parameter {:?} is {}, not a size", name, other.kind()),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, Value)> + '_ {
+        self.values.iter().map(|&(p, value)| (p.name, value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMS: &[Param] = &[
+        Param::size("size", 101, 103, "grid size").range(1, 1000),
+        Param::int("threshold", 100, "picoseconds saved"),
+    ];
+
+    #[test]
+    fn defaults_and_overrides() {
+        let mut params = Params::defaults(PARAMS);
+        assert_eq!(params.size("size"), (101, 103));
+        params.set("size", Value::Size(11, 7)).unwrap();
+        assert_eq!(params.size("size"), (11, 7));
+        assert_eq!(params.int("threshold"), 100);
+    }
+
+    #[test]
+    fn rejects_bad_overrides() {
+        let mut params = Params::defaults(PARAMS);
+        assert_eq!(
+            params.set("depth", Value::Int(2)),
+            Err(ParamError::Unknown("depth".to_string()))
+        );
+        assert!(params.set("threshold", Value::Size(1, 1)).is_err());
+        assert!(params.set("size", Value::Size(0, 7)).is_err());
+        assert!(params.set_str("size", "11").is_err());
+    }
+
+    #[test]
+    fn from_text() {
+        let mut params = Params::defaults(PARAMS);
+        params.set_str("size", "11x7").unwrap();
+        params.set_str("threshold", "64").unwrap();
+        assert_eq!(params.size("size"), (11, 7));
+        assert_eq!(params.int("threshold"), 64);
+
+        assert_eq!(
+            parse_assignment("depth=40").unwrap(),
+            ("depth".to_string(), "40".to_string())
+        );
+        assert!(parse_assignment("depth").is_err());
+    }
+}
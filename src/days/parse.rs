@@ -0,0 +1,140 @@
+//! Small helpers for turning puzzle inputs into values without panicking. Every helper takes the
+//! full input alongside the slice being parsed so that errors can report where they happened.
+
+use std::{fmt::Display, str::FromStr};
+
+use ndarray::Array2;
+
+use super::error::ParseError;
+
+/// An empty slice at the very end of the input, for reporting things that are missing
+pub fn end(input: &str) -> &str {
+    &input[input.len()..]
+}
+
+pub fn number<T>(input: &str, s: &str) -> Result<T, ParseError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    s.parse()
+        .map_err(|e| ParseError::at(input, s, format!("invalid number {:?}: {}", s, e)))
+}
+
+pub fn split_once<'a>(
+    input: &str,
+    s: &'a str,
+    delimiter: &str,
+) -> Result<(&'a str, &'a str), ParseError> {
+    s.split_once(delimiter)
+        .ok_or_else(|| ParseError::at(input, s, format!("expected {:?} in {:?}", delimiter, s)))
+}
+
+pub fn strip_prefix<'a>(input: &str, s: &'a str, prefix: &str) -> Result<&'a str, ParseError> {
+    s.strip_prefix(prefix)
+        .ok_or_else(|| ParseError::at(input, s, format!("expected {:?}", prefix)))
+}
+
+/// Take the next item from an iterator over pieces of `within`, a slice of the input. If the
+/// iterator has run out the error points at the end of `within`.
+pub fn next<'a>(
+    input: &str,
+    within: &str,
+    iter: &mut impl Iterator<Item = &'a str>,
+    expected: &str,
+) -> Result<&'a str, ParseError> {
+    iter.next()
+        .ok_or_else(|| ParseError::at(input, end(within), format!("missing {}", expected)))
+}
+
+/// Build a rectangular grid from the lines of `within`, a slice of the input, using `cell` to
+/// convert each character. Characters that `cell` rejects and rows of the wrong length are errors.
+pub fn grid<T>(
+    input: &str,
+    within: &str,
+    cell: impl Fn(char) -> Option<T>,
+) -> Result<Array2<T>, ParseError> {
+    let ncols = next(input, within, &mut within.lines(), "a grid")?
+        .chars()
+        .count();
+    let mut nrows = 0;
+    let mut values = Vec::with_capacity(within.len());
+
+    for l in within.lines() {
+        let len = l.chars().count();
+        if len != ncols {
+            return Err(ParseError::at(
+                input,
+                l,
+                format!("expected a row of length {}, found {}", ncols, len),
+            ));
+        }
+        for (j, c) in l.char_indices() {
+            match cell(c) {
+                Some(v) => values.push(v),
+                None => {
+                    return Err(ParseError::at(
+                        input,
+                        &l[j..],
+                        format!("unexpected character {:?}", c),
+                    ))
+                }
+            }
+        }
+        nrows += 1;
+    }
+
+    Ok(Array2::from_shape_vec((nrows, ncols), values).unwrap())
+}
+
+/// Find the (row, column) of the first occurrence of `to_find` in `within`, a grid in the input
+pub fn find_in_grid(
+    input: &str,
+    within: &str,
+    to_find: char,
+) -> Result<(usize, usize), ParseError> {
+    within
+        .lines()
+        .enumerate()
+        .find_map(|(i, l)| l.chars().position(|c| c == to_find).map(|j| (i, j)))
+        .ok_or_else(|| ParseError::at(input, end(within), format!("no {:?} found", to_find)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn number_error_location() {
+        let input = "1 2\n3 x";
+        let err = input
+            .split_whitespace()
+            .map(|s| number::<u32>(input, s))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap_err();
+        assert_eq!((err.line, err.column), (2, 3));
+    }
+
+    #[test]
+    fn ragged_grid() {
+        let input = "...\n..\n...";
+        let err = grid(input, input, Some).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 1));
+    }
+
+    #[test]
+    fn unexpected_grid_character() {
+        let input = "..#\n.?.";
+        let err = grid(input, input, |c| matches!(c, '.' | '#').then_some(c)).unwrap_err();
+        assert_eq!((err.line, err.column), (2, 2));
+    }
+
+    #[test]
+    fn missing_line() {
+        let input = "only one line";
+        let mut ls = input.lines();
+        next(input, input, &mut ls, "first").unwrap();
+        let err = next(input, input, &mut ls, "second").unwrap_err();
+        assert_eq!((err.line, err.column), (1, 14));
+    }
+}
@@ -0,0 +1,251 @@
+//! Every day registers itself here with the [`register!`] macro, so that adding a day only means
+//! adding its module. Days are collected at startup with `inventory`.
+
+use super::{
+    known_answers::Verdict,
+    params::{Param, ParamError, Params, Value},
+    solution::{self, ParseFn},
+    Answer, Solution,
+};
+
+pub const FIRST_DAY: u8 = 1;
+pub const LAST_DAY: u8 = 25;
+
+/// One way of solving a day
+#[derive(Debug)]
+pub struct Implementation {
+    pub name: &'static str,
+    pub params: &'static [Param],
+    pub(super) parse: ParseFn,
+}
+
+impl Implementation {
+    /// The implementation `name`, which solves the day with `S`
+    pub const fn of<S: Solution + 'static>(name: &'static str) -> Self {
+        Implementation {
+            name,
+            params: S::PARAMS,
+            parse: solution::parse_erased::<S>,
+        }
+    }
+
+    /// The parameters to solve `example` with
+    pub fn example_params(&self, example: &Example) -> Result<Params, ParamError> {
+        let mut params = Params::defaults(self.params);
+        for &(name, value) in example.params {
+            params.set(name, value)?;
+        }
+        Ok(params)
+    }
+}
+
+/// An example from the puzzle text, with the answers it gives. Parts without an answer aren't run,
+/// since some puzzles (like d14 part 2) have no answer for the example.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Example {
+    /// The input, which is trimmed before use so that it can be written on its own lines
+    pub input: &'static str,
+    pub part1: Option<&'static str>,
+    pub part2: Option<&'static str>,
+    /// Parameters that differ from the real input's
+    pub params: &'static [(&'static str, Value)],
+}
+
+impl Example {
+    pub const fn new(input: &'static str) -> Self {
+        Example {
+            input,
+            part1: None,
+            part2: None,
+            params: &[],
+        }
+    }
+
+    pub const fn part1(self, answer: &'static str) -> Self {
+        Example {
+            part1: Some(answer),
+            ..self
+        }
+    }
+
+    pub const fn part2(self, answer: &'static str) -> Self {
+        Example {
+            part2: Some(answer),
+            ..self
+        }
+    }
+
+    pub const fn params(self, params: &'static [(&'static str, Value)]) -> Self {
+        Example { params, ..self }
+    }
+
+    pub fn answer(&self, part: u8) -> Option<&'static str> {
+        match part {
+            1 => self.part1,
+            2 => self.part2,
+            _ => None,
+        }
+    }
+
+    pub fn check(&self, part: u8, actual: &Answer) -> Verdict {
+        // Numbers are compared as numbers, like the answers file does, and `-` is a part that isn't
+        // written yet, as it's displayed
+        let expected = self.answer(part).map(|s| match s {
+            "-" => Answer::NotImplemented,
+            s => s
+                .parse::<i128>()
+                .map_or_else(|_| Answer::from(s), Answer::from),
+        });
+        match expected {
+            None => Verdict::Unknown,
+            Some(expected) if expected == *actual => Verdict::Pass,
+            Some(expected) => Verdict::Fail { expected },
+        }
+    }
+}
+
+/// A day's entry in the registry. The first implementation is the default.
+#[derive(Debug)]
+pub struct Registration {
+    pub year: u16,
+    pub day: u8,
+    pub title: &'static str,
+    /// What kind of puzzle it is, e.g. `grid` or `pathfinding`, for picking days with `--tag`
+    pub tags: &'static [&'static str],
+    pub implementations: &'static [Implementation],
+    pub examples: &'static [Example],
+}
+
+inventory::collect!(Registration);
+
+/// The year a day is from when none is given. Its days are named just `dNN`, as they were before
+/// there were other years.
+pub const DEFAULT_YEAR: u16 = 2024;
+
+/// Every registered day, sorted by year and then day
+pub fn registrations() -> Vec<&'static Registration> {
+    let mut all: Vec<_> = inventory::iter::<Registration>.into_iter().collect();
+    all.sort_by_key(|r| (r.year, r.day));
+    all
+}
+
+/// The days of `year` between [`FIRST_DAY`] and [`LAST_DAY`] that nothing has registered
+pub fn missing_days(year: u16) -> Vec<u8> {
+    let registered = registrations();
+    (FIRST_DAY..=LAST_DAY)
+        .filter(|&day| !registered.iter().any(|r| r.year == year && r.day == day))
+        .collect()
+}
+
+/// Register a day's solution, with its implementations by name and optionally its tags and examples:
+///
+/// ```ignore
+/// register! {
+///     year: 2024,
+///     day: 5,
+///     title: "Print Queue",
+///     tags: ["graph", "sorting"],
+///     implementations: { "default" => Solver },
+///     examples: [Example::new(EXAMPLE).part1("143").part2("123")],
+/// }
+/// ```
+macro_rules! register {
+    (
+        year: $year:literal,
+        day: $day:literal,
+        title: $title:literal,
+        $(tags: [ $($tag:literal),* $(,)? ],)?
+        implementations: { $($name:literal => $solver:ty),+ $(,)? }
+        $(, examples: [ $($example:expr),* $(,)? ])? $(,)?
+    ) => {
+        ::inventory::submit! {
+            $crate::days::registry::Registration {
+                year: $year,
+                day: $day,
+                title: $title,
+                tags: &[$($($tag),*)?],
+                implementations: &[
+                    $(
+                        $crate::days::registry::Implementation::of::<$solver>($name),
+                    )+
+                ],
+                examples: &[$($($example),*)?],
+            }
+        }
+    };
+}
+
+pub(super) use register;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn days_are_unique() {
+        let all = registrations();
+        for pair in all.windows(2) {
+            assert_ne!(
+                (pair[0].year, pair[0].day),
+                (pair[1].year, pair[1].day),
+                "registered twice"
+            );
+        }
+    }
+
+    #[test]
+    fn every_day_has_an_implementation() {
+        for r in registrations() {
+            assert!(!r.implementations.is_empty(), "d{:02}", r.day);
+            assert!((FIRST_DAY..=LAST_DAY).contains(&r.day), "d{:02}", r.day);
+        }
+    }
+
+    #[test]
+    fn examples_have_answers_and_valid_params() {
+        for r in registrations() {
+            for example in r.examples {
+                assert!(example.part1.is_some() || example.part2.is_some());
+                for implementation in r.implementations {
+                    implementation.example_params(example).unwrap();
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn unwritten_parts_match_a_dash() {
+        let example = Example::new("").part1("-").part2("12");
+        assert_eq!(example.check(1, &Answer::NotImplemented), Verdict::Pass);
+        assert_eq!(example.check(2, &Answer::from(12)), Verdict::Pass);
+        assert!(matches!(
+            example.check(2, &Answer::NotImplemented),
+            Verdict::Fail { .. }
+        ));
+    }
+
+    #[test]
+    fn implementation_names_are_unique() {
+        for r in registrations() {
+            let names: Vec<_> = r.implementations.iter().map(|i| i.name).collect();
+            assert!(
+                names
+                    .iter()
+                    .enumerate()
+                    .all(|(i, n)| !names[..i].contains(n)),
+                "d{:02} has two implementations named alike",
+                r.day
+            );
+        }
+    }
+
+    #[test]
+    fn missing_days_are_the_gaps() {
+        let missing = missing_days(2024);
+        let registered = registrations().iter().filter(|r| r.year == 2024).count();
+        assert_eq!(missing.len() + registered, LAST_DAY as usize);
+        assert!(registrations()
+            .iter()
+            .all(|r| r.year != 2024 || !missing.contains(&r.day)));
+    }
+}
@@ -0,0 +1,60 @@
+use std::marker::PhantomData;
+
+use super::{
+    params::{Param, Params},
+    Answer, ParseError,
+};
+
+/// A solution to one day's puzzle. The input is parsed once and both parts are solved from the
+/// parsed form, so that parsing can be timed separately and isn't repeated for each part.
+pub trait Solution {
+    /// The parsed input, which may borrow from the input string
+    type Parsed<'a>;
+
+    /// The parameters the parts accept, with their values for the real input
+    const PARAMS: &'static [Param] = &[];
+
+    fn parse(input: &str) -> Result<Self::Parsed<'_>, ParseError>;
+
+    fn part1(parsed: &Self::Parsed<'_>, params: &Params) -> Result<Answer, ParseError>;
+
+    fn part2(parsed: &Self::Parsed<'_>, params: &Params) -> Result<Answer, ParseError>;
+
+    /// Parse and solve part 1 in one go, with the default parameters
+    fn solve_part1(input: &str) -> Result<Answer, ParseError> {
+        Self::part1(&Self::parse(input)?, &Params::defaults(Self::PARAMS))
+    }
+
+    /// Parse and solve part 2 in one go, with the default parameters
+    fn solve_part2(input: &str) -> Result<Answer, ParseError> {
+        Self::part2(&Self::parse(input)?, &Params::defaults(Self::PARAMS))
+    }
+}
+
+/// A parsed input with the type of its solution erased, so that every day can be run alike
+pub trait ParsedInput {
+    fn part1(&self, params: &Params) -> Result<Answer, ParseError>;
+
+    fn part2(&self, params: &Params) -> Result<Answer, ParseError>;
+}
+
+struct Parsed<'a, S: Solution>(S::Parsed<'a>, PhantomData<S>);
+
+impl<S: Solution> ParsedInput for Parsed<'_, S> {
+    fn part1(&self, params: &Params) -> Result<Answer, ParseError> {
+        S::part1(&self.0, params)
+    }
+
+    fn part2(&self, params: &Params) -> Result<Answer, ParseError> {
+        S::part2(&self.0, params)
+    }
+}
+
+pub(super) type ParseFn = for<'a> fn(&'a str) -> Result<Box<dyn ParsedInput + 'a>, ParseError>;
+
+pub(super) fn parse_erased<S: Solution + 'static>(
+    input: &str,
+) -> Result<Box<dyn ParsedInput + '_>, ParseError> {
+    let parsed = S::parse(input)?;
+    Ok(Box::new(Parsed::<S>(parsed, PhantomData)))
+}
@@ -1,28 +1,49 @@
-pub fn part1(input: &str) -> i64 {
-    return 0
+use super::{registry::Example, Answer, ParseError, Params, Solution};
+
+pub struct Solver;
+
+impl Solution for Solver {
+    type Parsed<'a> = ();
+
+    fn parse(_input: &str) -> Result<Self::Parsed<'_>, ParseError> {
+        Ok(())
+    }
+
+    fn part1(_parsed: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        Ok(Answer::NotImplemented)
+    }
+
+    fn part2(_parsed: &Self::Parsed<'_>, _: &Params) -> Result<Answer, ParseError> {
+        Ok(Answer::NotImplemented)
+    }
 }
 
-pub fn part2(input: &str) -> i64 {
-    return 0
+const EXAMPLE: &str = "
+
+";
+
+super::register! {
+    year: 2024,
+    day: 0,
+    title: "",
+    tags: [],
+    implementations: { "default" => Solver },
+    examples: [Example::new(EXAMPLE).part1("-").part2("-")],
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    const EXAMPLE: &str = "
-
-";
-
     #[test]
     fn example_part1() {
-        let result = part1(EXAMPLE.trim());
-        assert_eq!(result, 0)
+        let result = Solver::solve_part1(EXAMPLE.trim()).unwrap();
+        assert_eq!(result, Answer::NotImplemented)
     }
 
     #[test]
     fn example_part2() {
-        let result = part2(EXAMPLE.trim());
-        assert_eq!(result, 0)
+        let result = Solver::solve_part2(EXAMPLE.trim()).unwrap();
+        assert_eq!(result, Answer::NotImplemented)
     }
 }
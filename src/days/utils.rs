@@ -5,6 +5,8 @@ use std::{
     ops::Add,
 };
 
+use super::{cancel, ParseError};
+
 // The std library version of this is still in unstable
 pub fn minmax_by<T, F>(a: T, b: T, mut compare: F) -> (T, T)
 where
@@ -41,6 +43,12 @@ impl BfsWorkingSpace {
     }
 }
 
+impl Default for BfsWorkingSpace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 pub fn bi_bfs<F, I>(
     s: (usize, usize),
     e: (usize, usize),
@@ -140,7 +148,13 @@ impl<T, C: Cost> AstarWorkingSpace<T, C> {
     }
 }
 
-pub fn astar<T, C: Cost, FE, FN, I, FH>(
+impl<T, C: Cost> Default for AstarWorkingSpace<T, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn astar<T, C, FE, FN, I, FH>(
     s: T,
     is_end: FE,
     neighbours: FN,
@@ -229,12 +243,19 @@ impl<T, C: Cost> DijkstraWorkingSpace<T, C> {
     }
 }
 
-pub fn dijkstra_cost_map<T, C: Cost, FN, I>(
+impl<T, C: Cost> Default for DijkstraWorkingSpace<T, C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The lowest cost of reaching each node from `s`. Fails only if the search is cancelled.
+pub fn dijkstra_cost_map<T, C, FN, I>(
     s: T,
     neighbours: FN,
     ws: &mut DijkstraWorkingSpace<T, C>,
-    map_size: usize
-) -> HashMap<T, C>
+    map_size: usize,
+) -> Result<HashMap<T, C>, ParseError>
 where
     T: Copy + Eq + Hash,
     C: Cost,
@@ -250,7 +271,13 @@ where
 
     let mut cost_map = HashMap::with_capacity(map_size);
 
+    let mut steps = 0_u32;
     while let Some(current) = ws.queue.pop() {
+        steps = steps.wrapping_add(1);
+        if steps.is_multiple_of(4096) {
+            cancel::check()?;
+        }
+
         for (next, edge_weight) in neighbours(current.node) {
             let cost = current.cost + edge_weight;
 
@@ -264,5 +291,5 @@ where
         }
     }
 
-    cost_map
+    Ok(cost_map)
 }
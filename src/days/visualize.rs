@@ -0,0 +1,213 @@
+//! Watching simulations as they run. Days that simulate something pass each step to [`frame`] as a
+//! [`Frame`], which goes to the [`Observer`] installed on the current thread. Without an observer
+//! nothing happens, and the frame isn't even built.
+
+use std::{
+    cell::RefCell,
+    fmt::Write as _,
+    io::{self, Write},
+    thread,
+    time::Duration,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colour {
+    /// The terminal's own colour
+    Plain,
+    Grey,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+}
+
+impl Colour {
+    fn ansi(self) -> &'static str {
+        match self {
+            Colour::Plain => "\x1b[0m",
+            Colour::Grey => "\x1b[90m",
+            Colour::Red => "\x1b[31m",
+            Colour::Green => "\x1b[32m",
+            Colour::Yellow => "\x1b[33m",
+            Colour::Blue => "\x1b[34m",
+            Colour::Magenta => "\x1b[35m",
+            Colour::Cyan => "\x1b[36m",
+        }
+    }
+}
+
+/// One step of a simulation, as rows of coloured characters
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    /// What the frame shows, e.g. `step 10`
+    pub caption: String,
+    pub rows: Vec<Vec<(char, Colour)>>,
+}
+
+impl Frame {
+    /// A frame with `rows` rows of `cols` characters, given by `cell(row, col)`
+    pub fn grid(
+        caption: impl Into<String>,
+        rows: usize,
+        cols: usize,
+        mut cell: impl FnMut(usize, usize) -> (char, Colour),
+    ) -> Self {
+        Frame {
+            caption: caption.into(),
+            rows: (0..rows)
+                .map(|r| (0..cols).map(|c| cell(r, c)).collect())
+                .collect(),
+        }
+    }
+
+    /// The frame as text with ANSI colour codes, changing colour only where it has to and resetting
+    /// it at the end of each row
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::new();
+        for row in &self.rows {
+            let mut current = Colour::Plain;
+            for &(c, colour) in row {
+                if colour != current {
+                    out += colour.ansi();
+                    current = colour;
+                }
+                out.push(c);
+            }
+            if current != Colour::Plain {
+                out += Colour::Plain.ansi();
+            }
+            out.push('\n');
+        }
+        writeln!(out, "{}", self.caption).unwrap();
+        out
+    }
+}
+
+/// Something that wants to see the frames of a simulation
+pub trait Observer {
+    fn frame(&mut self, frame: &Frame);
+}
+
+thread_local! {
+    static OBSERVER: RefCell<Option<Box<dyn Observer>>> = const { RefCell::new(None) };
+}
+
+/// Send the frames of the simulations run on the current thread to `observer` from now on
+pub fn install(observer: Box<dyn Observer>) {
+    OBSERVER.with(|o| *o.borrow_mut() = Some(observer));
+}
+
+/// Whether anything is watching the simulations run on the current thread
+pub fn is_enabled() -> bool {
+    OBSERVER.with(|o| o.borrow().is_some())
+}
+
+/// Show the frame made by `make` to the current thread's observer, if there is one
+pub fn frame(make: impl FnOnce() -> Frame) {
+    OBSERVER.with(|o| {
+        if let Some(observer) = o.borrow_mut().as_mut() {
+            observer.frame(&make());
+        }
+    });
+}
+
+/// Draws frames over each other in a terminal, pausing after each one so they can be followed
+pub struct Terminal<W> {
+    out: W,
+    delay: Duration,
+    /// Only every this many frames are drawn
+    every: usize,
+    seen: usize,
+}
+
+impl Terminal<io::Stderr> {
+    /// Draw on stderr, so that the frames can be watched while the answers go to a file
+    pub fn stderr(delay: Duration, every: usize) -> Self {
+        Terminal::new(io::stderr(), delay, every)
+    }
+}
+
+impl<W: Write> Terminal<W> {
+    pub fn new(out: W, delay: Duration, every: usize) -> Self {
+        Terminal {
+            out,
+            delay,
+            every: every.max(1),
+            seen: 0,
+        }
+    }
+}
+
+impl<W: Write> Observer for Terminal<W> {
+    fn frame(&mut self, frame: &Frame) {
+        let skip = !self.seen.is_multiple_of(self.every);
+        self.seen += 1;
+        if skip {
+            return;
+        }
+        // Move to the top left and clear the screen. Errors are ignored, as a closed terminal
+        // shouldn't stop the solution
+        let _ =
+            write!(self.out, "\x1b[H\x1b[2J{}", frame.to_ansi()).and_then(|()| self.out.flush());
+        thread::sleep(self.delay);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn renders_colours() {
+        let frame = Frame::grid("step 1", 2, 3, |r, c| match (r, c) {
+            (0, 0) => ('@', Colour::Red),
+            (_, 2) => ('#', Colour::Grey),
+            _ => ('.', Colour::Plain),
+        });
+        assert_eq!(
+            frame.to_ansi(),
+            "\x1b[31m@\x1b[0m.\x1b[90m#\x1b[0m\n..\x1b[90m#\x1b[0m\nstep 1\n"
+        );
+    }
+
+    struct Recorder(Rc<RefCell<Vec<String>>>);
+
+    impl Observer for Recorder {
+        fn frame(&mut self, frame: &Frame) {
+            self.0.borrow_mut().push(frame.caption.clone());
+        }
+    }
+
+    fn step(i: usize) -> Frame {
+        Frame::grid(format!("step {}", i), 1, 1, |_, _| ('x', Colour::Plain))
+    }
+
+    #[test]
+    fn frames_go_to_the_installed_observer() {
+        let captions = std::thread::spawn(|| {
+            frame(|| panic!("built a frame without an observer"));
+            let captions = Rc::default();
+            install(Box::new(Recorder(Rc::clone(&captions))));
+            (0..2).for_each(|i| frame(|| step(i)));
+            let captions = captions.borrow().clone();
+            captions
+        })
+        .join()
+        .unwrap();
+        assert_eq!(captions, ["step 0", "step 1"]);
+        assert!(!is_enabled());
+    }
+
+    #[test]
+    fn terminal_skips_frames() {
+        let mut out = Vec::new();
+        let mut terminal = Terminal::new(&mut out, Duration::ZERO, 2);
+        (0..3).for_each(|i| terminal.frame(&step(i)));
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out, "\x1b[H\x1b[2Jx\nstep 0\n\x1b[H\x1b[2Jx\nstep 2\n");
+    }
+}
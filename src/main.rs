@@ -1,69 +1,1040 @@
-use std::time::{Duration, Instant};
-
-use advent_of_code_2024::days::{Day, DAYS};
-
-fn format_duration(d: Duration) -> String {
-    let nanos = d.as_nanos();
-
-    if nanos < 1000 {
-        format!("{}ns", nanos)
-    } else if nanos < 1_000_000 {
-        format!("{:.3}µs", nanos as f64 / 1000.0)
-    } else if nanos < 1_000_000_000 {
-        format!("{:.3}ms", nanos as f64 / 1_000_000.0)
-    } else {
-        format!("{:.3}s", nanos as f64 / 1_000_000_000.0)
-    }
-}
-
-pub fn time_execution<F, T>(f: F) -> (T, std::time::Duration)
-where
-    F: FnOnce() -> T,
-{
-    let start = Instant::now();
-    let result = f();
-    let duration = start.elapsed();
-    (result, duration)
-}
-
-fn run_days(days: Vec<&Day>) {
-    let (results, total) = time_execution(|| {
-        days.into_iter()
-            .map(|day| {
-                let (part1, duration1) = time_execution(|| day.part1());
-                let (part2, duration2) = time_execution(|| day.part2());
-                return (day.name.as_str(), part1, part2, duration1, duration2);
-            })
-            .collect::<Vec<_>>()
+use std::{
+    fmt::Write as _,
+    io::{self, Write as _},
+    iter,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    thread,
+    time::Duration,
+};
+
+use advent_of_code_2024::days::{
+    crypt::CryptError,
+    day_name, find_day,
+    input::default_data_dir,
+    known_answers::{self, AnswersError, KnownAnswers, Verdict},
+    params::{self, ParamError},
+    registry::{self, Implementation},
+    Answer, Day, InputError, InputSource, Params, DAYS,
+};
+use clap::{Parser, Subcommand};
+use itertools::Itertools;
+use report::{Format, PartReport, Report, Status, Timing};
+use runner::{DayResult, Failure, Repetitions, RunResults, Task, Timed};
+
+mod allocations;
+mod baseline;
+mod report;
+mod runner;
+mod scaffold;
+mod select;
+mod serve;
+mod site;
+mod watch;
+
+#[cfg(feature = "count-allocations")]
+#[global_allocator]
+static ALLOCATOR: allocations::Counting = allocations::Counting;
+
+/// Run Advent of Code 2024 solutions
+#[derive(Parser, Debug)]
+#[command(
+    args_conflicts_with_subcommands = true,
+    after_help = "Days are selected like this, from 2024 unless a year is given:
+  5, d05    day 5
+  2023/5    day 5 of 2023
+  2023/*    every day of 2023
+  1-10      days 1 to 10
+  17.2      only part 2 of day 17
+  !d14      leave out day 14, from every day if nothing else is selected"
+)]
+struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Days to run, see below. Runs every day if none are given
+    #[arg(value_parser = select::Selection::parse)]
+    days: Vec<select::Selection>,
+
+    /// Only run the selected days with this tag, e.g. `grid`. Days with any of the tags run if
+    /// given more than once, see --list
+    #[arg(long = "tag", value_name = "TAG")]
+    tags: Vec<String>,
+
+    /// Read the input for a single day from this file, or from stdin if `-`
+    #[arg(long, value_name = "FILE")]
+    input: Option<PathBuf>,
+
+    /// Directory containing a `YYYY/dNN.txt` input file for each day. Builds with the
+    /// `embed-inputs` feature use the inputs built into them unless this is given. Also where `new`
+    /// and `fetch` put inputs and `submit` records answers, given after the subcommand
+    #[arg(long, value_name = "DIR", env = "AOC_DATA_DIR", global = true)]
+    data_dir: Option<PathBuf>,
+
+    /// Compare answers against `answers.toml` in the data directory, or the one built in with the
+    /// inputs, failing if any differ
+    #[arg(long, conflicts_with_all = ["input", "record", "params"])]
+    check: bool,
+
+    /// Write the answers from this run to `answers.toml` in the data directory
+    #[arg(long, conflicts_with_all = ["input", "params"])]
+    record: bool,
+
+    /// Run the examples from each puzzle instead of the real input, checking their answers
+    #[arg(long, conflicts_with_all = ["input", "check", "record"])]
+    examples: bool,
+
+    /// How to print the results
+    #[arg(long, value_enum, default_value_t = Format::Table)]
+    format: Format,
+
+    /// Time each part over this many runs, reporting min, median, mean and standard deviation
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u32).range(1..))]
+    repeat: u32,
+
+    /// Run each part this many times before timing it
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    warmup: u32,
+
+    /// Run this implementation instead of each day's default, given as `NAME` for every day that
+    /// has it or `dNN/NAME` for one day, see --list
+    #[arg(long = "impl", value_name = "IMPL")]
+    implementations: Vec<String>,
+
+    /// Run every implementation of each day, failing if any disagree with the default one
+    #[arg(long, conflicts_with_all = ["implementations", "record"])]
+    cross_check: bool,
+
+    /// Override a puzzle parameter, e.g. `part2_depth=40`. Applies to every selected day that declares
+    /// it, see --list
+    #[arg(long = "param", value_name = "NAME=VALUE", value_parser = params::parse_assignment)]
+    params: Vec<(String, String)>,
+
+    /// Give up on parsing or on a part after this long (e.g. `500ms`, `10s` or `2m`), including
+    /// all of its repetitions, and carry on with the other days
+    #[arg(long, value_name = "DURATION", value_parser = runner::parse_duration)]
+    timeout: Option<Duration>,
+
+    /// Save this run's timings as a baseline to compare later runs against
+    #[arg(long, value_name = "NAME")]
+    save_baseline: Option<String>,
+
+    /// Compare this run's timings with a saved baseline, and fail if any part got slower
+    #[arg(long, value_name = "NAME")]
+    baseline: Option<String>,
+
+    /// Where baselines are saved
+    #[arg(long, value_name = "DIR", default_value = baseline::DEFAULT_DIR)]
+    baseline_dir: PathBuf,
+
+    /// How many percent slower a part has to be, beyond the noise in its timings, to count as a
+    /// regression against --baseline
+    #[arg(long, value_name = "PERCENT", default_value_t = 10.0)]
+    regression_threshold: f64,
+
+    /// Keep running, and solve the selected days again whenever their input files change
+    #[arg(long, conflicts_with_all = ["examples", "record", "save_baseline", "baseline"])]
+    watch: bool,
+
+    /// How often --watch checks the input files for changes
+    #[arg(long, value_name = "DURATION", default_value = watch::DEFAULT_INTERVAL, value_parser = runner::parse_duration, requires = "watch")]
+    poll_interval: Duration,
+
+    /// Draw the simulations of the days that have them (6, 14, 15, 16 and 18) on stderr as they run
+    #[arg(long, conflicts_with_all = ["repeat", "warmup", "jobs"])]
+    visualize: bool,
+
+    /// How long to show each frame for with --visualize
+    #[arg(long, value_name = "DURATION", default_value = "50ms", value_parser = runner::parse_duration, requires = "visualize")]
+    frame_delay: Duration,
+
+    /// Only draw every this many frames with --visualize, to get through long simulations
+    #[arg(long, value_name = "N", default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..), requires = "visualize")]
+    frame_every: u64,
+
+    /// Solve this many days at once. 0 uses every available core
+    #[arg(long, short, value_name = "N", default_value_t = 1)]
+    jobs: usize,
+
+    /// List the registered days, and any that are missing, instead of running them
+    #[arg(long)]
+    list: bool,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Start a new day from the template, with an empty input file
+    New {
+        /// The day to create, as a number or name (e.g. `12`, `d12` or `2023/12`)
+        day: String,
+
+        /// The puzzle's title
+        #[arg(long, default_value = "")]
+        title: String,
+    },
+    /// Solve inputs sent as JSON over a local socket, see `src/serve.rs` for the protocol
+    Serve {
+        /// The TCP address to listen on. Port 0 picks a free port
+        #[arg(long, value_name = "ADDRESS", default_value = serve::DEFAULT_ADDRESS)]
+        listen: String,
+
+        /// Listen on this Unix socket instead
+        #[arg(long, value_name = "PATH", conflicts_with = "listen")]
+        unix: Option<PathBuf>,
+
+        /// Give up on a request once parsing or the part takes longer than this
+        #[arg(long, value_name = "DURATION", default_value = serve::DEFAULT_TIMEOUT, value_parser = runner::parse_duration)]
+        timeout: Duration,
+    },
+    /// Download a day's input into the data directory
+    Fetch {
+        /// The day to fetch, as a number or name (e.g. `12`, `d12` or `2023/12`)
+        day: String,
+
+        /// Replace the input if there already is one
+        #[arg(long)]
+        force: bool,
+
+        #[command(flatten)]
+        site: SiteArgs,
+    },
+    /// Submit an answer, and record it in `answers.toml` if it's right
+    Submit {
+        /// The day, as a number or name (e.g. `12`, `d12` or `2023/12`)
+        day: String,
+
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: u8,
+
+        /// The answer to submit. Solves the day's input to get it if not given
+        answer: Option<String>,
+
+        #[command(flatten)]
+        site: SiteArgs,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct SiteArgs {
+    /// The `session` cookie of a logged in browser
+    #[arg(long, env = "AOC_SESSION", hide_env_values = true)]
+    session: String,
+
+    /// Where the site is
+    #[arg(long, env = "AOC_BASE_URL", default_value = site::DEFAULT_BASE_URL)]
+    base_url: String,
+}
+
+fn failure_status(failure: &Failure) -> Status {
+    match failure {
+        Failure::Error(_) => Status::Error,
+        Failure::Timeout(_) => Status::Timeout,
+        Failure::Panic(_) => Status::Error,
+        Failure::NotRun => Status::Skipped,
+    }
+}
+
+fn part_report(
+    task: &Task,
+    part: u8,
+    timed: &Timed<Answer>,
+    known: Option<&KnownAnswers>,
+) -> PartReport {
+    let day = task.day.name.as_str();
+    let timing = Timing::from_samples(&timed.samples);
+    let verdict = match (&timed.result, task.example, known) {
+        (Err(_), _, _) => None,
+        (Ok(answer), Some((_, example)), _) => Some(example.check(part, answer)),
+        (Ok(answer), None, Some(known)) => Some(known.check(day, part, answer)),
+        (Ok(_), None, None) => None,
+    };
+    let (status, expected) = match (&timed.result, verdict) {
+        (Err(failure), _) => (failure_status(failure), None),
+        (Ok(_), None) => (Status::Ok, None),
+        (Ok(_), Some(Verdict::Pass)) => (Status::Pass, None),
+        (Ok(_), Some(Verdict::Fail { expected })) => (Status::Fail, Some(expected.to_string())),
+        (Ok(_), Some(Verdict::Unknown)) => (Status::Unknown, None),
+    };
+
+    PartReport {
+        day: day.to_string(),
+        example: task.example.map(|(i, _)| i),
+        part,
+        implementation: task.implementation.name.to_string(),
+        status,
+        answer: timed.result.as_ref().ok().map(Answer::to_string),
+        expected,
+        duration_ns: timing.median_ns,
+        error: timed.result.as_ref().err().map(Failure::to_string),
+        timing,
+        allocations: timed.allocations,
+    }
+}
+
+/// The report for parsing, as part 0
+fn parse_report(task: &Task, parse: &Timed<()>) -> PartReport {
+    let timing = Timing::from_samples(&parse.samples);
+    PartReport {
+        day: task.day.name.clone(),
+        example: task.example.map(|(i, _)| i),
+        part: 0,
+        implementation: task.implementation.name.to_string(),
+        status: match &parse.result {
+            Ok(()) => Status::Ok,
+            Err(failure) => failure_status(failure),
+        },
+        answer: None,
+        expected: None,
+        duration_ns: timing.median_ns,
+        error: parse.result.as_ref().err().map(Failure::to_string),
+        timing,
+        allocations: parse.allocations,
+    }
+}
+
+/// The reports for parsing and each part of a task
+fn task_reports(
+    task: &Task,
+    result: &Result<DayResult, InputError>,
+    known: Option<&KnownAnswers>,
+) -> Vec<PartReport> {
+    match result {
+        Ok(r) => iter::once(parse_report(task, &r.parse))
+            .chain(
+                r.parts
+                    .iter()
+                    .map(|(part, timed)| part_report(task, *part, timed, known)),
+            )
+            .collect(),
+        Err(e) => {
+            let (status, error) = match e {
+                InputError::Missing(path) => {
+                    (Status::Skipped, format!("no input at {}", path.display()))
+                }
+                InputError::Decrypt(_, CryptError::NoKey) => {
+                    (Status::Skipped, CryptError::NoKey.to_string())
+                }
+                e => (Status::Error, e.to_string()),
+            };
+            iter::once(0)
+                .chain(task.parts())
+                .map(|part| PartReport {
+                    day: task.day.name.clone(),
+                    example: task.example.map(|(i, _)| i),
+                    part,
+                    implementation: task.implementation.name.to_string(),
+                    status,
+                    answer: None,
+                    expected: None,
+                    duration_ns: 0,
+                    error: Some(error.clone()),
+                    timing: Timing::default(),
+                    allocations: None,
+                })
+                .collect()
+        }
+    }
+}
+
+/// The whole report, from the reports of every part and the timings in `results`
+fn build_report(
+    parts: Vec<PartReport>,
+    results: &RunResults,
+    total: Duration,
+    jobs: usize,
+) -> Report {
+    // Total across parsing and every part for each repetition. Parts that failed early have fewer
+    // samples, and only count towards the repetitions they ran in.
+    let mut totals = Vec::new();
+    for r in results.iter().filter_map(|(_, r)| r.as_ref().ok()) {
+        for samples in r.samples() {
+            if totals.len() < samples.len() {
+                totals.resize(samples.len(), Duration::ZERO);
+            }
+            for (total, sample) in totals.iter_mut().zip(samples) {
+                *total += *sample;
+            }
+        }
+    }
+
+    let cpu = results
+        .iter()
+        .filter_map(|(_, r)| r.as_ref().ok())
+        .flat_map(|r| r.samples().flatten())
+        .sum();
+
+    Report {
+        parts,
+        jobs,
+        total_duration_ns: report::nanos(total),
+        cpu_duration_ns: report::nanos(cpu),
+        total: Timing::from_samples(&totals),
+        baseline: None,
+    }
+}
+
+/// Solve every task, printing each day as soon as it's done so that they aren't lost if something
+/// goes wrong later. The footer is left to the caller.
+fn solve(
+    tasks: &[Task],
+    config: &runner::Config,
+    jobs: usize,
+    format: Format,
+    known: Option<&KnownAnswers>,
+    check_implementations: bool,
+) -> (Report, RunResults) {
+    let mut parts = Vec::new();
+    emit(format.header());
+    let (results, total) = runner::run_tasks(tasks.to_vec(), config, jobs, |task, result| {
+        let start = parts.len();
+        parts.extend(task_reports(&task, result, known));
+        if check_implementations {
+            cross_check(&mut parts);
+        }
+        emit(&format.parts(&parts[start..]));
     });
-    results
+    (build_report(parts, &results, total, jobs), results)
+}
+
+/// Mark each answer that differs from the default implementation's as a failure, unless it was
+/// already checked against a known answer. Tasks run each day's default implementation first, so
+/// it gives the first answer for each part.
+fn cross_check(parts: &mut [PartReport]) {
+    let mut reference: Vec<&PartReport> = Vec::new();
+    let mut mismatches = Vec::new();
+    for (i, p) in parts.iter().enumerate().filter(|(_, p)| p.part > 0) {
+        let Some(answer) = &p.answer else {
+            continue;
+        };
+        let same_part =
+            |r: &&&PartReport| (&r.day, r.example, r.part) == (&p.day, p.example, p.part);
+        match reference.iter().find(same_part) {
+            None => reference.push(p),
+            Some(r) if r.answer.as_ref() != Some(answer) => {
+                if matches!(p.status, Status::Ok | Status::Unknown) {
+                    mismatches.push((
+                        i,
+                        r.answer.clone(),
+                        format!("disagrees with {}/{}", r.day, r.implementation),
+                    ));
+                }
+            }
+            Some(_) => (),
+        }
+    }
+    for (i, expected, error) in mismatches {
+        parts[i].status = Status::Fail;
+        parts[i].expected = expected;
+        parts[i].error = Some(error);
+    }
+}
+
+fn record(answers_path: &Path, results: &RunResults) -> Result<(), AnswersError> {
+    let mut known = KnownAnswers::load(answers_path)?;
+    for (task, r) in results.iter().filter(|(task, _)| task.example.is_none()) {
+        for (part, timed) in r.iter().flat_map(|r| &r.parts) {
+            if let Ok(answer) = &timed.result {
+                known.set(&task.day.name, *part, answer.clone());
+            }
+        }
+    }
+    known.save(answers_path)
+}
+
+/// Check that each override is for a parameter that one of `days` declares, and that its value
+/// suits every day that does
+fn check_overrides(days: &[&Day], overrides: &[(String, String)]) -> Result<(), ParamError> {
+    for (name, _) in overrides {
+        if !days.iter().any(|day| day.params().any(|p| p.name == name)) {
+            return Err(ParamError::Unknown(name.clone()));
+        }
+    }
+    for implementation in days.iter().flat_map(|day| day.implementations) {
+        Params::defaults(implementation.params).apply(overrides)?;
+    }
+    Ok(())
+}
+
+/// Check that each `--impl` names an implementation of one of `days`
+fn check_implementations(days: &[&Day], selections: &[String]) -> Result<(), String> {
+    for selection in selections {
+        let found = match selection.rsplit_once('/') {
+            Some((name, implementation)) => days
+                .iter()
+                .any(|day| day.name == name && day.implementation(implementation).is_some()),
+            None => days
+                .iter()
+                .any(|day| day.implementation(selection).is_some()),
+        };
+        if !found {
+            return Err(format!(
+                "no selected day has an implementation {:?}, see --list",
+                selection
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The implementation of `day` to run, preferring a `dNN/NAME` selection over a `NAME` one
+fn select_implementation(day: &Day, selections: &[String]) -> &'static Implementation {
+    let qualified = selections
+        .iter()
+        .filter_map(|s| s.rsplit_once('/'))
+        .filter(|&(name, _)| name == day.name)
+        .find_map(|(_, implementation)| day.implementation(implementation));
+    let unqualified = || {
+        selections
+            .iter()
+            .filter(|s| !s.contains('/'))
+            .find_map(|s| day.implementation(s))
+    };
+    qualified
+        .or_else(unqualified)
+        .unwrap_or_else(|| day.default_implementation())
+}
+
+/// Every day of each year with a registered day, with the missing ones marked
+fn list_days() -> String {
+    let mut out = String::new();
+    for (year, days) in &DAYS.iter().chunk_by(|day| day.year) {
+        let days: Vec<_> = days.collect();
+        writeln!(out, "{}:", year).unwrap();
+        for number in registry::FIRST_DAY..=registry::LAST_DAY {
+            match days.iter().find(|day| day.number == number) {
+                Some(day) => {
+                    write!(out, "  {} {}", day.name, day.title).unwrap();
+                    if day.implementations.len() > 1 {
+                        let names = day.implementations.iter().map(|i| i.name).join(", ");
+                        write!(out, " ({})", names).unwrap();
+                    }
+                    if !day.tags.is_empty() {
+                        write!(out, " [{}]", day.tags.join(", ")).unwrap();
+                    }
+                    writeln!(out)
+                }
+                None => writeln!(out, "  {} missing", day_name(year, number)),
+            }
+            .unwrap();
+            for param in days
+                .iter()
+                .filter(|day| day.number == number)
+                .flat_map(|day| day.params())
+            {
+                writeln!(
+                    out,
+                    "    --param {}={}  {}",
+                    param.name, param.default, param.help
+                )
+                .unwrap();
+            }
+        }
+    }
+    out
+}
+
+/// Print part of the report straight away. Errors are ignored, so that a closed pipe doesn't stop
+/// the run before answers can be recorded.
+fn emit(s: &str) {
+    let mut stdout = io::stdout().lock();
+    let _ = stdout.write_all(s.as_bytes()).and_then(|()| stdout.flush());
+}
+
+fn new_day(day: &str, title: &str, data_dir: &Path) -> ExitCode {
+    let (year, day) = match select::parse_day(day) {
+        Ok(day) => day,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let root = Path::new(env!("CARGO_MANIFEST_DIR"));
+    match scaffold::new_day(root, data_dir, year, day, title) {
+        Ok(created) => {
+            eprintln!("Created {}", created.module.display());
+            if let Some(input) = created.input {
+                eprintln!("Created {}", input.display());
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn fetch(day: &str, force: bool, args: &SiteArgs, data_dir: &Path) -> ExitCode {
+    let (year, day) = match select::parse_day(day) {
+        Ok(day) => day,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let client = site::Client::new(&args.base_url, &args.session);
+    let saved = client
+        .input(year, day)
+        .and_then(|input| site::save_input(data_dir, year, day, &input, force));
+    match saved {
+        Ok((path, stored)) => {
+            match stored {
+                site::Stored::Transcrypt => eprintln!(
+                    "Saved {}, transcrypt will encrypt it when it's committed",
+                    path.display()
+                ),
+                site::Stored::Encrypted => eprintln!("Saved {} encrypted", path.display()),
+                site::Stored::Plain => eprintln!(
+                    "Saved {} unencrypted, as there is no transcrypt or key, so don't commit it",
+                    path.display()
+                ),
+            }
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Solve one part of `day` with its real input
+fn solve_part(day: &'static Day, part: u8, data_dir: &Path) -> Result<Answer, String> {
+    let task = Task {
+        day,
+        implementation: day.default_implementation(),
+        example: None,
+        part: Some(part),
+    };
+    let config = runner::Config {
+        source: InputSource::DataDir(data_dir.to_path_buf()),
+        reps: Repetitions {
+            warmup: 0,
+            repeat: 1,
+        },
+        overrides: vec![],
+        timeout: None,
+        visualize: None,
+    };
+    let (mut results, _) = runner::run_tasks(vec![task], &config, 1, |_, _| {});
+    let (_, result) = results.pop().expect("one task was run");
+    let result = result.map_err(|e| e.to_string())?;
+    let timed = result
+        .parts
         .into_iter()
-        .for_each(|(name, part1, part2, duration1, duration2)| {
-            println!(
-                "{}: {}, {} ({}, {})",
-                name,
-                part1,
-                part2,
-                format_duration(duration1),
-                format_duration(duration2)
-            );
-        });
-    println!("Total time: {}", format_duration(total));
+        .find_map(|(p, timed)| (p == part).then_some(timed))
+        .expect("the part is run on real inputs");
+    timed.result.map_err(|e| e.to_string())
 }
 
-fn main() {
-    let args: Vec<_> = std::env::args()
-        .skip(1)
-        .map(|s| {
-            s.parse::<i32>()
-                .map_or_else(|_| s, |n| format!("d{:02}", n))
+fn submit(day: &str, part: u8, answer: Option<&str>, args: &SiteArgs, data_dir: &Path) -> ExitCode {
+    let (year, number) = match select::parse_day(day) {
+        Ok(day) => day,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let name = day_name(year, number);
+    // Numbers are recorded as numbers, like the answers the days give
+    let answer = match answer {
+        Some(answer) => answer
+            .parse::<i128>()
+            .map_or_else(|_| Answer::from(answer), Answer::from),
+        None => {
+            let Some(day) = find_day(year, number) else {
+                eprintln!(
+                    "{} has no registered solution, give the answer instead",
+                    name
+                );
+                return ExitCode::FAILURE;
+            };
+            match solve_part(day, part, data_dir) {
+                Ok(answer) => answer,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return ExitCode::FAILURE;
+                }
+            }
+        }
+    };
+
+    eprintln!("Submitting {} for {} part {}", answer, name, part);
+    let client = site::Client::new(&args.base_url, &args.session);
+    let outcome = match client.submit(year, number, part, &answer.to_string()) {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    println!("{}", outcome);
+    if outcome != site::Outcome::Correct {
+        return ExitCode::FAILURE;
+    }
+
+    let answers_path = data_dir.join(known_answers::FILENAME);
+    let recorded = KnownAnswers::load(&answers_path).and_then(|mut known| {
+        known.set(&name, part, answer);
+        known.save(&answers_path)
+    });
+    match recorded {
+        Ok(()) => {
+            eprintln!("Recorded the answer in {}", answers_path.display());
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn serve(listen: &str, unix: Option<&Path>, timeout: Duration) -> ExitCode {
+    let result = match unix {
+        #[cfg(unix)]
+        Some(path) => serve::bind_unix(path).and_then(|listener| {
+            eprintln!("Listening on {}", path.display());
+            let result = serve::serve(listener.incoming(), timeout);
+            let _ = std::fs::remove_file(path);
+            result
+        }),
+        #[cfg(not(unix))]
+        Some(_) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "Unix sockets aren't supported here",
+        )),
+        None => std::net::TcpListener::bind(listen).and_then(|listener| {
+            eprintln!("Listening on {}", listener.local_addr()?);
+            serve::serve(listener.incoming(), timeout)
+        }),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("serve: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    if let Some(command) = &args.command {
+        let data_dir = || args.data_dir.clone().unwrap_or_else(default_data_dir);
+        return match command {
+            Command::New { day, title } => new_day(day, title, &data_dir()),
+            Command::Serve {
+                listen,
+                unix,
+                timeout,
+            } => serve(listen, unix.as_deref(), *timeout),
+            Command::Fetch { day, force, site } => fetch(day, *force, site, &data_dir()),
+            Command::Submit {
+                day,
+                part,
+                answer,
+                site,
+            } => submit(day, *part, answer.as_deref(), site, &data_dir()),
+        };
+    }
+    if args.list {
+        print!("{}", list_days());
+        return ExitCode::SUCCESS;
+    }
+
+    let selected = match select::resolve(&args.days, &args.tags, &DAYS) {
+        Ok(selected) => selected,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let days: Vec<_> = selected.iter().map(|&(day, _)| day).collect();
+
+    if let Err(e) = check_overrides(&days, &args.params) {
+        eprintln!("--param: {}", e);
+        return ExitCode::FAILURE;
+    }
+    if let Err(e) = check_implementations(&days, &args.implementations) {
+        eprintln!("--impl: {}", e);
+        return ExitCode::FAILURE;
+    }
+
+    let baseline = match &args.baseline {
+        Some(name) => match baseline::load(&args.baseline_dir, name) {
+            Ok(report) => Some((name, report)),
+            Err(e) => {
+                eprintln!("--baseline: {}", e);
+                return ExitCode::FAILURE;
+            }
+        },
+        None => None,
+    };
+
+    // Check the name now rather than after the run
+    if let Some(Err(e)) = args
+        .save_baseline
+        .as_ref()
+        .map(|name| baseline::path(&args.baseline_dir, name))
+    {
+        eprintln!("--save-baseline: {}", e);
+        return ExitCode::FAILURE;
+    }
+
+    let explicit_data_dir = args.data_dir.is_some();
+    let data_dir = args.data_dir.unwrap_or_else(default_data_dir);
+    let answers_path = data_dir.join(known_answers::FILENAME);
+    let source = match args.input {
+        Some(path) if days.len() != 1 => {
+            eprintln!(
+                "--input {} needs exactly one day to be selected",
+                path.display()
+            );
+            return ExitCode::FAILURE;
+        }
+        Some(path) if path.as_os_str() == "-" => InputSource::Stdin,
+        Some(path) => InputSource::File(path),
+        None if cfg!(feature = "embed-inputs") && !explicit_data_dir => InputSource::Embedded,
+        None => InputSource::DataDir(data_dir),
+    };
+
+    // Embedded inputs are checked against the answers built in with them
+    let known = match (args.check, &source) {
+        (false, _) => None,
+        (true, InputSource::Embedded) => Some(KnownAnswers::load_embedded()),
+        (true, _) => Some(KnownAnswers::load(&answers_path)),
+    };
+    let known = match known.transpose() {
+        Ok(known) => known,
+        Err(e) => {
+            eprintln!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let tasks: Vec<_> = selected
+        .iter()
+        .flat_map(|&(day, part)| {
+            let examples: Vec<_> = match args.examples {
+                true => day
+                    .examples
+                    .iter()
+                    .enumerate()
+                    .map(|(i, example)| Some((i + 1, example)))
+                    .collect(),
+                false => vec![None],
+            };
+            let implementations = match args.cross_check {
+                true => day.implementations.iter().collect(),
+                false => vec![select_implementation(day, &args.implementations)],
+            };
+            examples.into_iter().cartesian_product(implementations).map(
+                move |(example, implementation)| Task {
+                    day,
+                    implementation,
+                    example,
+                    part,
+                },
+            )
         })
         .collect();
+    let jobs = match args.jobs {
+        0 => thread::available_parallelism().map_or(1, |n| n.get()),
+        n => n,
+    }
+    .clamp(1, tasks.len().max(1));
+    let config = runner::Config {
+        source,
+        reps: Repetitions {
+            warmup: args.warmup,
+            repeat: args.repeat,
+        },
+        overrides: args.params,
+        timeout: args.timeout,
+        visualize: args.visualize.then_some(runner::Visualize {
+            delay: args.frame_delay,
+            every: args.frame_every as usize,
+        }),
+    };
 
-    let days: Vec<_> = match args.len() {
-        0 => DAYS.iter().collect(),
-        _ => DAYS.iter().filter(|day| args.contains(&day.name)).collect(),
+    let format = args.format;
+    let solve = || {
+        solve(
+            &tasks,
+            &config,
+            jobs,
+            format,
+            known.as_ref(),
+            args.cross_check,
+        )
     };
+    if args.watch {
+        let paths = tasks
+            .iter()
+            .filter_map(|task| config.source.path(task.day.year, task.day.number));
+        let mut watcher = watch::Watcher::new(paths);
+        if watcher.len() == 0 {
+            eprintln!("--watch needs inputs read from files");
+            return ExitCode::FAILURE;
+        }
+
+        let mut previous: Option<Report> = None;
+        loop {
+            let (report, _) = solve();
+            emit(&report.footer(format));
+            if let Some(previous) = &previous {
+                eprint!("{}", watch::diff(&previous.parts, &report.parts));
+            }
+            previous = Some(report);
+            eprintln!(
+                "Watching {} input file(s) for changes, press Ctrl-C to stop",
+                watcher.len()
+            );
+            for path in watcher.wait(args.poll_interval) {
+                eprintln!("{} changed", path.display());
+            }
+        }
+    }
 
-    run_days(days);
+    let (mut report, results) = solve();
+    if let Some((name, baseline)) = &baseline {
+        let comparison =
+            baseline::Comparison::new(name, baseline, &report, args.regression_threshold);
+        // The table describes the comparison in its footer and JSON includes it, but CSV has no
+        // room for it
+        if format == Format::Csv {
+            eprint!("{}", comparison.describe());
+        }
+        report.baseline = Some(comparison);
+    }
+    emit(&report.footer(format));
+
+    if let Some(name) = &args.save_baseline {
+        match baseline::save(&args.baseline_dir, name, &report) {
+            Ok(path) => eprintln!("Saved baseline to {}", path.display()),
+            Err(e) => {
+                eprintln!("--save-baseline: {}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    if args.record {
+        match record(&answers_path, &results) {
+            Ok(()) => eprintln!("Recorded answers to {}", answers_path.display()),
+            Err(e) => {
+                eprintln!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+
+    // A parse error fails both parts as well, so only the parts are counted
+    let failures = report
+        .parts
+        .iter()
+        .filter(|p| {
+            p.part > 0 && matches!(p.status, Status::Fail | Status::Error | Status::Timeout)
+        })
+        .count();
+    if failures > 0 {
+        eprintln!("{} part(s) failed", failures);
+        return ExitCode::FAILURE;
+    }
+    if let Some(comparison) = &report.baseline {
+        let regressions = comparison.regressions();
+        if regressions > 0 {
+            eprintln!(
+                "{} part(s) regressed against baseline {}",
+                regressions, comparison.baseline
+            );
+            return ExitCode::FAILURE;
+        }
+    }
+    ExitCode::SUCCESS
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn part(implementation: &str, part: u8, answer: &str) -> PartReport {
+        PartReport {
+            day: "d05".to_string(),
+            example: None,
+            part,
+            implementation: implementation.to_string(),
+            status: Status::Ok,
+            answer: Some(answer.to_string()),
+            expected: None,
+            duration_ns: 0,
+            error: None,
+            timing: Timing::default(),
+            allocations: None,
+        }
+    }
+
+    #[test]
+    fn cross_check_flags_disagreements() {
+        let mut parts = vec![
+            part("comparator", 1, "143"),
+            part("comparator", 2, "123"),
+            part("toposort", 1, "143"),
+            part("toposort", 2, "124"),
+        ];
+        cross_check(&mut parts);
+        let statuses: Vec<_> = parts.iter().map(|p| p.status).collect();
+        assert_eq!(statuses, [Status::Ok, Status::Ok, Status::Ok, Status::Fail]);
+        assert_eq!(parts[3].expected.as_deref(), Some("123"));
+        assert_eq!(
+            parts[3].error.as_deref(),
+            Some("disagrees with d05/comparator")
+        );
+    }
+
+    #[test]
+    fn answers_are_only_for_the_default_params() {
+        for flag in ["--check", "--record"] {
+            let args = Args::try_parse_from(["aoc", flag, "--param", "part2_depth=40"]);
+            assert!(args.is_err(), "{}", flag);
+        }
+    }
+
+    #[test]
+    fn subcommands_take_the_data_dir() {
+        for args in [
+            &["aoc", "fetch", "5", "--data-dir", "X", "--session", "s"][..],
+            &[
+                "aoc",
+                "submit",
+                "5",
+                "1",
+                "--data-dir",
+                "X",
+                "--session",
+                "s",
+            ],
+            &["aoc", "new", "5", "--data-dir", "X"],
+        ] {
+            let args = Args::try_parse_from(args).unwrap();
+            assert!(args.command.is_some());
+            assert_eq!(args.data_dir, Some(PathBuf::from("X")));
+        }
+        // Flags for running days still can't be given with a subcommand
+        assert!(Args::try_parse_from(["aoc", "--check", "fetch", "5", "--session", "s"]).is_err());
+    }
+
+    #[test]
+    fn selects_implementations() {
+        let d05 = DAYS.iter().find(|day| day.name == "d05").unwrap();
+        let select = |selections: &[&str]| {
+            let selections: Vec<_> = selections.iter().map(|s| s.to_string()).collect();
+            select_implementation(d05, &selections).name
+        };
+        assert_eq!(select(&[]), "comparator");
+        assert_eq!(select(&["toposort"]), "toposort");
+        assert_eq!(select(&["comparator", "d05/toposort"]), "toposort");
+        assert_eq!(select(&["d01/toposort"]), "comparator");
+    }
 }
@@ -0,0 +1,469 @@
+//! Reporting the results of a run, either as a table for people or as JSON or CSV for scripts. The
+//! machine-readable formats have one row per part, plus the total, and their shape should only
+//! change by adding fields so that old runs can still be diffed against new ones. Tables and CSV
+//! are printed a day at a time as the days finish, and JSON all at once at the end.
+
+use std::{fmt::Write, time::Duration};
+
+use clap::ValueEnum;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    allocations::{format_bytes, Allocations},
+    baseline::Comparison,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Format {
+    Table,
+    Json,
+    Csv,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Status {
+    /// Solved, with nothing to check the answer against
+    Ok,
+    Pass,
+    Fail,
+    /// Solved in check mode, but no answer has been recorded
+    Unknown,
+    Error,
+    /// Took longer than the timeout
+    Timeout,
+    Skipped,
+}
+
+impl Status {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Status::Ok => "ok",
+            Status::Pass => "pass",
+            Status::Fail => "fail",
+            Status::Unknown => "unknown",
+            Status::Error => "error",
+            Status::Timeout => "timeout",
+            Status::Skipped => "skipped",
+        }
+    }
+}
+
+/// Summary statistics over repeated runs of the same thing, in nanoseconds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Timing {
+    pub samples: usize,
+    pub min_ns: u64,
+    pub median_ns: u64,
+    pub mean_ns: u64,
+    pub stddev_ns: u64,
+}
+
+impl Timing {
+    pub fn from_samples(samples: &[Duration]) -> Self {
+        if samples.is_empty() {
+            return Timing::default();
+        }
+
+        let mut ns: Vec<_> = samples.iter().map(|&d| nanos(d)).collect();
+        ns.sort_unstable();
+        let n = ns.len();
+        let median = match n % 2 {
+            0 => (ns[n / 2 - 1] + ns[n / 2]) / 2,
+            _ => ns[n / 2],
+        };
+        let mean = ns.iter().map(|&x| x as f64).sum::<f64>() / n as f64;
+        // Population standard deviation, so a single sample has a deviation of zero
+        let variance = ns.iter().map(|&x| (x as f64 - mean).powi(2)).sum::<f64>() / n as f64;
+
+        Timing {
+            samples: n,
+            min_ns: ns[0],
+            median_ns: median,
+            mean_ns: mean.round() as u64,
+            stddev_ns: variance.sqrt().round() as u64,
+        }
+    }
+
+    fn describe(&self) -> String {
+        format!(
+            "min {}, median {}, mean {}, stddev {} ({} runs)",
+            format_duration(Duration::from_nanos(self.min_ns)),
+            format_duration(Duration::from_nanos(self.median_ns)),
+            format_duration(Duration::from_nanos(self.mean_ns)),
+            format_duration(Duration::from_nanos(self.stddev_ns)),
+            self.samples
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PartReport {
+    pub day: String,
+    /// The example that was run, counting from 1, or `None` for the real input
+    pub example: Option<usize>,
+    /// The part, or 0 for parsing the input
+    pub part: u8,
+    pub implementation: String,
+    pub status: Status,
+    pub answer: Option<String>,
+    pub expected: Option<String>,
+    /// The median time when the part was run more than once
+    pub duration_ns: u64,
+    pub error: Option<String>,
+    pub timing: Timing,
+    /// What the last timed run allocated, with the `count-allocations` feature
+    pub allocations: Option<Allocations>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Report {
+    pub parts: Vec<PartReport>,
+    /// How many days were solved at once
+    pub jobs: usize,
+    /// Wall time for the whole run, including reading inputs and any repeats
+    pub total_duration_ns: u64,
+    /// Time spent solving, summed over every part and repeat on every thread
+    pub cpu_duration_ns: u64,
+    /// Statistics for the sum of every part's time in each repetition
+    pub total: Timing,
+    /// How the timings compare with a saved baseline, if one was given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<Comparison>,
+}
+
+pub fn nanos(d: Duration) -> u64 {
+    d.as_nanos().try_into().unwrap_or(u64::MAX)
+}
+
+pub fn format_duration(d: Duration) -> String {
+    let nanos = d.as_nanos();
+
+    if nanos < 1000 {
+        format!("{}ns", nanos)
+    } else if nanos < 1_000_000 {
+        format!("{:.3}µs", nanos as f64 / 1000.0)
+    } else if nanos < 1_000_000_000 {
+        format!("{:.3}ms", nanos as f64 / 1_000_000.0)
+    } else {
+        format!("{:.3}s", nanos as f64 / 1_000_000_000.0)
+    }
+}
+
+impl Format {
+    /// The start of a report, before any parts
+    pub fn header(self) -> &'static str {
+        match self {
+            Format::Csv => {
+                "day,part,implementation,status,answer,expected,duration_ns,error,\
+                 samples,min_ns,median_ns,mean_ns,stddev_ns,example,\
+                 allocations,allocated_bytes,peak_bytes\n"
+            }
+            Format::Table | Format::Json => "",
+        }
+    }
+
+    /// Some of a report's parts, which can be printed before the rest have run. Nothing for JSON,
+    /// which is only printed whole, by [`Report::footer`].
+    pub fn parts(self, parts: &[PartReport]) -> String {
+        match self {
+            Format::Table => table_parts(parts),
+            Format::Json => String::new(),
+            Format::Csv => csv_parts(parts),
+        }
+    }
+}
+
+impl Report {
+    /// The end of the report, after its parts
+    pub fn footer(&self, format: Format) -> String {
+        match format {
+            Format::Table => self.table_footer(),
+            Format::Json => serde_json::to_string_pretty(self).unwrap() + "\n",
+            Format::Csv => self.csv_footer(),
+        }
+    }
+
+    fn table_footer(&self) -> String {
+        let mut out = String::new();
+        writeln!(
+            out,
+            "Total time: {}",
+            format_duration(Duration::from_nanos(self.total_duration_ns))
+        )
+        .unwrap();
+        if self.total.samples > 1 {
+            writeln!(out, "  per run: {}", self.total.describe()).unwrap();
+        }
+        if self.jobs > 1 {
+            writeln!(
+                out,
+                "CPU time: {} ({} jobs)",
+                format_duration(Duration::from_nanos(self.cpu_duration_ns)),
+                self.jobs
+            )
+            .unwrap();
+        }
+        if let Some(comparison) = &self.baseline {
+            out += &comparison.describe();
+        }
+        out
+    }
+
+    fn csv_footer(&self) -> String {
+        let mut out = String::new();
+        writeln!(
+            out,
+            "total,,,,,,{},,{},,,,",
+            self.total_duration_ns,
+            csv_timing(&self.total)
+        )
+        .unwrap();
+        writeln!(out, "cpu,,,,,,{},,,,,,,,,,", self.cpu_duration_ns).unwrap();
+        out
+    }
+}
+
+fn table_parts(parts: &[PartReport]) -> String {
+    let mut out = String::new();
+    let groups = parts
+        .iter()
+        .chunk_by(|p| (&p.day, &p.implementation, p.example));
+    for ((day, implementation, example), parts) in &groups {
+        let parts: Vec<_> = parts.collect();
+        let day = match implementation.as_str() {
+            "default" => day.to_string(),
+            implementation => format!("{}/{}", day, implementation),
+        };
+        let day = match example {
+            Some(i) => format!("{} example {}", day, i),
+            None => day,
+        };
+        // Say which part it was if only one ran, e.g. from a selection like `17.2`
+        let day = match parts.iter().filter(|p| p.part > 0).collect::<Vec<_>>()[..] {
+            [p] => format!("{} {}", day, part_name(p.part)),
+            _ => day,
+        };
+        let error = parts.iter().find_map(|p| p.error.as_deref());
+
+        if parts.iter().all(|p| p.status == Status::Skipped) {
+            writeln!(out, "{}: skipped ({})", day, error.unwrap_or_default()).unwrap();
+        } else if parts.iter().all(|p| p.answer.is_none())
+            && parts.iter().map(|p| &p.error).all_equal()
+        {
+            // The input couldn't be read, so neither part ran
+            writeln!(
+                out,
+                "{}: FAILED\n  error: {}",
+                day,
+                error.unwrap_or_default()
+            )
+            .unwrap();
+        } else {
+            writeln!(
+                out,
+                "{}: {} ({})",
+                day,
+                parts
+                    .iter()
+                    .filter(|p| p.part > 0)
+                    .map(|p| table_answer(p))
+                    .join(", "),
+                parts.iter().map(|p| table_duration(p)).join(", ")
+            )
+            .unwrap();
+            // A parse error is repeated by both parts
+            for e in parts.iter().filter_map(|p| p.error.as_ref()).unique() {
+                writeln!(out, "  error: {}", e).unwrap();
+            }
+            for p in parts.iter().filter(|p| p.timing.samples > 1) {
+                writeln!(out, "  {}: {}", part_name(p.part), p.timing.describe()).unwrap();
+            }
+            for p in parts.iter() {
+                if let Some(a) = p.allocations {
+                    writeln!(
+                        out,
+                        "  {}: {} allocations, {} allocated, {} peak",
+                        part_name(p.part),
+                        a.count,
+                        format_bytes(a.bytes),
+                        format_bytes(a.peak_bytes)
+                    )
+                    .unwrap();
+                }
+            }
+        }
+    }
+    out
+}
+
+fn csv_parts(parts: &[PartReport]) -> String {
+    let mut out = String::new();
+    for p in parts {
+        writeln!(
+            out,
+            "{},{},{},{},{},{},{},{},{},{},{}",
+            csv_field(&p.day),
+            p.part,
+            csv_field(&p.implementation),
+            p.status.as_str(),
+            csv_field(p.answer.as_deref().unwrap_or_default()),
+            csv_field(p.expected.as_deref().unwrap_or_default()),
+            p.duration_ns,
+            csv_field(p.error.as_deref().unwrap_or_default()),
+            csv_timing(&p.timing),
+            p.example.map(|i| i.to_string()).unwrap_or_default(),
+            p.allocations
+                .map(|a| format!("{},{},{}", a.count, a.bytes, a.peak_bytes))
+                .unwrap_or_else(|| ",,".to_string()),
+        )
+        .unwrap();
+    }
+    out
+}
+
+/// A part's answer and how it was checked, or why there isn't one
+pub fn table_answer(p: &PartReport) -> String {
+    let answer = p.answer.as_deref().unwrap_or_default();
+    match p.status {
+        Status::Ok => answer.to_string(),
+        Status::Pass => format!("{} PASS", answer),
+        Status::Fail => format!(
+            "{} FAIL (expected {})",
+            answer,
+            p.expected.as_deref().unwrap_or_default()
+        ),
+        Status::Unknown => format!("{} UNKNOWN", answer),
+        Status::Error => "FAILED".to_string(),
+        Status::Timeout => "TIMEOUT".to_string(),
+        Status::Skipped => "SKIPPED".to_string(),
+    }
+}
+
+fn table_duration(p: &PartReport) -> String {
+    let duration = format_duration(Duration::from_nanos(p.duration_ns));
+    match p.part {
+        0 => format!("parse {}", duration),
+        _ => duration,
+    }
+}
+
+fn part_name(part: u8) -> String {
+    match part {
+        0 => "parse".to_string(),
+        part => format!("part {}", part),
+    }
+}
+
+fn csv_timing(t: &Timing) -> String {
+    format!(
+        "{},{},{},{},{}",
+        t.samples, t.min_ns, t.median_ns, t.mean_ns, t.stddev_ns
+    )
+}
+
+/// Quote a CSV field if it needs it. Several answers are comma separated lists.
+fn csv_field(s: &str) -> String {
+    if s.contains([',', '"', '\n']) {
+        format!("\"{}\"", s.replace('"', "\"\""))
+    } else {
+        s.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn part(day: &str, part: u8, status: Status, answer: Option<&str>) -> PartReport {
+        PartReport {
+            day: day.to_string(),
+            example: None,
+            part,
+            implementation: "default".to_string(),
+            status,
+            answer: answer.map(str::to_string),
+            expected: None,
+            duration_ns: 1500,
+            error: None,
+            timing: Timing::from_samples(&[Duration::from_nanos(1500)]),
+            allocations: None,
+        }
+    }
+
+    /// The whole report, as it's printed a part at a time
+    fn render(report: &Report, format: Format) -> String {
+        format.header().to_string() + &format.parts(&report.parts) + &report.footer(format)
+    }
+
+    fn report() -> Report {
+        Report {
+            parts: vec![
+                part("d01", 0, Status::Ok, None),
+                part("d01", 1, Status::Ok, Some("11")),
+                part("d01", 2, Status::Ok, Some("31")),
+                part("d23", 1, Status::Ok, Some("7")),
+                part("d23", 2, Status::Ok, Some("co,de,ka,ta")),
+            ],
+            jobs: 1,
+            total_duration_ns: 6000,
+            cpu_duration_ns: 6000,
+            total: Timing::from_samples(&[Duration::from_nanos(6000)]),
+            baseline: None,
+        }
+    }
+
+    #[test]
+    fn timing_statistics() {
+        let samples = [4, 2, 9, 5, 5].map(Duration::from_nanos);
+        let t = Timing::from_samples(&samples);
+        assert_eq!((t.samples, t.min_ns, t.median_ns, t.mean_ns), (5, 2, 5, 5));
+        assert_eq!(t.stddev_ns, 2);
+
+        let even = Timing::from_samples(&[10, 20].map(Duration::from_nanos));
+        assert_eq!(even.median_ns, 15);
+    }
+
+    #[test]
+    fn csv_quotes_lists() {
+        let csv = render(&report(), Format::Csv);
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 8);
+        assert_eq!(lines[1], "d01,0,default,ok,,,1500,,1,1500,1500,1500,0,,,,");
+        assert_eq!(
+            lines[5],
+            "d23,2,default,ok,\"co,de,ka,ta\",,1500,,1,1500,1500,1500,0,,,,"
+        );
+        assert_eq!(lines[6], "total,,,,,,6000,,1,6000,6000,6000,0,,,,");
+        assert_eq!(lines[7], "cpu,,,,,,6000,,,,,,,,,,");
+    }
+
+    #[test]
+    fn json_shape() {
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&report(), Format::Json)).unwrap();
+        assert_eq!(json["total_duration_ns"], 6000);
+        assert_eq!(json["parts"][0]["day"], "d01");
+        assert_eq!(json["parts"][0]["status"], "ok");
+        assert_eq!(json["parts"][1]["answer"], "11");
+        assert_eq!(json["parts"][4]["answer"], "co,de,ka,ta");
+    }
+
+    #[test]
+    fn table_groups_days() {
+        let table = render(&report(), Format::Table);
+        assert!(table.starts_with("d01: 11, 31 (parse 1.500µs, 1.500µs, 1.500µs)\n"));
+        assert!(table.ends_with("Total time: 6.000µs\n"));
+
+        // A single part is named, as it could be either
+        let parts = [
+            part("d17", 0, Status::Ok, None),
+            part("d17", 2, Status::Ok, Some("5")),
+        ];
+        assert_eq!(
+            Format::Table.parts(&parts),
+            "d17 part 2: 5 (parse 1.500µs, 1.500µs)\n"
+        );
+    }
+}
@@ -0,0 +1,616 @@
+//! Running tasks, each one day on its real input or on one of its examples, and timing them. With a
+//! timeout each task runs on a thread of its own, which is cancelled and left behind if a part takes
+//! too long, so that the remaining tasks can still run. A part that panics fails alone, without
+//! stopping the run.
+
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    fmt, iter,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc, Mutex, Once,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+use advent_of_code_2024::days::{
+    cancel,
+    registry::{Example, Implementation},
+    visualize::{self, Terminal},
+    Answer, Day, InputError, InputSource, Params, SolveError,
+};
+
+use crate::{
+    allocations::{self, Allocations},
+    report::format_duration,
+};
+
+/// How many times to run each part
+#[derive(Debug, Clone, Copy)]
+pub struct Repetitions {
+    pub warmup: u32,
+    pub repeat: u32,
+}
+
+/// How every task in a run is run
+#[derive(Debug, Clone)]
+pub struct Config {
+    pub source: InputSource,
+    pub reps: Repetitions,
+    /// Parameter overrides, as `(name, value)`, which have already been checked
+    pub overrides: Vec<(String, String)>,
+    /// How long parsing or each part may take, including all of its repetitions
+    pub timeout: Option<Duration>,
+    /// Draw the simulations in the terminal as they run
+    pub visualize: Option<Visualize>,
+}
+
+/// How simulations are drawn
+#[derive(Debug, Clone, Copy)]
+pub struct Visualize {
+    /// The pause after each frame
+    pub delay: Duration,
+    /// Only every this many frames are drawn
+    pub every: usize,
+}
+
+pub fn time_execution<F, T>(f: F) -> (T, std::time::Duration)
+where
+    F: FnOnce() -> T,
+{
+    let start = Instant::now();
+    let result = f();
+    let duration = start.elapsed();
+    (result, duration)
+}
+
+/// Parse a duration such as `500ms`, `10s` or `2m`. A number without a unit is in seconds.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let split = s.find(|c: char| !c.is_ascii_digit() && c != '.');
+    let (number, unit) = s.split_at(split.unwrap_or(s.len()));
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration {:?}", s))?;
+    let seconds = match unit {
+        "ms" => number / 1000.0,
+        "" | "s" => number,
+        "m" => number * 60.0,
+        _ => return Err(format!("unknown unit {:?}, expected ms, s or m", unit)),
+    };
+    Duration::try_from_secs_f64(seconds).map_err(|e| e.to_string())
+}
+
+/// One run of a day, on its real input or on one of its examples
+#[derive(Clone, Copy)]
+pub struct Task {
+    pub day: &'static Day,
+    pub implementation: &'static Implementation,
+    /// The example's number, counting from 1, and the example
+    pub example: Option<(usize, &'static Example)>,
+    /// Only this part, rather than both
+    pub part: Option<u8>,
+}
+
+impl Task {
+    pub fn parts(&self) -> Vec<u8> {
+        (1..=2)
+            .filter(|&p| self.part.is_none_or(|part| part == p))
+            // Only the parts the example has an answer for
+            .filter(|&p| {
+                self.example
+                    .is_none_or(|(_, example)| example.answer(p).is_some())
+            })
+            .collect()
+    }
+}
+
+/// Why parsing or a part has no result
+#[derive(Debug, Clone)]
+pub enum Failure {
+    Error(SolveError),
+    /// Took longer than this, and was cancelled
+    Timeout(Duration),
+    Panic(Panic),
+    /// Not run, since something before it in the same task didn't finish
+    NotRun,
+}
+
+/// A panic caught while running something
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Panic {
+    pub message: String,
+    /// Where it panicked, as `file:line:column`
+    pub location: Option<String>,
+}
+
+impl fmt::Display for Panic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.location {
+            Some(location) => write!(f, "panicked at {}: {}", location, self.message),
+            None => write!(f, "panicked: {}", self.message),
+        }
+    }
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Failure::Error(e) => write!(f, "{}", e),
+            Failure::Timeout(timeout) => write!(f, "timed out after {}", format_duration(*timeout)),
+            Failure::Panic(panic) => write!(f, "{}", panic),
+            Failure::NotRun => write!(f, "not run, since an earlier step didn't finish"),
+        }
+    }
+}
+
+/// The result of running something, with the time taken by each timed run
+pub struct Timed<T> {
+    pub result: Result<T, Failure>,
+    pub samples: Vec<Duration>,
+    /// What the last timed run allocated, when allocations are counted
+    pub allocations: Option<Allocations>,
+}
+
+impl<T> Timed<T> {
+    fn failed(failure: Failure) -> Self {
+        Timed {
+            result: Err(failure),
+            samples: vec![],
+            allocations: None,
+        }
+    }
+}
+
+pub struct DayResult {
+    pub parse: Timed<()>,
+    pub parts: Vec<(u8, Timed<Answer>)>,
+}
+
+impl DayResult {
+    /// The samples for parsing and then each part
+    pub fn samples(&self) -> impl Iterator<Item = &Vec<Duration>> {
+        iter::once(&self.parse.samples).chain(self.parts.iter().map(|(_, p)| &p.samples))
+    }
+
+    /// Gather the steps a task finished. The first one missing timed out if `timeout` is given,
+    /// and the rest weren't run.
+    fn from_steps(task: Task, steps: Vec<Step>, timeout: Option<Duration>) -> Self {
+        let mut missing = iter::once(timeout.map_or(Failure::NotRun, Failure::Timeout))
+            .chain(iter::repeat(Failure::NotRun));
+        let mut steps = steps.into_iter().peekable();
+
+        let parse = match steps.next_if(|s| matches!(s, Step::Parsed(_))) {
+            Some(Step::Parsed(parse)) => parse,
+            _ => Timed::failed(missing.next().unwrap()),
+        };
+        let parts = task
+            .parts()
+            .into_iter()
+            .map(
+                |part| match steps.next_if(|s| matches!(s, Step::Part(p, _) if *p == part)) {
+                    Some(Step::Part(_, timed)) => (part, timed),
+                    _ => (part, Timed::failed(missing.next().unwrap())),
+                },
+            )
+            .collect();
+
+        DayResult { parse, parts }
+    }
+}
+
+/// The outcome of each task, which is an input error if the day couldn't be run at all
+pub type RunResults = Vec<(Task, Result<DayResult, InputError>)>;
+
+/// Something a task has finished, in order
+enum Step {
+    Parsed(Timed<()>),
+    Part(u8, Timed<Answer>),
+}
+
+thread_local! {
+    /// Whether a panic on this thread will be caught by [`catch`]
+    static CATCHING: Cell<bool> = const { Cell::new(false) };
+    /// Where the last caught panic on this thread happened
+    static PANIC_LOCATION: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Keep the location of panics that [`catch`] will catch, instead of printing them in the middle of
+/// the results. Other panics are printed as usual.
+fn install_panic_hook() {
+    static HOOK: Once = Once::new();
+    HOOK.call_once(|| {
+        let default = panic::take_hook();
+        panic::set_hook(Box::new(move |info| match CATCHING.get() {
+            true => PANIC_LOCATION.set(info.location().map(|l| l.to_string())),
+            false => default(info),
+        }));
+    });
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    match payload.downcast_ref::<&str>() {
+        Some(s) => s.to_string(),
+        None => payload
+            .downcast_ref::<String>()
+            .cloned()
+            .unwrap_or_else(|| "unknown panic payload".to_string()),
+    }
+}
+
+/// Call `f`, catching a panic as a failure
+pub fn catch<T>(f: impl FnOnce() -> Result<T, SolveError>) -> Result<T, Failure> {
+    install_panic_hook();
+    let was_catching = CATCHING.replace(true);
+    let result = panic::catch_unwind(AssertUnwindSafe(f));
+    CATCHING.set(was_catching);
+
+    match result {
+        Ok(result) => result.map_err(Failure::Error),
+        Err(payload) => Err(Failure::Panic(Panic {
+            message: panic_message(&*payload),
+            location: PANIC_LOCATION.take(),
+        })),
+    }
+}
+
+/// Run a part `reps.warmup` times untimed and then `reps.repeat` times timed, returning the last
+/// result. A part that fails isn't run again.
+fn time_part<T>(f: impl Fn() -> Result<T, SolveError>, reps: Repetitions) -> Timed<T> {
+    for _ in 0..reps.warmup {
+        if catch(&f).is_err() {
+            break;
+        }
+    }
+
+    let mut samples = Vec::with_capacity(reps.repeat as usize);
+    loop {
+        let ((result, duration), allocations) =
+            allocations::measure(|| time_execution(|| catch(&f)));
+        samples.push(duration);
+        if result.is_err() || samples.len() >= reps.repeat as usize {
+            return Timed {
+                result,
+                samples,
+                allocations,
+            };
+        }
+    }
+}
+
+/// Run `task`, passing each step to `finished` as soon as it's done
+fn run_steps(
+    task: Task,
+    config: &Config,
+    mut finished: impl FnMut(Step),
+) -> Result<(), InputError> {
+    let day = task.day;
+    let implementation = task.implementation;
+    if let Some(v) = config.visualize {
+        visualize::install(Box::new(Terminal::stderr(v.delay, v.every)));
+    }
+    let (input, mut params) = match task.example {
+        Some((_, example)) => (
+            example.input.trim().to_string(),
+            implementation
+                .example_params(example)
+                .expect("example parameters are checked by the registry tests"),
+        ),
+        None => (
+            day.load_input(&config.source)?,
+            Params::defaults(implementation.params),
+        ),
+    };
+    params
+        .apply(&config.overrides)
+        .expect("overrides are checked before running");
+
+    let parsed = time_part(|| day.parse(implementation, &input), config.reps);
+    finished(Step::Parsed(Timed {
+        result: parsed.result.as_ref().map(|_| ()).map_err(Failure::clone),
+        samples: parsed.samples,
+        allocations: parsed.allocations,
+    }));
+
+    for part in task.parts() {
+        let timed = match &parsed.result {
+            Ok(parsed) => time_part(
+                || match part {
+                    1 => day.part1(&**parsed, &params),
+                    _ => day.part2(&**parsed, &params),
+                },
+                config.reps,
+            ),
+            // The parts can't run without a parsed input
+            Err(e) => Timed::failed(e.clone()),
+        };
+        finished(Step::Part(part, timed));
+    }
+    Ok(())
+}
+
+fn run_task(task: Task, config: &Config) -> Result<DayResult, InputError> {
+    match config.timeout {
+        None => {
+            let mut steps = Vec::new();
+            run_steps(task, config, |step| steps.push(step))?;
+            Ok(DayResult::from_steps(task, steps, None))
+        }
+        Some(timeout) => run_task_with_timeout(task, config, timeout, &Abandoned::default()),
+    }
+}
+
+/// Counts the threads that [`run_task_with_timeout`] gave up on and that haven't finished yet. A
+/// part that never checks for cancellation keeps its thread busy until it's done.
+#[derive(Debug, Clone, Default)]
+pub struct Abandoned(Arc<AtomicUsize>);
+
+impl Abandoned {
+    pub fn count(&self) -> usize {
+        self.0.load(Ordering::SeqCst)
+    }
+}
+
+/// Run `task` on a thread of its own, waiting up to `timeout` for each step. If a step takes longer
+/// the thread is cancelled and left to finish on its own, counted in `abandoned` until it does.
+pub fn run_task_with_timeout(
+    task: Task,
+    config: &Config,
+    timeout: Duration,
+    abandoned: &Abandoned,
+) -> Result<DayResult, InputError> {
+    let (sender, receiver) = mpsc::channel();
+    let token = cancel::Token::new();
+    // Whether the thread has finished, locked while either side decides whether it's abandoned
+    let finished = Arc::new(Mutex::new(false));
+    {
+        let config = config.clone();
+        let (token, finished, abandoned) = (token.clone(), finished.clone(), abandoned.clone());
+        thread::spawn(move || {
+            cancel::install(token.clone());
+            let result = run_steps(task, &config, |step| {
+                // The runner may have given up on the task already
+                let _ = sender.send(Ok(step));
+            });
+            if let Err(e) = result {
+                let _ = sender.send(Err(e));
+            }
+
+            let mut finished = finished.lock().unwrap();
+            *finished = true;
+            if token.is_cancelled() {
+                abandoned.0.fetch_sub(1, Ordering::SeqCst);
+            }
+        });
+    }
+
+    let mut steps = Vec::new();
+    loop {
+        match receiver.recv_timeout(timeout) {
+            Ok(Ok(step)) => steps.push(step),
+            Ok(Err(e)) => return Err(e),
+            Err(RecvTimeoutError::Disconnected) => {
+                return Ok(DayResult::from_steps(task, steps, None))
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                let finished = finished.lock().unwrap();
+                if !*finished {
+                    token.cancel();
+                    abandoned.0.fetch_add(1, Ordering::SeqCst);
+                }
+                return Ok(DayResult::from_steps(task, steps, Some(timeout)));
+            }
+        }
+    }
+}
+
+/// Run every task on `jobs` threads, returning the results in the same order as `tasks`. Each
+/// result is also passed to `finished` as soon as it and those of every earlier task are done.
+pub fn run_tasks(
+    tasks: Vec<Task>,
+    config: &Config,
+    jobs: usize,
+    mut finished: impl FnMut(Task, &Result<DayResult, InputError>),
+) -> (RunResults, Duration) {
+    time_execution(|| {
+        if jobs == 1 {
+            return tasks
+                .into_iter()
+                .map(|task| {
+                    let result = run_task(task, config);
+                    finished(task, &result);
+                    (task, result)
+                })
+                .collect();
+        }
+
+        let next = AtomicUsize::new(0);
+        let (sender, receiver) = mpsc::channel();
+        thread::scope(|scope| {
+            for _ in 0..jobs {
+                let sender = sender.clone();
+                let (tasks, next) = (&tasks, &next);
+                scope.spawn(move || loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(&task) = tasks.get(i) else {
+                        break;
+                    };
+                    // The receiver outlives the workers
+                    sender.send((i, task, run_task(task, config))).unwrap();
+                });
+            }
+            drop(sender);
+
+            // Hold back results that finish early, so that they're passed on in order
+            let mut results = Vec::with_capacity(tasks.len());
+            let mut waiting = BTreeMap::new();
+            for (i, task, result) in receiver {
+                waiting.insert(i, (task, result));
+                while let Some((task, result)) = waiting.remove(&results.len()) {
+                    finished(task, &result);
+                    results.push((task, result));
+                }
+            }
+            results
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use advent_of_code_2024::days::{registry, ParseError, Solution, DAYS};
+
+    #[test]
+    fn durations() {
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("2"), Ok(Duration::from_secs(2)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert!(parse_duration("2h").is_err());
+        assert!(parse_duration("s").is_err());
+    }
+
+    /// Run d17 on `input`, which is written to a file of its own
+    fn run_d17(input: &str, timeout: Option<Duration>) -> DayResult {
+        let d17 = DAYS.iter().find(|day| day.name == "d17").unwrap();
+        run_day(d17, input, timeout)
+    }
+
+    fn run_day(day: &'static Day, input: &str, timeout: Option<Duration>) -> DayResult {
+        let path = std::env::temp_dir().join(format!(
+            "runner-{}-{:?}.txt",
+            std::process::id(),
+            thread::current().id()
+        ));
+        std::fs::write(&path, input).unwrap();
+
+        let task = Task {
+            day,
+            implementation: day.default_implementation(),
+            example: None,
+            part: None,
+        };
+        let config = Config {
+            source: InputSource::File(path.clone()),
+            reps: Repetitions {
+                warmup: 0,
+                repeat: 1,
+            },
+            overrides: vec![],
+            visualize: None,
+            timeout,
+        };
+        let result = run_task(task, &config).unwrap();
+        std::fs::remove_file(path).unwrap();
+        result
+    }
+
+    /// A day whose parts always panic, as none of the registered ones should
+    struct Panics;
+
+    impl Solution for Panics {
+        type Parsed<'a> = ();
+
+        fn parse(_: &str) -> Result<(), ParseError> {
+            Ok(())
+        }
+
+        fn part1(_: &(), _: &Params) -> Result<Answer, ParseError> {
+            panic!("part 1 panicked")
+        }
+
+        fn part2(_: &(), _: &Params) -> Result<Answer, ParseError> {
+            panic!("part 2 panicked")
+        }
+    }
+
+    #[test]
+    fn panics_fail_the_part() {
+        static IMPLEMENTATIONS: [Implementation; 1] = [Implementation::of::<Panics>("default")];
+        let day = Box::leak(Box::new(Day {
+            name: "d00".to_string(),
+            year: registry::DEFAULT_YEAR,
+            number: 0,
+            title: "Panics",
+            tags: &[],
+            implementations: &IMPLEMENTATIONS,
+            examples: &[],
+        }));
+        for timeout in [None, Some(Duration::from_secs(10))] {
+            let result = run_day(day, "", timeout);
+            assert!(result.parse.result.is_ok());
+            for (part, result) in &result.parts {
+                let Err(Failure::Panic(panic)) = &result.result else {
+                    panic!("expected a panic");
+                };
+                assert_eq!(panic.message, format!("part {} panicked", part));
+                assert!(panic.location.as_ref().unwrap().contains("runner.rs"));
+            }
+        }
+    }
+
+    #[test]
+    fn parts_that_never_finish_time_out() {
+        // A program that jumps back to its start forever
+        let input = "Register A: 1\nRegister B: 0\nRegister C: 0\n\nProgram: 3,0\n";
+        let result = run_d17(input, Some(Duration::from_millis(100)));
+
+        assert!(result.parse.result.is_ok());
+        assert!(matches!(result.parts[0].1.result, Err(Failure::Timeout(_))));
+        assert!(matches!(result.parts[1].1.result, Err(Failure::NotRun)));
+    }
+
+    #[test]
+    fn failing_days_dont_stop_the_rest() {
+        // Both of these used to overflow the stack, which no panic handler can recover from
+        let inputs = [
+            ("d23", "ab-ab\n"),
+            (
+                "d24",
+                "x00: 1\n\nx00 AND b -> a\na OR x00 -> b\na XOR b -> z00\n",
+            ),
+            ("d22", "1\n10\n100\n2024\n"),
+        ];
+        let dir = std::env::temp_dir().join(format!("runner-days-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let tasks = inputs
+            .iter()
+            .map(|&(name, input)| {
+                std::fs::write(dir.join(format!("{}.txt", name)), input).unwrap();
+                let day = DAYS.iter().find(|day| day.name == name).unwrap();
+                Task {
+                    day,
+                    implementation: day.default_implementation(),
+                    example: None,
+                    part: Some(1),
+                }
+            })
+            .collect();
+        let config = Config {
+            source: InputSource::DataDir(dir.clone()),
+            reps: Repetitions {
+                warmup: 0,
+                repeat: 1,
+            },
+            overrides: vec![],
+            visualize: None,
+            timeout: None,
+        };
+
+        let (results, _) = run_tasks(tasks, &config, 1, |_, _| {});
+        std::fs::remove_dir_all(dir).unwrap();
+
+        let results: Vec<_> = results.into_iter().map(|(_, r)| r.unwrap()).collect();
+        assert!(matches!(results[0].parse.result, Err(Failure::Error(_))));
+        assert!(matches!(
+            results[1].parts[0].1.result,
+            Err(Failure::Error(_))
+        ));
+        assert!(matches!(&results[2].parts[0].1.result, Ok(answer) if *answer == 37327623));
+    }
+}
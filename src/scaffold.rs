@@ -0,0 +1,216 @@
+//! The `new` subcommand, which starts a day from `src/days/template.rs`. The new module registers
+//! itself, so the only other change is declaring it in `src/days.rs`. Days of years other than
+//! [`registry::DEFAULT_YEAR`] go in a module for their year instead, e.g. `src/days/y2023/d05.rs`
+//! declared in `src/days/y2023.rs`, which is created along with its first day.
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use advent_of_code_2024::days::{input, registry};
+
+const TEMPLATE: &str = include_str!("days/template.rs");
+
+#[derive(Debug)]
+pub enum ScaffoldError {
+    /// The day's module already exists, and is never overwritten
+    Exists(PathBuf),
+    Io(PathBuf, io::Error),
+}
+
+impl fmt::Display for ScaffoldError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScaffoldError::Exists(path) => write!(f, "{} already exists", path.display()),
+            ScaffoldError::Io(path, e) => write!(f, "failed to write {}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for ScaffoldError {}
+
+/// The files written for a new day
+#[derive(Debug)]
+pub struct Created {
+    pub module: PathBuf,
+    /// The empty input file, unless there was already one
+    pub input: Option<PathBuf>,
+}
+
+/// The header of a new year's module. Its days use the same names through `super` as the days of
+/// the default year do.
+const YEAR_MODULE: &str = "//! The days of {year}
+
+#[allow(unused_imports)]
+use super::{register, registry, utils, Answer, ParseError, Params, Solution};
+
+";
+
+/// Create the module for day `day` of `year` under `root` from the template, declare it, and create
+/// an empty input file in `data_dir` if there isn't one
+pub fn new_day(
+    root: &Path,
+    data_dir: &Path,
+    year: u16,
+    day: u8,
+    title: &str,
+) -> Result<Created, ScaffoldError> {
+    let name = format!("d{:02}", day);
+    let days_rs = root.join("src/days.rs");
+    let (module, parent_rs) = match year {
+        registry::DEFAULT_YEAR => (
+            root.join("src/days").join(format!("{}.rs", name)),
+            days_rs.clone(),
+        ),
+        year => (
+            root.join(format!("src/days/y{}/{}.rs", year, name)),
+            root.join(format!("src/days/y{}.rs", year)),
+        ),
+    };
+
+    let declarations = match fs::read_to_string(&parent_rs) {
+        Ok(declarations) => declarations,
+        Err(e) if e.kind() == io::ErrorKind::NotFound && parent_rs != days_rs => {
+            YEAR_MODULE.replace("{year}", &year.to_string())
+        }
+        Err(e) => return Err(ScaffoldError::Io(parent_rs, e)),
+    };
+    let declarations =
+        declare_module(&declarations, &name).ok_or(ScaffoldError::Exists(module.clone()))?;
+    // A new year's module needs declaring itself
+    let year_declaration = match parent_rs != days_rs && !parent_rs.exists() {
+        true => {
+            let days =
+                fs::read_to_string(&days_rs).map_err(|e| ScaffoldError::Io(days_rs.clone(), e))?;
+            declare_module(&days, &format!("y{}", year))
+        }
+        false => None,
+    };
+
+    if let Some(dir) = module.parent() {
+        fs::create_dir_all(dir).map_err(|e| ScaffoldError::Io(dir.to_path_buf(), e))?;
+    }
+
+    fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&module)
+        .and_then(|mut f| io::Write::write_all(&mut f, render(year, day, title).as_bytes()))
+        .map_err(|e| match e.kind() {
+            io::ErrorKind::AlreadyExists => ScaffoldError::Exists(module.clone()),
+            _ => ScaffoldError::Io(module.clone(), e),
+        })?;
+    fs::write(&parent_rs, declarations).map_err(|e| ScaffoldError::Io(parent_rs, e))?;
+    if let Some(year_declaration) = year_declaration {
+        fs::write(&days_rs, year_declaration).map_err(|e| ScaffoldError::Io(days_rs, e))?;
+    }
+
+    let input = data_dir.join(input::relative_path(year, day));
+    if let Some(dir) = input.parent() {
+        fs::create_dir_all(dir).map_err(|e| ScaffoldError::Io(dir.to_path_buf(), e))?;
+    }
+    let input = match fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&input)
+    {
+        Ok(_) => Some(input),
+        Err(e) if e.kind() == io::ErrorKind::AlreadyExists => None,
+        Err(e) => return Err(ScaffoldError::Io(input, e)),
+    };
+
+    Ok(Created { module, input })
+}
+
+/// The template, filled in for day `day` of `year`
+fn render(year: u16, day: u8, title: &str) -> String {
+    TEMPLATE
+        .replacen(
+            &format!("    year: {},\n", registry::DEFAULT_YEAR),
+            &format!("    year: {},\n", year),
+            1,
+        )
+        .replacen("    day: 0,\n", &format!("    day: {},\n", day), 1)
+        .replacen(
+            "    title: \"\",\n",
+            &format!("    title: {:?},\n", title),
+            1,
+        )
+}
+
+/// Add `pub mod <name>;` to `days.rs`, or a year's module, among the other days or years, keeping
+/// them sorted. Returns `None` if the module is already declared.
+fn declare_module(days_rs: &str, name: &str) -> Option<String> {
+    let declaration = format!("pub mod {};", name);
+    // `d` for days or `y` for years
+    let prefix = format!("pub mod {}", &name[..1]);
+    let is_day = |line: &str| {
+        line.strip_prefix(prefix.as_str())
+            .and_then(|rest| rest.strip_suffix(';'))
+            .is_some_and(|n| n.chars().all(|c| c.is_ascii_digit()))
+    };
+
+    let mut lines: Vec<&str> = days_rs.lines().collect();
+    if lines.contains(&declaration.as_str()) {
+        return None;
+    }
+
+    // Insert before the first later day, or else after the last day, or the last module if this is
+    // the first year
+    let position = lines
+        .iter()
+        .position(|&line| is_day(line) && line > declaration.as_str())
+        .or_else(|| lines.iter().rposition(|&line| is_day(line)).map(|i| i + 1))
+        .or_else(|| {
+            lines
+                .iter()
+                .rposition(|line| line.starts_with("pub mod "))
+                .map(|i| i + 1)
+        })
+        .unwrap_or(lines.len());
+    lines.insert(position, &declaration);
+
+    Some(lines.join("\n") + "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_template() {
+        let rendered = render(2024, 12, "Garden Groups");
+        assert!(rendered.contains("    year: 2024,\n    day: 12,\n    title: \"Garden Groups\",\n"));
+        assert!(render(2023, 1, "").contains("    year: 2023,\n    day: 1,\n"));
+        // The example is registered, so it can't only be defined for the tests
+        let (before_tests, _) = rendered.split_once("#[cfg(test)]").unwrap();
+        assert!(before_tests.contains("const EXAMPLE: &str"));
+        assert!(before_tests
+            .contains("    examples: [Example::new(EXAMPLE).part1(\"-\").part2(\"-\")],\n"));
+    }
+
+    #[test]
+    fn declares_in_order() {
+        let days_rs = "pub mod utils;\n\npub mod d11;\npub mod d13;\n\npub static DAYS: X;\n";
+        assert_eq!(
+            declare_module(days_rs, "d12").unwrap(),
+            "pub mod utils;\n\npub mod d11;\npub mod d12;\npub mod d13;\n\npub static DAYS: X;\n"
+        );
+        assert_eq!(
+            declare_module(days_rs, "d14").unwrap(),
+            "pub mod utils;\n\npub mod d11;\npub mod d13;\npub mod d14;\n\npub static DAYS: X;\n"
+        );
+        assert_eq!(declare_module(days_rs, "d13"), None);
+
+        let with_year = declare_module(days_rs, "y2023").unwrap();
+        assert_eq!(
+            with_year,
+            "pub mod utils;\n\npub mod d11;\npub mod d13;\npub mod y2023;\n\npub static DAYS: X;\n"
+        );
+        assert_eq!(
+            declare_module(&with_year, "y2022").unwrap(),
+            "pub mod utils;\n\npub mod d11;\npub mod d13;\npub mod y2022;\npub mod y2023;\n\npub static DAYS: X;\n"
+        );
+    }
+}
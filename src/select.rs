@@ -0,0 +1,315 @@
+//! Which days, and which of their parts, to run, from the selections given on the command line.
+//! Days are from [`registry::DEFAULT_YEAR`] unless they have a year in front of them:
+//!
+//! ```text
+//! 5, d05    day 5
+//! 2023/5    day 5 of 2023
+//! 2023/*    every day of 2023
+//! 1-10      days 1 to 10
+//! 17.2      only part 2 of day 17
+//! !d14      leave out day 14, from every day if nothing else is selected
+//! ```
+
+use std::fmt;
+
+use advent_of_code_2024::days::{day_name, registry, Day};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    pub year: u16,
+    /// The first and last day, or every day of the year if `None`
+    pub days: Option<(u8, u8)>,
+    /// Only this part, rather than both
+    pub part: Option<u8>,
+    /// Leave these days out instead
+    pub exclude: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SelectError {
+    Invalid(String),
+    /// No registered day is selected, which is most likely a typo
+    Unregistered(Selection),
+    /// No registered day has the tag
+    UnknownTag(String),
+}
+
+impl fmt::Display for SelectError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SelectError::Invalid(s) => write!(
+                f,
+                "invalid day {:?}, expected a number from {} to {} or a name like `d05`, a range \
+                 like `1-10` or `YEAR/*`, optionally after a year as in `2023/5`, with a part as \
+                 in `17.2` or `!` in front to leave it out",
+                s,
+                registry::FIRST_DAY,
+                registry::LAST_DAY
+            ),
+            SelectError::Unregistered(selection) if selection.is_single_day() => {
+                write!(f, "{} has no registered solution, see --list", selection)
+            }
+            SelectError::Unregistered(selection) => {
+                write!(f, "{} has no registered days, see --list", selection)
+            }
+            SelectError::UnknownTag(tag) => write!(f, "no day is tagged {:?}, see --list", tag),
+        }
+    }
+}
+
+impl std::error::Error for SelectError {}
+
+impl fmt::Display for Selection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.exclude {
+            write!(f, "!")?;
+        }
+        match self.days {
+            None => write!(f, "{}/*", self.year)?,
+            Some((first, last)) if first == last => write!(f, "{}", day_name(self.year, first))?,
+            Some((first, last)) => write!(f, "{}-d{:02}", day_name(self.year, first), last)?,
+        }
+        match self.part {
+            Some(part) => write!(f, ".{}", part),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Selection {
+    pub fn parse(s: &str) -> Result<Self, SelectError> {
+        let invalid = || SelectError::Invalid(s.to_string());
+        let (exclude, rest) = match s.strip_prefix('!') {
+            Some(rest) => (true, rest),
+            None => (false, s),
+        };
+        let (year, rest) = match rest.split_once('/') {
+            Some((year, rest)) => (Some(parse_year(year).ok_or_else(invalid)?), rest),
+            None => (None, rest),
+        };
+        let (rest, part) = match rest.rsplit_once('.') {
+            Some((rest, "1")) => (rest, Some(1)),
+            Some((rest, "2")) => (rest, Some(2)),
+            Some(_) => return Err(invalid()),
+            None => (rest, None),
+        };
+        let days = match (rest, year) {
+            ("*", Some(_)) => None,
+            _ => {
+                let (first, last) = match rest.split_once('-') {
+                    Some((first, last)) => (parse_number(first), parse_number(last)),
+                    None => (parse_number(rest), parse_number(rest)),
+                };
+                match (first, last) {
+                    (Some(first), Some(last)) if first <= last => Some((first, last)),
+                    _ => return Err(invalid()),
+                }
+            }
+        };
+        Ok(Selection {
+            year: year.unwrap_or(registry::DEFAULT_YEAR),
+            days,
+            part,
+            exclude,
+        })
+    }
+
+    fn is_single_day(&self) -> bool {
+        matches!(self.days, Some((first, last)) if first == last)
+    }
+
+    /// Whether `day` is one of the selected days, whichever parts are selected
+    pub fn covers(&self, day: &Day) -> bool {
+        day.year == self.year
+            && self
+                .days
+                .is_none_or(|(first, last)| (first..=last).contains(&day.number))
+    }
+
+    pub fn matches(&self, day: &Day, part: u8) -> bool {
+        self.covers(day) && self.part.is_none_or(|p| p == part)
+    }
+}
+
+fn parse_year(s: &str) -> Option<u16> {
+    match s.len() == 4 && s.chars().all(|c| c.is_ascii_digit()) {
+        true => s.parse().ok(),
+        false => None,
+    }
+}
+
+/// A day given as a number or name, e.g. `5` or `d05`
+fn parse_number(s: &str) -> Option<u8> {
+    s.strip_prefix('d')
+        .unwrap_or(s)
+        .parse()
+        .ok()
+        .filter(|number| (registry::FIRST_DAY..=registry::LAST_DAY).contains(number))
+}
+
+/// Parse a single day, as the year and number, for the subcommands that work on one day
+pub fn parse_day(s: &str) -> Result<(u16, u8), SelectError> {
+    match Selection::parse(s)? {
+        Selection {
+            year,
+            days: Some((first, last)),
+            part: None,
+            exclude: false,
+        } if first == last => Ok((year, first)),
+        _ => Err(SelectError::Invalid(s.to_string())),
+    }
+}
+
+/// The days of `days` that are selected, each with the one part to run if only one of them is. With
+/// no selections other than exclusions every day is selected, and with `tags` only the days with
+/// one of them are. Fails if a selection or tag doesn't match any day, as it's most likely a typo.
+pub fn resolve<'a>(
+    selections: &[Selection],
+    tags: &[String],
+    days: &'a [Day],
+) -> Result<Vec<(&'a Day, Option<u8>)>, SelectError> {
+    if let Some(selection) = selections
+        .iter()
+        .find(|s| !days.iter().any(|day| s.covers(day)))
+    {
+        return Err(SelectError::Unregistered(*selection));
+    }
+    if let Some(tag) = tags
+        .iter()
+        .find(|&tag| !days.iter().any(|day| day.tags.contains(&tag.as_str())))
+    {
+        return Err(SelectError::UnknownTag(tag.clone()));
+    }
+
+    let (excluded, included): (Vec<&Selection>, Vec<_>) =
+        selections.iter().partition(|s| s.exclude);
+    let selected = |day: &Day, part| {
+        (included.is_empty() || included.iter().any(|s| s.matches(day, part)))
+            && !excluded.iter().any(|s| s.matches(day, part))
+    };
+    Ok(days
+        .iter()
+        .filter(|day| tags.is_empty() || tags.iter().any(|t| day.tags.contains(&t.as_str())))
+        .filter_map(|day| match (selected(day, 1), selected(day, 2)) {
+            (true, true) => Some((day, None)),
+            (true, false) => Some((day, Some(1))),
+            (false, true) => Some((day, Some(2))),
+            (false, false) => None,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use advent_of_code_2024::days::DAYS;
+
+    use super::*;
+
+    fn parse(s: &str) -> Selection {
+        Selection::parse(s).unwrap()
+    }
+
+    #[test]
+    fn selections() {
+        let days = |year, first, last| Selection {
+            year,
+            days: Some((first, last)),
+            part: None,
+            exclude: false,
+        };
+        assert_eq!(parse("12"), days(registry::DEFAULT_YEAR, 12, 12));
+        assert_eq!(parse("d05"), days(registry::DEFAULT_YEAR, 5, 5));
+        assert_eq!(parse("2023/5"), days(2023, 5, 5));
+        assert_eq!(parse("2023/d05"), days(2023, 5, 5));
+        assert_eq!(parse("1-10"), days(registry::DEFAULT_YEAR, 1, 10));
+        assert_eq!(parse("2023/d01-d10"), days(2023, 1, 10));
+        assert_eq!(
+            parse("2023/*"),
+            Selection {
+                days: None,
+                ..days(2023, 1, 1)
+            }
+        );
+        assert_eq!(
+            parse("17.2"),
+            Selection {
+                part: Some(2),
+                ..days(registry::DEFAULT_YEAR, 17, 17)
+            }
+        );
+        assert_eq!(
+            parse("!2023/d14.1"),
+            Selection {
+                part: Some(1),
+                exclude: true,
+                ..days(2023, 14, 14)
+            }
+        );
+        for invalid in [
+            "0", "26", "twelve", "*", "23/5", "2023/", "2023/26", "2023/5/1", "10-1", "1-", "17.3",
+            "17.", "!!5", "5!",
+        ] {
+            assert!(Selection::parse(invalid).is_err(), "{}", invalid);
+        }
+
+        assert_eq!(parse_day("2023/5"), Ok((2023, 5)));
+        for invalid in ["2023/*", "1-2", "17.2", "!5"] {
+            assert!(parse_day(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn names_selections_like_the_registry() {
+        for s in [
+            "d05",
+            "2023/d05",
+            "2023/*",
+            "!d01-d10",
+            "d17.2",
+            "!2023/d05.1",
+        ] {
+            assert_eq!(parse(s).to_string(), s);
+        }
+    }
+
+    #[test]
+    fn resolves_days_and_parts() {
+        let resolve = |selections: &[&str], tags: &[&str]| {
+            let selections: Vec<_> = selections.iter().map(|s| parse(s)).collect();
+            let tags: Vec<_> = tags.iter().map(|t| t.to_string()).collect();
+            resolve(&selections, &tags, &DAYS).map(|days| {
+                days.iter()
+                    .map(|(day, part)| match part {
+                        Some(part) => format!("{}.{}", day.name, part),
+                        None => day.name.clone(),
+                    })
+                    .collect::<Vec<_>>()
+            })
+        };
+
+        // d12 isn't registered, but the range is still fine
+        assert_eq!(
+            resolve(&["10-14", "!d13", "17.2"], &[]).unwrap(),
+            ["d10", "d11", "d14", "d17.2"]
+        );
+        assert_eq!(
+            resolve(&["!1-23", "!d25.1"], &[]).unwrap(),
+            ["d24", "d25.2"]
+        );
+        assert_eq!(resolve(&["1-10"], &["pathfinding"]).unwrap(), ["d10"]);
+        assert_eq!(resolve(&[], &[]).unwrap().len(), DAYS.len());
+
+        assert_eq!(
+            resolve(&["d12"], &[]).unwrap_err().to_string(),
+            "d12 has no registered solution, see --list"
+        );
+        assert!(matches!(
+            resolve(&["!2015/*"], &[]),
+            Err(SelectError::Unregistered(_))
+        ));
+        assert_eq!(
+            resolve(&[], &["gird"]),
+            Err(SelectError::UnknownTag("gird".to_string()))
+        );
+    }
+}
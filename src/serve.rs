@@ -0,0 +1,435 @@
+//! Solving inputs sent over a local socket, for tools that would rather not start the binary for
+//! every input. Clients send requests as JSON, one per line, and get a line of JSON back for each:
+//!
+//! ```text
+//! > {"day": 11, "part": 1, "input": "125 17", "params": {"part1_blinks": 6}}
+//! < {"day":"d11","implementation":"default","part":1,"answer":"22","parse_ns":2810,"duration_ns":9870}
+//! > {"day": 99, "part": 1, "input": ""}
+//! < {"error":"d99 has no registered solution"}
+//! ```
+//!
+//! Each connection is served on a thread of its own, so requests on different connections are
+//! solved at once. Parsing and the part may each take up to the timeout, after which the request
+//! fails and the solve is cancelled. A solve that doesn't notice it has been cancelled keeps running
+//! in the background. Responses say how many of those there are as `abandoned`, and no more
+//! requests are taken while there are [`MAX_ABANDONED`] of them.
+
+use std::{
+    collections::BTreeMap,
+    fmt,
+    io::{self, BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    thread,
+    time::Duration,
+};
+
+use advent_of_code_2024::days::{day_name, find_day, registry, Day, InputSource, Params};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    report,
+    runner::{self, Abandoned, Config, Repetitions, Task},
+    select,
+};
+
+pub const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+
+pub const DEFAULT_TIMEOUT: &str = "10s";
+
+/// How many timed out solves may still be running before requests are refused
+pub const MAX_ABANDONED: usize = 4;
+
+/// A day as a number or a name, e.g. `5`, `"d05"` or `"2023/5"`, as on the command line
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum DayName {
+    Number(u8),
+    Name(String),
+}
+
+impl DayName {
+    fn parse(&self) -> Result<(u16, u8), select::SelectError> {
+        match self {
+            DayName::Number(n) => Ok((registry::DEFAULT_YEAR, *n)),
+            DayName::Name(name) => select::parse_day(name),
+        }
+    }
+}
+
+/// A parameter's value, as it would be given to `--param`
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ParamValue {
+    Number(u64),
+    Text(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Request {
+    day: DayName,
+    part: u8,
+    input: String,
+    /// The day's default implementation if not given
+    #[serde(default)]
+    implementation: Option<String>,
+    #[serde(default)]
+    params: BTreeMap<String, ParamValue>,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct Response {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    day: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    implementation: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    part: Option<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    answer: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    parse_ns: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    duration_ns: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    /// How many timed out solves are still running, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    abandoned: Option<usize>,
+}
+
+impl Response {
+    fn error(error: impl fmt::Display) -> Self {
+        Response {
+            error: Some(error.to_string()),
+            ..Response::default()
+        }
+    }
+}
+
+fn solve(request: Request, timeout: Duration, abandoned: &Abandoned) -> Response {
+    let (year, number) = match request.day.parse() {
+        Ok(day) => day,
+        Err(e) => return Response::error(e),
+    };
+    let Some(day) = find_day(year, number) else {
+        return Response::error(format_args!(
+            "{} has no registered solution",
+            day_name(year, number)
+        ));
+    };
+    let mut response = match abandoned.count() {
+        n if n >= MAX_ABANDONED => Response::error(format_args!(
+            "{} timed out solves are still running, try again later",
+            n
+        )),
+        _ => solve_day(day, request, timeout, abandoned),
+    };
+    response.abandoned = Some(abandoned.count()).filter(|&n| n > 0);
+    response
+}
+
+fn solve_day(
+    day: &'static Day,
+    request: Request,
+    timeout: Duration,
+    abandoned: &Abandoned,
+) -> Response {
+    let implementation = match &request.implementation {
+        None => day.default_implementation(),
+        Some(name) => match day.implementation(name) {
+            Some(implementation) => implementation,
+            None => {
+                return Response::error(format_args!("{} has no implementation {}", day.name, name))
+            }
+        },
+    };
+    if !(1..=2).contains(&request.part) {
+        return Response::error(format_args!("there is no part {}", request.part));
+    }
+
+    // Checked here, so that the runner can take them as overrides
+    let mut overrides = Vec::new();
+    let mut params = Params::defaults(implementation.params);
+    for (name, value) in &request.params {
+        let value = match value {
+            ParamValue::Number(n) => n.to_string(),
+            ParamValue::Text(s) => s.clone(),
+        };
+        if let Err(e) = params.set_str(name, &value) {
+            return Response::error(e);
+        }
+        overrides.push((name.clone(), value));
+    }
+
+    let task = Task {
+        day,
+        implementation,
+        example: None,
+        part: Some(request.part),
+    };
+    let config = Config {
+        source: InputSource::Text(request.input),
+        reps: Repetitions {
+            warmup: 0,
+            repeat: 1,
+        },
+        overrides,
+        timeout: Some(timeout),
+        visualize: None,
+    };
+    let result = match runner::run_task_with_timeout(task, &config, timeout, abandoned) {
+        Ok(result) => result,
+        Err(e) => return Response::error(e),
+    };
+
+    let mut response = Response {
+        day: Some(day.name.clone()),
+        implementation: Some(implementation.name.to_string()),
+        part: Some(request.part),
+        ..Response::default()
+    };
+    response.parse_ns = result.parse.samples.first().map(|&t| report::nanos(t));
+    if let Err(e) = result.parse.result {
+        response.error = Some(e.to_string());
+        return response;
+    }
+    let (_, part) = &result.parts[0];
+    response.duration_ns = part.samples.first().map(|&t| report::nanos(t));
+    match &part.result {
+        Ok(answer) => response.answer = Some(answer.to_string()),
+        Err(e) => response.error = Some(e.to_string()),
+    }
+    response
+}
+
+/// Answer each request read from `reader` until the client hangs up, giving up on any that take
+/// longer than `timeout`
+fn handle(
+    reader: impl Read,
+    mut writer: impl Write,
+    timeout: Duration,
+    abandoned: &Abandoned,
+) -> io::Result<()> {
+    for line in BufReader::new(reader).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let response = match serde_json::from_str(&line) {
+            Ok(request) => solve(request, timeout, abandoned),
+            Err(e) => Response::error(format_args!("invalid request: {}", e)),
+        };
+        let json = serde_json::to_string(&response).expect("responses can always be serialised");
+        writeln!(writer, "{}", json)?;
+        writer.flush()?;
+    }
+    Ok(())
+}
+
+/// A connection that can be read from on one handle while being written to on another
+pub trait Stream: Read + Write + Send + Sized + 'static {
+    fn try_clone(&self) -> io::Result<Self>;
+}
+
+impl Stream for TcpStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        TcpStream::try_clone(self)
+    }
+}
+
+#[cfg(unix)]
+impl Stream for std::os::unix::net::UnixStream {
+    fn try_clone(&self) -> io::Result<Self> {
+        std::os::unix::net::UnixStream::try_clone(self)
+    }
+}
+
+/// Listen on the Unix socket at `path`, replacing one left behind by a server that has stopped. The
+/// socket is removed again when the server is interrupted or terminated.
+#[cfg(unix)]
+pub fn bind_unix(path: &std::path::Path) -> io::Result<std::os::unix::net::UnixListener> {
+    use std::{
+        fs,
+        os::unix::{
+            fs::FileTypeExt,
+            net::{UnixListener, UnixStream},
+        },
+        process,
+    };
+
+    // Nothing answers on a stale socket, while a live one is left for binding to fail on
+    let is_socket = fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_socket());
+    if is_socket && UnixStream::connect(path).is_err() {
+        fs::remove_file(path)?;
+    }
+    let listener = UnixListener::bind(path)?;
+
+    let path = path.to_path_buf();
+    ctrlc::set_handler(move || {
+        let _ = fs::remove_file(&path);
+        process::exit(0);
+    })
+    .map_err(io::Error::other)?;
+    Ok(listener)
+}
+
+/// Serve every connection from `incoming` on a thread of its own. Only fails if the listener does.
+pub fn serve<S: Stream>(
+    incoming: impl Iterator<Item = io::Result<S>>,
+    timeout: Duration,
+) -> io::Result<()> {
+    // Shared by every connection, so that clients can't get around the limit by reconnecting
+    let abandoned = Abandoned::default();
+    for stream in incoming {
+        let stream = stream?;
+        let abandoned = abandoned.clone();
+        thread::spawn(move || {
+            let result = stream
+                .try_clone()
+                .and_then(|reader| handle(reader, stream, timeout, &abandoned));
+            if let Err(e) = result {
+                eprintln!("connection failed: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use advent_of_code_2024::days::{registry::Implementation, Answer, ParseError, Solution};
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    fn exchange(requests: &str, timeout: Duration) -> Vec<serde_json::Value> {
+        exchange_with(requests, timeout, &Abandoned::default())
+    }
+
+    fn exchange_with(
+        requests: &str,
+        timeout: Duration,
+        abandoned: &Abandoned,
+    ) -> Vec<serde_json::Value> {
+        let mut out = Vec::new();
+        handle(requests.as_bytes(), &mut out, timeout, abandoned).unwrap();
+        String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|l| serde_json::from_str(l).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn answers_requests() {
+        let responses = exchange(
+            concat!(
+                r#"{"day": 11, "part": 1, "input": "125 17", "params": {"part1_blinks": 6}}"#,
+                "\n\n",
+                r#"{"day": "d11", "part": 3, "input": "125 17"}"#,
+                "\n",
+                r#"{"day": 11, "part": 1, "input": "125 17", "params": {"blinks": 6}}"#,
+                "\n",
+                r#"{"day": 11}"#,
+                "\n",
+            ),
+            Duration::from_secs(10),
+        );
+        assert_eq!(responses[0]["answer"], "22");
+        assert_eq!(responses[0]["day"], "d11");
+        assert!(responses[0]["duration_ns"].is_u64());
+        assert_eq!(responses[1]["error"], "there is no part 3");
+        assert_eq!(responses[2]["error"], r#"unknown parameter "blinks""#);
+        assert!(responses[3]["error"]
+            .as_str()
+            .unwrap()
+            .starts_with("invalid request: missing field `part`"));
+    }
+
+    #[test]
+    fn gives_up_on_slow_requests() {
+        // A program that jumps back to its start forever
+        let responses = exchange(
+            r#"{"day": 17, "part": 1, "input": "Register A: 1\nRegister B: 0\nRegister C: 0\n\nProgram: 3,0"}"#,
+            Duration::from_millis(100),
+        );
+        assert_eq!(responses[0]["error"], "timed out after 100.000ms");
+        assert!(responses[0]["parse_ns"].is_u64());
+    }
+
+    /// Set once the test using [`Stubborn`] is done with it
+    static RELEASE: AtomicBool = AtomicBool::new(false);
+
+    /// A day whose parts ignore cancellation, as none of the registered ones should
+    struct Stubborn;
+
+    impl Solution for Stubborn {
+        type Parsed<'a> = ();
+
+        fn parse(_: &str) -> Result<(), ParseError> {
+            Ok(())
+        }
+
+        fn part1(_: &(), _: &Params) -> Result<Answer, ParseError> {
+            while !RELEASE.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(1));
+            }
+            Ok(Answer::NotImplemented)
+        }
+
+        fn part2(parsed: &(), params: &Params) -> Result<Answer, ParseError> {
+            Self::part1(parsed, params)
+        }
+    }
+
+    #[test]
+    fn refuses_requests_while_too_many_solves_are_abandoned() {
+        static IMPLEMENTATIONS: [Implementation; 1] = [Implementation::of::<Stubborn>("default")];
+        let day = Box::leak(Box::new(Day {
+            name: "d00".to_string(),
+            year: registry::DEFAULT_YEAR,
+            number: 0,
+            title: "Stubborn",
+            tags: &[],
+            implementations: &IMPLEMENTATIONS,
+            examples: &[],
+        }));
+        let abandoned = Abandoned::default();
+        for n in 1..=MAX_ABANDONED {
+            let request = Request {
+                day: DayName::Number(0),
+                part: 1,
+                input: String::new(),
+                implementation: None,
+                params: BTreeMap::new(),
+            };
+            let response = solve_day(day, request, Duration::from_millis(10), &abandoned);
+            assert_eq!(response.error.unwrap(), "timed out after 10.000ms");
+            assert_eq!(abandoned.count(), n);
+        }
+
+        let responses = exchange_with(
+            r#"{"day": 11, "part": 1, "input": "125 17"}"#,
+            Duration::from_secs(10),
+            &abandoned,
+        );
+        assert_eq!(
+            responses[0]["error"],
+            format!(
+                "{} timed out solves are still running, try again later",
+                MAX_ABANDONED
+            )
+        );
+        assert_eq!(responses[0]["abandoned"], MAX_ABANDONED);
+
+        // The abandoned threads are no longer counted once they finish
+        RELEASE.store(true, Ordering::SeqCst);
+        for _ in 0..1000 {
+            if abandoned.count() == 0 {
+                break;
+            }
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(abandoned.count(), 0);
+    }
+}
@@ -0,0 +1,333 @@
+//! Talking to the Advent of Code site, for the `fetch` and `submit` subcommands. Both need the
+//! session cookie of a logged in browser. The base URL can be changed, so that they can be tried
+//! against a local server.
+
+use std::{
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    process::Command,
+    time::Duration,
+};
+
+use advent_of_code_2024::days::{crypt, input};
+use hmac::{Hmac, Mac};
+use regex::Regex;
+use sha2::Sha256;
+
+pub const DEFAULT_BASE_URL: &str = "https://adventofcode.com";
+
+#[derive(Debug)]
+pub enum SiteError {
+    /// The request didn't get a response
+    Http(String),
+    /// The site answered with an error, e.g. 400 for a bad session or 404 for a day that isn't out
+    Status(u16, String),
+    /// The response to a submission didn't say whether the answer was right
+    Unrecognised(String),
+    /// There was already an input, which is never overwritten unless asked
+    Exists(PathBuf),
+    Io(PathBuf, io::Error),
+}
+
+impl fmt::Display for SiteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SiteError::Http(e) => write!(f, "request failed: {}", e),
+            SiteError::Status(status, body) => write!(f, "the site said {}: {}", status, body),
+            SiteError::Unrecognised(text) => write!(f, "unrecognised response: {}", text),
+            SiteError::Exists(path) => {
+                write!(
+                    f,
+                    "{} already exists, use --force to replace it",
+                    path.display()
+                )
+            }
+            SiteError::Io(path, e) => write!(f, "failed to write {}: {}", path.display(), e),
+        }
+    }
+}
+
+impl std::error::Error for SiteError {}
+
+impl From<ureq::Error> for SiteError {
+    fn from(e: ureq::Error) -> Self {
+        match e {
+            ureq::Error::Status(status, response) => {
+                let body = response.into_string().unwrap_or_default();
+                SiteError::Status(status, body.trim().lines().next().unwrap_or("").to_string())
+            }
+            ureq::Error::Transport(e) => SiteError::Http(e.to_string()),
+        }
+    }
+}
+
+/// What the site made of a submitted answer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Correct,
+    /// Wrong, with whether it was too high or too low if the site said
+    Incorrect {
+        hint: Option<String>,
+    },
+    /// Answers can't be submitted again straight after a wrong one
+    TooSoon {
+        wait: Option<String>,
+    },
+    /// The part has already been solved, or its first part hasn't
+    WrongLevel,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Correct => write!(f, "That's the right answer"),
+            Outcome::Incorrect { hint: None } => write!(f, "That's not the right answer"),
+            Outcome::Incorrect { hint: Some(hint) } => {
+                write!(f, "That's not the right answer, it's {}", hint)
+            }
+            Outcome::TooSoon { wait: None } => write!(f, "Answered too recently, wait a while"),
+            Outcome::TooSoon { wait: Some(wait) } => {
+                write!(
+                    f,
+                    "Answered too recently, wait {} before trying again",
+                    wait
+                )
+            }
+            Outcome::WrongLevel => write!(
+                f,
+                "That part can't be answered, it has already been solved or is still locked"
+            ),
+        }
+    }
+}
+
+/// Work out the outcome from the page the site responds with
+fn outcome(html: &str) -> Result<Outcome, SiteError> {
+    let text = match (html.find("<article>"), html.find("</article>")) {
+        (Some(start), Some(end)) if start < end => &html[start..end],
+        _ => html,
+    };
+    let tags = Regex::new("<[^>]*>").unwrap();
+    let text = tags.replace_all(text, "");
+
+    let captured = |pattern: &str| {
+        Regex::new(pattern)
+            .unwrap()
+            .captures(&text)
+            .map(|c| c[1].to_string())
+    };
+    if text.contains("That's the right answer") {
+        Ok(Outcome::Correct)
+    } else if text.contains("That's not the right answer") {
+        Ok(Outcome::Incorrect {
+            hint: captured(r"your answer is (too (?:high|low))"),
+        })
+    } else if text.contains("You gave an answer too recently") {
+        Ok(Outcome::TooSoon {
+            wait: captured(r"You have (.+?) left to wait"),
+        })
+    } else if text.contains("You don't seem to be solving the right level") {
+        Ok(Outcome::WrongLevel)
+    } else {
+        Err(SiteError::Unrecognised(
+            text.split_whitespace().collect::<Vec<_>>().join(" "),
+        ))
+    }
+}
+
+pub struct Client {
+    base_url: String,
+    session: String,
+    agent: ureq::Agent,
+}
+
+impl Client {
+    pub fn new(base_url: &str, session: &str) -> Self {
+        let agent = ureq::AgentBuilder::new()
+            .timeout(Duration::from_secs(30))
+            .user_agent(concat!(
+                env!("CARGO_PKG_NAME"),
+                "/",
+                env!("CARGO_PKG_VERSION")
+            ))
+            .build();
+        Client {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            session: session.to_string(),
+            agent,
+        }
+    }
+
+    fn url(&self, year: u16, day: u8, page: &str) -> String {
+        format!("{}/{}/day/{}/{}", self.base_url, year, day, page)
+    }
+
+    fn cookie(&self) -> String {
+        format!("session={}", self.session)
+    }
+
+    pub fn input(&self, year: u16, day: u8) -> Result<String, SiteError> {
+        let response = self
+            .agent
+            .get(&self.url(year, day, "input"))
+            .set("Cookie", &self.cookie())
+            .call()?;
+        response
+            .into_string()
+            .map_err(|e| SiteError::Http(e.to_string()))
+    }
+
+    pub fn submit(&self, year: u16, day: u8, part: u8, answer: &str) -> Result<Outcome, SiteError> {
+        let response = self
+            .agent
+            .post(&self.url(year, day, "answer"))
+            .set("Cookie", &self.cookie())
+            .send_form(&[("level", &part.to_string()), ("answer", answer)])?;
+        let html = response
+            .into_string()
+            .map_err(|e| SiteError::Http(e.to_string()))?;
+        outcome(&html)
+    }
+}
+
+/// How a fetched input was stored
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stored {
+    /// In plain text, for transcrypt to encrypt when it's committed
+    Transcrypt,
+    /// Already encrypted with the key from the environment, since transcrypt isn't set up
+    Encrypted,
+    /// In plain text, with nothing to encrypt it
+    Plain,
+}
+
+/// The trimmed output of git run in `dir`, if it succeeds
+fn git(dir: &Path, args: &[&str]) -> Option<String> {
+    Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Whether git will encrypt `path` with transcrypt when it's committed
+fn transcrypt_configured(path: &Path) -> bool {
+    let dir = path.parent().unwrap_or(Path::new("."));
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    let attribute = git(dir, &["check-attr", "filter", "--", &file_name]);
+    attribute.is_some_and(|a| a.ends_with(": filter: crypt"))
+        && git(dir, &["config", "--get", "filter.crypt.clean"]).is_some_and(|c| !c.is_empty())
+}
+
+/// The salt transcrypt encrypts `content` with, which comes from the content so that encrypting
+/// it again changes nothing. It's the last 8 bytes of an HMAC-SHA256 of the content, keyed by
+/// `filename:password` where `filename` is the file's path in the repository.
+fn transcrypt_salt(filename: &str, password: &str, content: &str) -> [u8; 8] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(format!("{}:{}", filename, password).as_bytes())
+        .expect("HMAC takes keys of any length");
+    mac.update(content.as_bytes());
+    mac.finalize().into_bytes()[24..].try_into().unwrap()
+}
+
+/// Write a fetched input for day `day` of `year` to the data directory. Inputs mustn't be
+/// published, so it's encrypted if transcrypt won't do it on commit and there is a key to do it
+/// with. An empty file, as left by `new`, is replaced, but an input is only replaced if `force` is
+/// set.
+pub fn save_input(
+    data_dir: &Path,
+    year: u16,
+    day: u8,
+    input: &str,
+    force: bool,
+) -> Result<(PathBuf, Stored), SiteError> {
+    let path = data_dir.join(input::relative_path(year, day));
+    let existing = fs::metadata(&path).map_or(0, |m| m.len());
+    if existing > 0 && !force {
+        return Err(SiteError::Exists(path));
+    }
+
+    let dir = path.parent().unwrap_or(data_dir);
+    fs::create_dir_all(dir).map_err(|e| SiteError::Io(dir.to_path_buf(), e))?;
+    let (content, stored) = if transcrypt_configured(&path) {
+        (input.to_string(), Stored::Transcrypt)
+    } else if let Ok(key) = crypt::key_from_env() {
+        // The same as transcrypt would commit, so that setting it up later changes nothing. Out of
+        // a repository the data directory is taken to be `data/` at its root.
+        let prefix = git(dir, &["rev-parse", "--show-prefix"]);
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let filename = match prefix {
+            Some(prefix) => format!("{}{}", prefix, file_name),
+            None => Path::new("data")
+                .join(input::relative_path(year, day))
+                .to_string_lossy()
+                .into_owned(),
+        };
+        let salt = transcrypt_salt(&filename, &key, input);
+        (crypt::encrypt(input, &key, salt), Stored::Encrypted)
+    } else {
+        (input.to_string(), Stored::Plain)
+    };
+
+    fs::write(&path, content).map_err(|e| SiteError::Io(path.clone(), e))?;
+    Ok((path, stored))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(article: &str) -> String {
+        format!(
+            "<html><body><main><article><p>{}</p></article></main></body></html>",
+            article
+        )
+    }
+
+    #[test]
+    fn reads_outcomes() {
+        let outcomes = [
+            (
+                "That's the right answer! You are <em>one gold star</em> closer.",
+                Outcome::Correct,
+            ),
+            (
+                "That's not the right answer; your answer is too high.  If you're stuck...",
+                Outcome::Incorrect {
+                    hint: Some("too high".to_string()),
+                },
+            ),
+            (
+                "That's not the right answer.  If you're stuck...",
+                Outcome::Incorrect { hint: None },
+            ),
+            (
+                "You gave an answer too recently; you have to wait after submitting an answer \
+                 before trying again.  You have 37s left to wait.",
+                Outcome::TooSoon {
+                    wait: Some("37s".to_string()),
+                },
+            ),
+            (
+                "You don't seem to be solving the right level.  Did you already complete it?",
+                Outcome::WrongLevel,
+            ),
+        ];
+        for (article, expected) in outcomes {
+            assert_eq!(outcome(&page(article)).unwrap(), expected);
+        }
+        assert!(matches!(
+            outcome(&page("Something <b>else</b>")),
+            Err(SiteError::Unrecognised(text)) if text == "Something else"
+        ));
+    }
+
+    #[test]
+    fn salts_like_transcrypt() {
+        // From `openssl dgst -hmac "data/2024/d05.txt:correct horse" -sha256`, as transcrypt runs it
+        let salt = transcrypt_salt("data/2024/d05.txt", "correct horse", "1,2\n3,4\n");
+        assert_eq!(salt, [0x22, 0xc0, 0xf0, 0x55, 0x72, 0x34, 0xf3, 0xc1]);
+    }
+}
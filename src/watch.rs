@@ -0,0 +1,185 @@
+//! Watching input files by polling their modification times and sizes, so that days can be solved
+//! again as soon as their inputs are edited, and comparing the answers with the previous run's.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Write,
+    fs,
+    path::{Path, PathBuf},
+    thread,
+    time::{Duration, SystemTime},
+};
+
+use crate::report::{table_answer, PartReport};
+
+pub const DEFAULT_INTERVAL: &str = "500ms";
+
+/// When a file was last modified and how big it was, or nothing if it doesn't exist
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &Path) -> Stamp {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+/// A set of files and how they looked when they were last polled
+pub struct Watcher {
+    files: Vec<(PathBuf, Stamp)>,
+}
+
+impl Watcher {
+    pub fn new(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        let mut paths: Vec<_> = paths.into_iter().collect();
+        paths.sort();
+        paths.dedup();
+        let files = paths
+            .into_iter()
+            .map(|path| {
+                let stamp = stamp(&path);
+                (path, stamp)
+            })
+            .collect();
+        Watcher { files }
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    /// The files that changed since the last poll, including any that were created or removed
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        for (path, last) in &mut self.files {
+            let now = stamp(path);
+            if now != *last {
+                *last = now;
+                changed.push(path.clone());
+            }
+        }
+        changed
+    }
+
+    /// Wait until some files change, and then until they stop changing, since editors often save
+    /// in more than one write
+    pub fn wait(&mut self, interval: Duration) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        while changed.is_empty() {
+            thread::sleep(interval);
+            changed = self.poll();
+        }
+        loop {
+            thread::sleep(interval);
+            let more = self.poll();
+            if more.is_empty() {
+                break;
+            }
+            changed.extend(more);
+        }
+        changed.sort();
+        changed.dedup();
+        changed
+    }
+}
+
+fn label(p: &PartReport) -> String {
+    let mut label = p.day.clone();
+    if p.implementation != "default" {
+        write!(label, "/{}", p.implementation).unwrap();
+    }
+    if let Some(i) = p.example {
+        write!(label, " example {}", i).unwrap();
+    }
+    write!(label, " part {}", p.part).unwrap();
+    label
+}
+
+/// A line for each part whose answer differs from the previous run's, for people
+pub fn diff(previous: &[PartReport], current: &[PartReport]) -> String {
+    let answers = |parts: &[PartReport]| -> BTreeMap<_, _> {
+        parts
+            .iter()
+            .filter(|p| p.part > 0)
+            .map(|p| {
+                let key = (p.day.clone(), p.implementation.clone(), p.example, p.part);
+                (key, (label(p), table_answer(p)))
+            })
+            .collect()
+    };
+    let (before, after) = (answers(previous), answers(current));
+
+    let mut out = String::new();
+    for (key, (label, answer)) in &after {
+        match before.get(key) {
+            Some((_, old)) if old == answer => {}
+            Some((_, old)) => writeln!(out, "  {}: {} -> {}", label, old, answer).unwrap(),
+            None => writeln!(out, "  {}: {} (new)", label, answer).unwrap(),
+        }
+    }
+    match out.is_empty() {
+        true => "No answers changed since the last run\n".to_string(),
+        false => format!("Changed since the last run:\n{}", out),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::{Status, Timing};
+
+    fn part(day: &str, part: u8, status: Status, answer: Option<&str>) -> PartReport {
+        PartReport {
+            day: day.to_string(),
+            example: None,
+            part,
+            implementation: "default".to_string(),
+            status,
+            answer: answer.map(str::to_string),
+            expected: None,
+            duration_ns: 0,
+            error: None,
+            timing: Timing::default(),
+            allocations: None,
+        }
+    }
+
+    #[test]
+    fn diffs_answers() {
+        let previous = [
+            part("d01", 0, Status::Ok, None),
+            part("d01", 1, Status::Ok, Some("11")),
+            part("d01", 2, Status::Error, None),
+        ];
+        let mut current = [
+            part("d01", 0, Status::Ok, None),
+            part("d01", 1, Status::Ok, Some("11")),
+            part("d01", 2, Status::Ok, Some("31")),
+        ];
+        assert_eq!(
+            diff(&previous, &current),
+            "Changed since the last run:\n  d01 part 2: FAILED -> 31\n"
+        );
+        current[2] = part("d01", 2, Status::Error, None);
+        assert_eq!(
+            diff(&previous, &current),
+            "No answers changed since the last run\n"
+        );
+    }
+
+    #[test]
+    fn notices_changed_files() {
+        let dir = std::env::temp_dir().join(format!("watch-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let (input, missing) = (dir.join("d01.txt"), dir.join("d02.txt"));
+        fs::write(&input, "3   4\n").unwrap();
+
+        let mut watcher = Watcher::new([input.clone(), missing.clone(), input.clone()]);
+        assert_eq!(watcher.len(), 2);
+        assert!(watcher.poll().is_empty());
+
+        fs::write(&input, "3   4\n4   3\n").unwrap();
+        fs::write(&missing, "").unwrap();
+        assert_eq!(watcher.poll(), [input, missing]);
+        assert!(watcher.poll().is_empty());
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -0,0 +1,170 @@
+//! Runs `serve` and talks to it over its sockets like a client would
+
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::TcpStream,
+    process::{Child, Command, Stdio},
+};
+
+use advent_of_code_2024::days::DAYS;
+use serde_json::{json, Value};
+
+/// The server, which is killed when the test ends
+struct Server {
+    child: Child,
+    /// What it said it's listening on
+    address: String,
+}
+
+impl Server {
+    fn start(args: &[&str]) -> Self {
+        let mut child = Command::new(env!("CARGO_BIN_EXE_advent-of-code-2024"))
+            .arg("serve")
+            .args(args)
+            .stderr(Stdio::piped())
+            .spawn()
+            .unwrap();
+        let mut line = String::new();
+        BufReader::new(child.stderr.as_mut().unwrap())
+            .read_line(&mut line)
+            .unwrap();
+        let address = line
+            .trim()
+            .strip_prefix("Listening on ")
+            .unwrap_or_else(|| panic!("unexpected first line {:?}", line))
+            .to_string();
+        Server { child, address }
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Send a request and wait for its response
+fn call(stream: &mut (impl Read + Write), reader: &mut impl BufRead, request: Value) -> Value {
+    writeln!(stream, "{}", request).unwrap();
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    serde_json::from_str(&line).unwrap()
+}
+
+fn connect(server: &Server) -> (TcpStream, BufReader<TcpStream>) {
+    let stream = TcpStream::connect(&server.address).unwrap();
+    let reader = BufReader::new(stream.try_clone().unwrap());
+    (stream, reader)
+}
+
+#[test]
+fn solves_every_example() {
+    let server = Server::start(&["--listen", "127.0.0.1:0"]);
+    let (mut stream, mut reader) = connect(&server);
+
+    for day in DAYS.iter() {
+        for example in day.examples {
+            for part in 1..=2 {
+                let Some(expected) = example.answer(part) else {
+                    continue;
+                };
+                let params: serde_json::Map<_, _> = example
+                    .params
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), json!(value.to_string())))
+                    .collect();
+                let request = json!({
+                    "day": day.name,
+                    "part": part,
+                    "input": example.input.trim(),
+                    "params": params,
+                });
+                let response = call(&mut stream, &mut reader, request);
+                assert_eq!(
+                    response["answer"], expected,
+                    "{} part {}: {}",
+                    day.name, part, response
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn reports_errors() {
+    let server = Server::start(&["--listen", "127.0.0.1:0"]);
+    let (mut stream, mut reader) = connect(&server);
+
+    let response = call(
+        &mut stream,
+        &mut reader,
+        json!({"day": 99, "part": 1, "input": ""}),
+    );
+    assert_eq!(response, json!({"error": "d99 has no registered solution"}));
+
+    // d17 rejects the reserved combo operand, which fails the request but not the server
+    let response = call(
+        &mut stream,
+        &mut reader,
+        json!({"day": 17, "part": 1, "input": "Register A: 0\nRegister B: 0\nRegister C: 0\n\nProgram: 2,7"}),
+    );
+    assert!(response["error"]
+        .as_str()
+        .unwrap()
+        .contains("combo operand 7 is reserved"));
+
+    let response = call(
+        &mut stream,
+        &mut reader,
+        json!({"day": 1, "part": 1, "input": "3   4"}),
+    );
+    assert_eq!(response["answer"], "1");
+}
+
+#[test]
+fn serves_connections_at_once() {
+    let server = Server::start(&["--listen", "127.0.0.1:0"]);
+    // The first client is left in the middle of a request, which mustn't hold up the second
+    let (mut first, _) = connect(&server);
+    write!(first, r#"{{"day": 1, "part": 1, "#).unwrap();
+
+    let (mut second, mut reader) = connect(&server);
+    let response = call(
+        &mut second,
+        &mut reader,
+        json!({"day": "d01", "part": 2, "input": "3   3\n4   3"}),
+    );
+    assert_eq!(response["answer"], "6");
+}
+
+#[cfg(unix)]
+#[test]
+fn listens_on_unix_sockets() {
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    let path = std::env::temp_dir().join(format!("serve-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    // A socket left behind by a server that has stopped is replaced
+    drop(UnixListener::bind(&path).unwrap());
+    let mut server = Server::start(&["--unix", path.to_str().unwrap()]);
+    assert_eq!(server.address, path.display().to_string());
+
+    let mut stream = UnixStream::connect(&path).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let response = call(
+        &mut stream,
+        &mut reader,
+        json!({"day": 1, "part": 1, "input": "3   4"}),
+    );
+    assert_eq!(response["answer"], "1");
+
+    // Stopping the server removes the socket
+    let status = Command::new("kill")
+        .arg(server.child.id().to_string())
+        .status()
+        .unwrap();
+    assert!(status.success());
+    server.child.wait().unwrap();
+    assert!(!path.exists());
+}
@@ -0,0 +1,177 @@
+//! Runs `fetch` and `submit` against a stub of the Advent of Code site
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use advent_of_code_2024::days::crypt;
+use tiny_http::{Method, Response, Server};
+
+const SESSION: &str = "test-session";
+const INPUT: &str = "3   4\n4   3\n2   5\n";
+
+/// The requests the stub has seen, as `METHOD path body`
+type Log = Arc<Mutex<Vec<String>>>;
+
+/// A stub site that serves [`INPUT`] for day 1, and accepts 3 as the answer to its first part.
+/// Returns its base URL.
+fn start_stub() -> (String, Log) {
+    let server = Server::http("127.0.0.1:0").unwrap();
+    let base_url = format!("http://{}", server.server_addr().to_ip().unwrap());
+    let log = Log::default();
+    let seen = Arc::clone(&log);
+    thread::spawn(move || {
+        for mut request in server.incoming_requests() {
+            let mut body = String::new();
+            request.as_reader().read_to_string(&mut body).unwrap();
+            let url = request.url().to_string();
+            seen.lock()
+                .unwrap()
+                .push(format!("{} {} {}", request.method(), url, body));
+
+            let session = format!("session={}", SESSION);
+            let logged_in = request
+                .headers()
+                .iter()
+                .any(|h| h.field.equiv("Cookie") && h.value.as_str() == session);
+            let response = match (request.method(), url.as_str()) {
+                _ if !logged_in => Response::from_string("Please log in").with_status_code(400),
+                (Method::Get, "/2024/day/1/input") => Response::from_string(INPUT),
+                (Method::Post, "/2024/day/1/answer") => {
+                    let article = match body.as_str() {
+                        "level=1&answer=3" => "That's the right answer!",
+                        _ => "That's not the right answer; your answer is too high.",
+                    };
+                    Response::from_string(format!(
+                        "<main><article><p>{}</p></article></main>",
+                        article
+                    ))
+                }
+                _ => Response::from_string("Not found").with_status_code(404),
+            };
+            request.respond(response).unwrap();
+        }
+    });
+    (base_url, log)
+}
+
+fn data_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("site-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+fn run(base_url: &str, data_dir: &Path, key: Option<&str>, args: &[&str]) -> Output {
+    let mut command = Command::new(env!("CARGO_BIN_EXE_advent-of-code-2024"));
+    command
+        .args(args)
+        .env("AOC_DATA_DIR", data_dir)
+        .env("AOC_SESSION", SESSION)
+        .env("AOC_BASE_URL", base_url)
+        .env_remove("AOC_DATA_KEY")
+        .env_remove("AOC_DATA_KEY_FILE");
+    if let Some(key) = key {
+        command.env("AOC_DATA_KEY", key);
+    }
+    command.output().unwrap()
+}
+
+#[test]
+fn fetches_inputs() {
+    let (base_url, log) = start_stub();
+    let dir = data_dir("fetch");
+
+    // `new` leaves an empty input behind, which is replaced
+    let input = dir.join("2024/d01.txt");
+    fs::create_dir_all(input.parent().unwrap()).unwrap();
+    fs::write(&input, "").unwrap();
+    let output = run(&base_url, &dir, None, &["fetch", "1"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(fs::read_to_string(&input).unwrap(), INPUT);
+    assert_eq!(log.lock().unwrap()[0], "GET /2024/day/1/input ");
+
+    // But a real one isn't, unless forced
+    let output = run(&base_url, &dir, Some("key"), &["fetch", "d01"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("use --force"));
+
+    // With a key and no transcrypt, the input is encrypted
+    let output = run(&base_url, &dir, Some("key"), &["fetch", "d01", "--force"]);
+    assert!(output.status.success(), "{:?}", output);
+    let saved = fs::read_to_string(&input).unwrap();
+    assert!(crypt::is_encrypted(&saved));
+    assert_eq!(crypt::decrypt(&saved, "key").unwrap(), INPUT);
+
+    let output = run(&base_url, &dir, None, &["fetch", "2"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("404"));
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn submits_answers() {
+    let (base_url, log) = start_stub();
+    let dir = data_dir("submit");
+    let answers = dir.join("answers.toml");
+
+    let output = run(&base_url, &dir, None, &["submit", "1", "1", "4"]);
+    assert!(!output.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout),
+        "That's not the right answer, it's too high\n"
+    );
+    assert!(!answers.exists());
+
+    // Without an answer the day is solved to get one, here from the flat layout of old data
+    // directories
+    fs::write(dir.join("d01.txt"), INPUT).unwrap();
+    let output = run(&base_url, &dir, None, &["submit", "1", "1"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        log.lock().unwrap()[1],
+        "POST /2024/day/1/answer level=1&answer=3"
+    );
+    assert_eq!(fs::read_to_string(&answers).unwrap(), "[d01]\npart1 = 3\n");
+
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn uses_the_data_dir_flag_over_the_environment() {
+    let (base_url, _) = start_stub();
+    let ignored = data_dir("ignored");
+    let dir = data_dir("flag");
+    let dir_arg = dir.to_str().unwrap();
+
+    let output = run(
+        &base_url,
+        &ignored,
+        None,
+        &["fetch", "1", "--data-dir", dir_arg],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(fs::read_to_string(dir.join("2024/d01.txt")).unwrap(), INPUT);
+
+    // The answer comes from the input fetched into the same directory
+    let output = run(
+        &base_url,
+        &ignored,
+        None,
+        &["submit", "1", "1", "--data-dir", dir_arg],
+    );
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        fs::read_to_string(dir.join("answers.toml")).unwrap(),
+        "[d01]\npart1 = 3\n"
+    );
+    assert_eq!(fs::read_dir(&ignored).unwrap().count(), 0);
+
+    fs::remove_dir_all(dir).unwrap();
+    fs::remove_dir_all(ignored).unwrap();
+}